@@ -645,6 +645,15 @@ impl Server {
             })
             .add_request_handler(user_handler(
                 forward_read_only_project_request::<proto::GetSignatureHelp>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::LspExtHoverRange>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSymbolKind>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetRawHover>,
             ));
 
         Arc::new(server)
@@ -948,12 +948,14 @@ fn random_diagnostic(
         diagnostic: Diagnostic {
             source: None, // (optional) service that created the diagnostic
             code: None,   // (optional) machine-readable code that identifies the diagnostic
+            code_description: None,
             severity,
             message,
             group_id,
             is_primary,
             is_disk_based: false,
             is_unnecessary: false,
+            related_locations: Vec::new(),
         },
     }
 }
@@ -126,6 +126,11 @@ pub struct ExpandExcerptsDown {
     #[serde(default)]
     pub(super) lines: u32,
 }
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct OpenHoverLink {
+    pub url: String,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct ShowCompletions {
     #[serde(default)]
@@ -147,6 +152,7 @@ impl_actions!(
         MoveToBeginningOfLine,
         MoveToEndOfLine,
         MoveUpByLines,
+        OpenHoverLink,
         SelectDownByLines,
         SelectNext,
         SelectPrevious,
@@ -168,6 +174,7 @@ gpui::actions!(
         AcceptPartialInlineCompletion,
         AddSelectionAbove,
         AddSelectionBelow,
+        ApplyHoverQuickFix,
         Backspace,
         Cancel,
         CancelLanguageServerWork,
@@ -221,6 +228,12 @@ gpui::actions!(
         HalfPageDown,
         HalfPageUp,
         Hover,
+        HoverNextLink,
+        HoverNextSymbol,
+        HoverPrevLink,
+        HoverPrevSymbol,
+        HoverScrollToBottom,
+        HoverScrollToTop,
         Indent,
         JoinLines,
         LineDown,
@@ -247,12 +260,14 @@ gpui::actions!(
         NextScreen,
         OpenExcerpts,
         OpenExcerptsSplit,
+        OpenFocusedHoverLink,
         OpenPermalinkToLine,
         OpenUrl,
         Outdent,
         PageDown,
         PageUp,
         Paste,
+        PinHover,
         PreviousInlineCompletion,
         Redo,
         RedoSelection,
@@ -286,6 +301,7 @@ gpui::actions!(
         SelectPageUp,
         ShowCharacterPalette,
         ShowInlineCompletion,
+        ShowLastHover,
         ShowSignatureHelp,
         ShuffleLines,
         SortLinesCaseInsensitive,
@@ -297,6 +313,7 @@ gpui::actions!(
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleSelectionMenu,
+        ToggleHoverPopover,
         ToggleHunkDiff,
         ToggleInlayHints,
         ToggleLineNumbers,
@@ -312,6 +329,9 @@ gpui::actions!(
     ]
 );
 
+#[cfg(debug_assertions)]
+gpui::actions!(editor, [ShowRawHover]);
+
 action_as!(outline, ToggleOutline as Toggle);
 
 action_as!(go_to_line, ToggleGoToLine as Toggle);
@@ -23,6 +23,7 @@ mod git;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
+pub mod hover_status_item;
 mod hunk_diff;
 mod indent_guides;
 mod inlay_hint_cache;
@@ -76,7 +77,7 @@ use gpui::{
     WeakView, WhiteSpace, WindowContext,
 };
 use highlight_matching_bracket::refresh_matching_bracket_highlights;
-use hover_popover::{hide_hover, HoverState};
+use hover_popover::{hide_hover, hide_unpinned_hover, update_hover_on_edit, HoverState};
 use hunk_diff::ExpandedHunks;
 pub(crate) use hunk_diff::HunkToExpand;
 use indent_guides::ActiveIndentGuidesState;
@@ -93,9 +94,9 @@ use language::{
 };
 use language::{point_to_lsp, BufferRow, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
-use task::{ResolvedTask, TaskTemplate, TaskVariables};
+use task::{ResolvedTask, TaskContext, TaskTemplate, TaskVariables};
 
-use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
+use hover_links::{HoverLink, HoveredLinkState, InlayHighlight, RangeInEditor};
 pub use lsp::CompletionContext;
 use lsp::{
     CompletionItemKind, CompletionTriggerKind, DiagnosticSeverity, InsertTextFormat,
@@ -269,6 +270,7 @@ pub fn init_settings(cx: &mut AppContext) {
 
 pub fn init(cx: &mut AppContext) {
     init_settings(cx);
+    hover_popover::init(cx);
 
     workspace::register_project_item::<Editor>(cx);
     workspace::register_followable_item::<Editor>(cx);
@@ -441,6 +443,34 @@ struct RunnableTasks {
     context_range: Range<BufferOffset>,
 }
 
+impl RunnableTasks {
+    fn task_context(
+        &self,
+        project: &Model<Project>,
+        buffer: &Model<Buffer>,
+        buffer_row: u32,
+        cx: &mut AppContext,
+    ) -> Task<Option<TaskContext>> {
+        let position = Point::new(buffer_row, self.column);
+        let range_start = buffer.read(cx).anchor_at(position, Bias::Right);
+        let location = Location {
+            buffer: buffer.clone(),
+            range: range_start..range_start,
+        };
+        // Fill in the environmental variables from the tree-sitter captures
+        let mut captured_task_variables = TaskVariables::default();
+        for (capture_name, value) in self.extra_variables.clone() {
+            captured_task_variables.insert(
+                task::VariableName::Custom(capture_name.into()),
+                value.clone(),
+            );
+        }
+        project.update(cx, |project, cx| {
+            project.task_context_for_location(captured_task_variables, location, cx)
+        })
+    }
+}
+
 #[derive(Clone)]
 struct ResolvedTasks {
     templates: SmallVec<[(TaskSourceKind, ResolvedTask); 1]>,
@@ -1959,6 +1989,13 @@ impl Editor {
             }
         }
 
+        if self.hover_state.visible() {
+            key_context.add("showing_hover");
+            if self.hover_state.keyboard_invoked() {
+                key_context.add("showing_keyboard_hover");
+            }
+        }
+
         for layer in self.keymap_context_layers.values() {
             key_context.extend(layer);
         }
@@ -2370,7 +2407,7 @@ impl Editor {
                 drop(context_menu);
             }
 
-            hide_hover(self, cx);
+            hide_unpinned_hover(self, cx);
 
             if old_cursor_position.to_display_point(&display_map).row()
                 != new_cursor_position.to_display_point(&display_map).row()
@@ -4423,27 +4460,7 @@ impl Editor {
                             .as_ref()
                             .zip(editor.project.clone())
                             .map(|(tasks, project)| {
-                                let position = Point::new(buffer_row, tasks.column);
-                                let range_start = buffer.read(cx).anchor_at(position, Bias::Right);
-                                let location = Location {
-                                    buffer: buffer.clone(),
-                                    range: range_start..range_start,
-                                };
-                                // Fill in the environmental variables from the tree-sitter captures
-                                let mut captured_task_variables = TaskVariables::default();
-                                for (capture_name, value) in tasks.extra_variables.clone() {
-                                    captured_task_variables.insert(
-                                        task::VariableName::Custom(capture_name.into()),
-                                        value.clone(),
-                                    );
-                                }
-                                project.update(cx, |project, cx| {
-                                    project.task_context_for_location(
-                                        captured_task_variables,
-                                        location,
-                                        cx,
-                                    )
-                                })
+                                tasks.task_context(&project, &buffer, buffer_row, cx)
                             });
 
                     Some(cx.spawn(|editor, mut cx| async move {
@@ -5090,6 +5107,7 @@ impl Editor {
         _style: &EditorStyle,
         is_active: bool,
         row: DisplayRow,
+        info: Option<SharedString>,
         cx: &mut ViewContext<Self>,
     ) -> IconButton {
         IconButton::new(("run_indicator", row.0 as usize), ui::IconName::Play)
@@ -5097,6 +5115,9 @@ impl Editor {
             .icon_size(IconSize::XSmall)
             .icon_color(Color::Muted)
             .selected(is_active)
+            .when_some(info, |button, info| {
+                button.tooltip(move |cx| Tooltip::text(info.clone(), cx))
+            })
             .on_click(cx.listener(move |editor, _e, cx| {
                 editor.focus(cx);
                 editor.toggle_code_actions(
@@ -5115,6 +5136,29 @@ impl Editor {
             .map_or(false, |menu| menu.visible())
     }
 
+    /// The range of the symbol highlighted by the visible hover popover, if any.
+    pub fn hovered_symbol_range(&self) -> Option<Range<Anchor>> {
+        self.hover_state
+            .info_popovers
+            .iter()
+            .find_map(|info_popover| match &info_popover.symbol_range {
+                RangeInEditor::Text(range) => Some(range.clone()),
+                RangeInEditor::Inlay(_) => None,
+            })
+    }
+
+    /// Fetches the hover of the symbol at `offset` and renders its popovers without showing
+    /// them in the editor, so that they can be placed anywhere.
+    pub fn build_hover_elements_at(
+        &mut self,
+        offset: usize,
+        max_size: Size<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Option<Vec<AnyElement>>> {
+        let anchor = self.buffer.read(cx).snapshot(cx).anchor_before(offset);
+        hover_popover::build_hover_elements(self, anchor, max_size, cx)
+    }
+
     fn render_context_menu(
         &self,
         cursor_position: DisplayPoint,
@@ -11127,6 +11171,8 @@ impl Editor {
                 self.active_indent_guides_state.dirty = true;
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
+                self.hover_state.last_edit_at = Some(cx.background_executor().now());
+                update_hover_on_edit(self, cx);
                 if self.has_active_inline_completion(cx) {
                     self.update_visible_inline_completion(cx);
                 }
@@ -11239,6 +11285,7 @@ impl Editor {
             )),
             cx,
         );
+        hover_popover::refresh_hover_styles(self, cx);
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
@@ -11647,7 +11694,7 @@ impl Editor {
             blame.update(cx, GitBlame::blur)
         }
         if !self.hover_state.focused(cx) {
-            hide_hover(self, cx);
+            hide_unpinned_hover(self, cx);
         }
 
         self.hide_context_menu(cx);
@@ -12255,6 +12302,8 @@ pub enum EditorEvent {
     TransactionBegun {
         transaction_id: clock::Lamport,
     },
+    HoverLinkClicked(String),
+    HoverChanged,
 }
 
 impl EventEmitter<EditorEvent> for Editor {}
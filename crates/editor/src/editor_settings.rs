@@ -8,6 +8,59 @@ pub struct EditorSettings {
     pub cursor_blink: bool,
     pub current_line_highlight: CurrentLineHighlight,
     pub hover_popover_enabled: bool,
+    pub hover_popover_delay: u64,
+    pub hover_live_update: bool,
+    pub hover_allow_while_typing: bool,
+    pub hover_highlight_empty_range: bool,
+    pub hover_truncation_hint: bool,
+    pub hover_test_status: bool,
+    pub hover_typographic_replacements: bool,
+    pub hover_diagnostic_anchor: HoverDiagnosticAnchor,
+    pub hover_collapse_stack_traces: bool,
+    pub diagnostic_hover_max_lines: u32,
+    pub diagnostic_hover_show_code: bool,
+    pub hover_keyword_docs: bool,
+    pub hover_fade_out_millis: u64,
+    pub hover_show_toc: bool,
+    pub hover_code_line_numbers: bool,
+    pub hover_raw_doc_comment: bool,
+    pub hover_enclosing_modifier: HoverEnclosingModifier,
+    pub hover_preload_code_languages: bool,
+    pub hover_attribute_docs: bool,
+    pub hover_backdrop: bool,
+    pub hover_import_suggestions: bool,
+    pub hover_dependency_source: bool,
+    pub hover_retrigger_cooldown_millis: u64,
+    pub hover_highlight_inline_with_doc_language: bool,
+    pub hover_mouse_enabled: bool,
+    pub hover_fold_generics: bool,
+    pub hover_run_doctests: bool,
+    pub hover_preserve_server_spacing: bool,
+    pub hover_popover_placement: HoverPopoverPlacement,
+    pub hover_stale_warning: bool,
+    pub hover_bracket_match: bool,
+    pub hover_remember_last: bool,
+    pub hover_code_theme: Option<String>,
+    pub hover_popover_layer: HoverPopoverLayer,
+    pub hover_show_symbol_kind: bool,
+    pub hover_prominent_quickfix: bool,
+    pub hover_render_local_images: bool,
+    pub hover_block_underlying_moves: bool,
+    pub hover_regex_explain: bool,
+    pub hover_popover_pointer: bool,
+    pub hover_chain_debounce: bool,
+    pub hover_min_symbol_length: u32,
+    pub hover_code_lens_info: bool,
+    pub hover_text_align: HoverTextAlign,
+    pub hover_cache: bool,
+    pub hover_on_modifier_click: bool,
+    pub hover_paragraph_indent: f32,
+    pub hover_empty_fallback: bool,
+    pub hover_overflow_behavior: HoverOverflowBehavior,
+    pub hover_const_value: bool,
+    pub hover_show_hints: bool,
+    pub hover_highlight_inline_with_buffer_language: bool,
+    pub hover_markup_kind: HoverMarkupKind,
     pub show_completions_on_input: bool,
     pub show_completion_documentation: bool,
     pub completion_documentation_secondary_query_debounce: u64,
@@ -69,6 +122,101 @@ pub enum DoubleClickInMultibuffer {
     Open,
 }
 
+/// Where to position the hover popovers when the hovered point has a diagnostic.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverDiagnosticAnchor {
+    /// Position the popovers at the start of the diagnostic's range.
+    #[default]
+    RangeStart,
+    /// Position the popovers at the point that was hovered.
+    MousePoint,
+}
+
+/// The key which, held while hovering, shows the hover of the expression
+/// enclosing the hovered symbol.
+///
+/// Default: none
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverEnclosingModifier {
+    /// Always hover the innermost symbol.
+    #[default]
+    None,
+    Alt,
+    #[serde(alias = "cmd", alias = "ctrl")]
+    CmdOrCtrl,
+    Shift,
+}
+
+/// Where to show the documentation of hovered symbols.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverPopoverPlacement {
+    /// In a popover next to the hovered symbol.
+    #[default]
+    Popover,
+    /// As the first line of the documentation in the status bar, without a popover.
+    StatusBar,
+}
+
+/// Whether hover popovers are drawn above or below signature help.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverPopoverLayer {
+    /// Draw hover popovers on top of signature help.
+    #[default]
+    AboveSignatureHelp,
+    /// Draw signature help on top of hover popovers.
+    BelowSignatureHelp,
+}
+
+/// How to align the prose of hover popovers.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverTextAlign {
+    /// Align every line to the left edge of the popover.
+    #[default]
+    Left,
+    /// Stretch every wrapped line of a paragraph, except its last, to the width of the popover.
+    Justify,
+}
+
+/// What to scroll when the diagnostic and info popovers together are taller than the
+/// space around the hovered symbol.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HoverOverflowBehavior {
+    /// Shorten the info popovers and scroll them, keeping the diagnostic whole.
+    #[default]
+    ScrollInfo,
+    /// Share the space between the diagnostic and info popovers, scrolling both.
+    ScrollBoth,
+}
+
+/// The format of hover documentation.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HoverMarkupKind {
+    /// Render hover documentation as markdown.
+    #[default]
+    Markdown,
+    /// Show hover documentation as plain text.
+    PlainText,
+}
+
+impl HoverMarkupKind {
+    /// The hover content formats to ask language servers for, the most preferred first.
+    pub fn content_format(self) -> Vec<lsp::MarkupKind> {
+        match self {
+            HoverMarkupKind::Markdown => vec![lsp::MarkupKind::Markdown],
+            HoverMarkupKind::PlainText => {
+                vec![lsp::MarkupKind::PlainText, lsp::MarkupKind::Markdown]
+            }
+        }
+    }
+}
+
 #[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct Jupyter {
@@ -162,6 +310,276 @@ pub struct EditorSettingsContent {
     ///
     /// Default: true
     pub hover_popover_enabled: Option<bool>,
+    /// Time to wait in milliseconds before showing the informational hover box.
+    /// 0 shows it as soon as the language server responds.
+    ///
+    /// Default: 350
+    pub hover_popover_delay: Option<u64>,
+    /// Whether a visible hover popover should re-query the language server
+    /// when the hovered symbol is edited, keeping its contents up to date.
+    ///
+    /// Default: false
+    pub hover_live_update: Option<bool>,
+    /// Whether the `editor::Hover` action may show a popover while text is
+    /// being typed into the editor.
+    ///
+    /// Default: true
+    pub hover_allow_while_typing: Option<bool>,
+    /// Whether to highlight the hovered symbol when the language server
+    /// returns a range for it but no documentation to show.
+    ///
+    /// Default: false
+    pub hover_highlight_empty_range: Option<bool>,
+    /// Whether to show a footer in hover popovers whose content looks like it
+    /// was truncated by the language server.
+    ///
+    /// Default: false
+    pub hover_truncation_hint: Option<bool>,
+    /// Whether hovering a runnable test shows the result of its last run
+    /// instead of the language server's documentation.
+    ///
+    /// Default: false
+    pub hover_test_status: Option<bool>,
+    /// Whether to render straight quotes as curly quotes and `--` as an em dash
+    /// in the prose of hover popovers. Code is never changed.
+    ///
+    /// Default: false
+    pub hover_typographic_replacements: Option<bool>,
+    /// Where to position the hover popovers when the hovered point has a diagnostic.
+    ///
+    /// Default: range_start
+    pub hover_diagnostic_anchor: Option<HoverDiagnosticAnchor>,
+    /// Whether to detect stack traces at the end of diagnostic messages and
+    /// collapse them behind a toggle in the diagnostic popover.
+    ///
+    /// Default: false
+    pub hover_collapse_stack_traces: Option<bool>,
+    /// The number of lines of a diagnostic message to show in the hover popover
+    /// before the rest is hidden behind a toggle. Set to 0 to always show the
+    /// whole message.
+    ///
+    /// Default: 0
+    pub diagnostic_hover_max_lines: Option<u32>,
+    /// Whether to show the code of a diagnostic, like `E0502`, after its message
+    /// in the hover popover. Codes that link to their documentation are always shown.
+    ///
+    /// Default: false
+    pub diagnostic_hover_show_code: Option<bool>,
+    /// Whether to show built-in documentation when hovering a language keyword
+    /// the language server has no hover for.
+    ///
+    /// Default: false
+    pub hover_keyword_docs: Option<bool>,
+    /// Time to fade out hover popovers over when they are dismissed, in milliseconds.
+    /// Set to 0 to remove them immediately. Popovers are always removed immediately
+    /// when the system asks to reduce motion.
+    ///
+    /// Default: 120
+    pub hover_fade_out_millis: Option<u64>,
+    /// Whether to list the headings of hover documentation with several headings
+    /// in a collapsible table of contents.
+    ///
+    /// Default: false
+    pub hover_show_toc: Option<bool>,
+    /// Whether to show line numbers in the code blocks of hover documentation.
+    ///
+    /// Default: false
+    pub hover_code_line_numbers: Option<bool>,
+    /// Whether to show the doc comment above the hovered symbol's definition verbatim,
+    /// in place of the documentation of the language server's hover.
+    ///
+    /// Default: false
+    pub hover_raw_doc_comment: Option<bool>,
+    /// The key which, held while hovering, shows the hover of the expression
+    /// enclosing the hovered symbol instead of the symbol itself. This requires a language
+    /// server that supports hovering ranges, such as rust-analyzer.
+    ///
+    /// Default: none
+    pub hover_enclosing_modifier: Option<HoverEnclosingModifier>,
+    /// Whether to load the languages of hover code blocks that aren't loaded yet
+    /// and highlight the code blocks once they are.
+    ///
+    /// Default: false
+    pub hover_preload_code_languages: Option<bool>,
+    /// Whether to add built-in documentation for common attributes and derived
+    /// traits to their hover.
+    ///
+    /// Default: false
+    pub hover_attribute_docs: Option<bool>,
+    /// Whether to dim the editor behind visible hover popovers. Clicking the
+    /// dimmed editor dismisses the popovers.
+    ///
+    /// Default: false
+    pub hover_backdrop: Option<bool>,
+    /// Whether the diagnostic popover of an unresolved symbol offers the imports
+    /// suggested by the language server's code actions.
+    ///
+    /// Default: false
+    pub hover_import_suggestions: Option<bool>,
+    /// Whether hovers of symbols defined in dependencies offer to view the
+    /// dependency's source in a read-only editor.
+    ///
+    /// Default: false
+    pub hover_dependency_source: Option<bool>,
+    /// Time in milliseconds during which hovering a symbol whose popover was just
+    /// hidden doesn't query its hover again. 0 re-queries immediately.
+    ///
+    /// Default: 0
+    pub hover_retrigger_cooldown_millis: Option<u64>,
+    /// Whether to highlight inline code in hover popovers in the language of the
+    /// last code block before it.
+    ///
+    /// Default: false
+    pub hover_highlight_inline_with_doc_language: Option<bool>,
+    /// Whether hovering with the mouse shows hover popovers. When disabled, they
+    /// are only shown by the `editor::Hover` action.
+    ///
+    /// Default: true
+    pub hover_mouse_enabled: Option<bool>,
+    /// Whether to fold the generic parameters and `where` clause of the signature
+    /// in hover popovers, with a toggle to show them.
+    ///
+    /// Default: false
+    pub hover_fold_generics: Option<bool>,
+    /// Whether hovers of Rust items whose documentation has runnable examples
+    /// offer to run them as doctests and show the result.
+    ///
+    /// Default: false
+    pub hover_run_doctests: Option<bool>,
+    /// Whether to keep the line breaks of plain text hover content, instead of
+    /// joining its lines into paragraphs like markdown does.
+    ///
+    /// Default: false
+    pub hover_preserve_server_spacing: Option<bool>,
+    /// Where to show the documentation of hovered symbols.
+    ///
+    /// Default: popover
+    pub hover_popover_placement: Option<HoverPopoverPlacement>,
+    /// Whether to warn in hover popovers of buffers with unsaved changes that
+    /// a language server which isn't sent document edits may not have taken
+    /// them into account.
+    ///
+    /// Default: false
+    pub hover_stale_warning: Option<bool>,
+    /// Whether hovering a bracket shows the line of its matching bracket,
+    /// instead of the language server's hover.
+    ///
+    /// Default: false
+    pub hover_bracket_match: Option<bool>,
+    /// Whether to remember the last hover of each buffer, so that it can be shown
+    /// again with the `editor::ShowLastHover` action after switching editors.
+    ///
+    /// Default: false
+    pub hover_remember_last: Option<bool>,
+    /// The name of the theme whose syntax colors highlight code in hover popovers.
+    ///
+    /// If this option is not included, the syntax colors of the current theme are used.
+    pub hover_code_theme: Option<String>,
+    /// Whether hover popovers are drawn above or below signature help. Both are
+    /// drawn below the completions menu.
+    ///
+    /// Default: above_signature_help
+    pub hover_popover_layer: Option<HoverPopoverLayer>,
+    /// Whether to show the kind of the hovered symbol, such as function or struct,
+    /// at the top of hover popovers, when the language server lists its document's symbols.
+    ///
+    /// Default: false
+    pub hover_show_symbol_kind: Option<bool>,
+    /// Whether to show the quick fix of a hovered error above its message, where it
+    /// can be applied with the `editor::ApplyHoverQuickFix` action.
+    ///
+    /// Default: false
+    pub hover_prominent_quickfix: Option<bool>,
+    /// Whether to render images linked from hover documentation inline when they are
+    /// small files on the local disk. Other images are shown as links.
+    ///
+    /// Default: false
+    pub hover_render_local_images: Option<bool>,
+    /// Whether mouse moves over hover popovers are kept from reaching the editor
+    /// underneath. When disabled, the editor keeps updating its hover target while
+    /// the mouse is over a popover.
+    ///
+    /// Default: true
+    pub hover_block_underlying_moves: Option<bool>,
+    /// Whether to explain the parts of a hovered regular expression literal in
+    /// plain language.
+    ///
+    /// Default: false
+    pub hover_regex_explain: Option<bool>,
+    /// Whether to draw a small pointer from the hover popover toward the symbol
+    /// it describes.
+    ///
+    /// Default: false
+    pub hover_popover_pointer: Option<bool>,
+    /// Whether to wait for the mouse to settle on a segment of a method chain, like
+    /// `a.b().c()`, before querying its hover, instead of querying each segment the
+    /// mouse sweeps across.
+    ///
+    /// Default: false
+    pub hover_chain_debounce: Option<bool>,
+    /// The minimum number of characters a symbol needs for hovering it with the
+    /// mouse to show its documentation. The `editor::Hover` action shows
+    /// documentation for symbols of any length.
+    ///
+    /// Default: 0
+    pub hover_min_symbol_length: Option<u32>,
+    /// Whether hovering a run indicator in the gutter lists the commands of the
+    /// tasks it runs.
+    ///
+    /// Default: false
+    pub hover_code_lens_info: Option<bool>,
+    /// How to align the prose of hover popovers. Code blocks are always left-aligned.
+    ///
+    /// Default: left
+    pub hover_text_align: Option<HoverTextAlign>,
+    /// Whether to reuse the language server's hover at a position of a buffer until
+    /// the buffer changes, including when hovering it in another excerpt of a multibuffer.
+    ///
+    /// Default: false
+    pub hover_cache: Option<bool>,
+    /// Whether clicking a symbol with the platform's secondary modifier (Cmd on
+    /// macOS, Ctrl elsewhere) shows its hover right away when the click doesn't
+    /// go to a definition, instead of finding all references.
+    ///
+    /// Default: false
+    pub hover_on_modifier_click: Option<bool>,
+    /// The indentation of paragraphs in hover popovers, in rems. Paragraphs in
+    /// lists, headings and code blocks aren't indented.
+    ///
+    /// Default: 0
+    pub hover_paragraph_indent: Option<f32>,
+    /// Whether to show the hovered word and how many times it appears in the
+    /// buffer when there is no other documentation to show for it.
+    ///
+    /// Default: false
+    pub hover_empty_fallback: Option<bool>,
+    /// What to scroll when the diagnostic and info popovers together are taller
+    /// than the space around the hovered symbol.
+    ///
+    /// Default: scroll_info
+    pub hover_overflow_behavior: Option<HoverOverflowBehavior>,
+    /// Whether to show the value of a hovered constant as a `name = value` line,
+    /// from the language server or else from the constant's literal initializer.
+    ///
+    /// Default: false
+    pub hover_const_value: Option<bool>,
+    /// Whether to show the keybinding for pinning hover popovers at their bottom,
+    /// until popovers have been pinned a few times.
+    ///
+    /// Default: false
+    pub hover_show_hints: Option<bool>,
+    /// Whether to highlight inline code in hover popovers in the language of the
+    /// hovered buffer, when no code block before it sets the doc's language.
+    ///
+    /// Default: false
+    pub hover_highlight_inline_with_buffer_language: Option<bool>,
+    /// The format to ask language servers for hover documentation in, and to show it as.
+    /// With `plaintext`, the documentation is shown as is, without parsing its markup.
+    /// Language servers are asked when they start, so they need to be restarted for a
+    /// change to apply to them.
+    ///
+    /// Default: markdown
+    pub hover_markup_kind: Option<HoverMarkupKind>,
 
     /// Whether to pop the completions menu while typing in an editor without
     /// explicitly requesting it.
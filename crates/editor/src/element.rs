@@ -5,14 +5,18 @@ use crate::{
         BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint, TransformBlock,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, HoverPopoverLayer, MultiCursorModifier,
+        ShowScrollbar,
     },
     git::{
         blame::{CommitDetails, GitBlame},
         diff_hunk_to_display, DisplayDiffHunk,
     },
     hover_popover::{
-        self, hover_at, HOVER_POPOVER_GAP, MIN_POPOVER_CHARACTER_WIDTH, MIN_POPOVER_LINE_HEIGHT,
+        self, hover_at, hover_at_with_modifiers, hover_popover_heights, hover_popover_max_width,
+        language_hover_popover_max_width, HoverPopoverHeights, HoverPopoverWidthBounds,
+        HOVER_POPOVER_GAP, HOVER_POPOVER_POINTER_SIZE, MIN_POPOVER_CHARACTER_WIDTH,
+        MIN_POPOVER_LINE_HEIGHT,
     },
     hunk_status,
     items::BufferSearchHighlights,
@@ -28,7 +32,7 @@ use client::ParticipantIndex;
 use collections::{BTreeMap, HashMap};
 use git::{blame::BlameEntry, diff::DiffHunkStatus, Oid};
 use gpui::{
-    anchored, deferred, div, fill, outline, point, px, quad, relative, size, svg,
+    anchored, canvas, deferred, div, fill, outline, point, px, quad, relative, size, svg,
     transparent_black, Action, AnchorCorner, AnyElement, AvailableSpace, Bounds, ClipboardItem,
     ContentMask, Corners, CursorStyle, DispatchPhase, Edges, Element, ElementInputHandler, Entity,
     FontId, GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Length,
@@ -39,7 +43,8 @@ use gpui::{
 };
 use itertools::Itertools;
 use language::language_settings::{
-    IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings, ShowWhitespaceSetting,
+    language_settings, IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings,
+    ShowWhitespaceSetting,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{Anchor, MultiBufferPoint, MultiBufferRow};
@@ -65,6 +70,45 @@ use ui::{h_flex, ButtonLike, ButtonStyle, ContextMenu, Tooltip};
 use util::ResultExt;
 use workspace::{item::Item, Workspace};
 
+// The priorities with which the editor's popovers are drawn, where higher ones are drawn on top.
+// The completions menu goes above hover popovers and signature help, since it's tied to what's
+// being typed, while hover popovers go either above or below signature help depending on
+// `hover_popover_layer`.
+const HOVER_BACKDROP_PRIORITY: usize = 1;
+const SIGNATURE_HELP_PRIORITY: usize = 3;
+const CONTEXT_MENU_PRIORITY: usize = 5;
+
+fn hover_popover_priority(layer: HoverPopoverLayer) -> usize {
+    match layer {
+        HoverPopoverLayer::AboveSignatureHelp => SIGNATURE_HELP_PRIORITY + 1,
+        HoverPopoverLayer::BelowSignatureHelp => SIGNATURE_HELP_PRIORITY - 1,
+    }
+}
+
+/// The corners of a pointer from the edge of a hover popover to the point it describes,
+/// starting with its tip. The pointer is kept within the popover's horizontal bounds.
+fn hover_popover_pointer(
+    target: gpui::Point<Pixels>,
+    popover_bounds: Bounds<Pixels>,
+    above: bool,
+) -> [gpui::Point<Pixels>; 3] {
+    let x = target.x.clamp(
+        popover_bounds.left() + HOVER_POPOVER_POINTER_SIZE,
+        (popover_bounds.right() - HOVER_POPOVER_POINTER_SIZE)
+            .max(popover_bounds.left() + HOVER_POPOVER_POINTER_SIZE),
+    );
+    let base_y = if above {
+        popover_bounds.bottom()
+    } else {
+        popover_bounds.top()
+    };
+    [
+        point(x, target.y),
+        point(x - HOVER_POPOVER_POINTER_SIZE, base_y),
+        point(x + HOVER_POPOVER_POINTER_SIZE, base_y),
+    ]
+}
+
 struct SelectionLayout {
     head: DisplayPoint,
     cursor_shape: CursorShape,
@@ -327,6 +371,20 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_indent_guides);
         register_action(view, cx, Editor::toggle_inlay_hints);
         register_action(view, cx, hover_popover::hover);
+        register_action(view, cx, hover_popover::hover_next_symbol);
+        register_action(view, cx, hover_popover::hover_prev_symbol);
+        register_action(view, cx, hover_popover::hover_scroll_to_top);
+        register_action(view, cx, hover_popover::hover_scroll_to_bottom);
+        register_action(view, cx, hover_popover::toggle_hover_popover);
+        register_action(view, cx, hover_popover::pin_hover);
+        register_action(view, cx, hover_popover::open_hover_link);
+        register_action(view, cx, hover_popover::hover_next_link);
+        register_action(view, cx, hover_popover::hover_prev_link);
+        register_action(view, cx, hover_popover::open_focused_hover_link);
+        register_action(view, cx, hover_popover::show_last_hover);
+        register_action(view, cx, hover_popover::apply_hover_quick_fix);
+        #[cfg(debug_assertions)]
+        register_action(view, cx, hover_popover::show_raw_hover);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
         register_action(view, cx, Editor::copy_relative_path);
@@ -673,6 +731,7 @@ impl EditorElement {
         let modifiers = event.modifiers;
         let gutter_hovered = gutter_hitbox.is_hovered(cx);
         editor.set_gutter_hovered(gutter_hovered, cx);
+        editor.hover_state.update_mouse_over_popover(event.position);
 
         // Don't trigger hover popover if mouse is hovering over context menu
         if text_hitbox.is_hovered(cx) {
@@ -686,7 +745,7 @@ impl EditorElement {
                     .snapshot
                     .buffer_snapshot
                     .anchor_before(point.to_offset(&position_map.snapshot, Bias::Left));
-                hover_at(editor, Some(anchor), cx);
+                hover_at_with_modifiers(editor, anchor, &modifiers, cx);
                 Self::update_visible_cursor(editor, point, position_map, cx);
             } else {
                 hover_at(editor, None, cx);
@@ -1592,10 +1651,13 @@ impl EditorElement {
                         return None;
                     }
                     let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    let info =
+                        hover_popover::run_indicator_info(tasks, &snapshot.buffer_snapshot, cx);
                     let button = editor.render_run_indicator(
                         &self.style,
                         Some(display_row) == active_task_indicator_row,
                         display_row,
+                        info,
                         cx,
                     );
 
@@ -2498,7 +2560,7 @@ impl EditorElement {
             list_origin.y -= line_height + list_height;
         }
 
-        cx.defer_draw(context_menu, list_origin, 1);
+        cx.defer_draw(context_menu, list_origin, CONTEXT_MENU_PRIORITY);
         true
     }
 
@@ -2538,29 +2600,80 @@ impl EditorElement {
             horizontal_offset: Pixels,
         }
 
+        let width_bounds = HoverPopoverWidthBounds {
+            em_width,
+            editor_width: hitbox.size.width,
+            window_width: cx.viewport_size().width,
+        };
+        let popover_anchor = self
+            .editor
+            .read(cx)
+            .hover_state
+            .popover_anchor(&snapshot.buffer_snapshot, cx);
+        let max_width = match popover_anchor {
+            Some(anchor) => language_hover_popover_max_width(
+                &snapshot.buffer_snapshot,
+                anchor,
+                width_bounds,
+                cx,
+            ),
+            None => hover_popover_max_width(
+                em_width,
+                hitbox.size.width,
+                cx.viewport_size().width,
+                language_settings(None, None, cx).hover_popover_max_width_percent,
+            ),
+        };
         let max_size = size(
-            (120. * em_width) // Default size
-                .min(hitbox.size.width / 2.) // Shrink to half of the editor width
-                .max(MIN_POPOVER_CHARACTER_WIDTH * em_width), // Apply minimum width of 20 characters
+            max_width,
             (16. * line_height) // Default size
                 .min(hitbox.size.height / 2.) // Shrink to half of the editor height
                 .max(MIN_POPOVER_LINE_HEIGHT * line_height), // Apply minimum height of 4 lines
         );
 
-        let hover_popovers = self.editor.update(cx, |editor, cx| {
-            editor.hover_state.render(
-                &snapshot,
-                &self.style,
-                visible_display_row_range.clone(),
-                max_size,
-                editor.workspace.as_ref().map(|(w, _)| w.clone()),
-                cx,
-            )
-        });
-        let Some((position, hover_popovers)) = hover_popovers else {
+        let render_hover_popovers = |max_heights: Option<HoverPopoverHeights>,
+                                     cx: &mut WindowContext| {
+            self.editor.update(cx, |editor, cx| {
+                editor.hover_state.render(
+                    &snapshot,
+                    &self.style,
+                    visible_display_row_range.clone(),
+                    max_size,
+                    max_heights,
+                    Some(width_bounds),
+                    editor.workspace.as_ref().map(|(w, _)| w.clone()),
+                    cx,
+                )
+            })
+        };
+        let Some((position, hover_popovers)) = render_hover_popovers(None, cx) else {
             return;
         };
 
+        let backdrop = self.editor.update(cx, |editor, cx| {
+            editor
+                .hover_state
+                .render_backdrop(&snapshot.buffer_snapshot, cx)
+        });
+        if let Some(mut backdrop) = backdrop {
+            backdrop.layout_as_root(text_hitbox.size.into(), cx);
+            cx.defer_draw(backdrop, text_hitbox.origin, HOVER_BACKDROP_PRIORITY);
+        }
+
+        let hover_settings = hover_popover::hover_settings_at(
+            &snapshot.buffer_snapshot,
+            position.to_point(&snapshot.display_snapshot),
+            cx,
+        );
+        let priority = hover_popover_priority(hover_settings.hover_popover_layer);
+        let block_underlying_moves = hover_settings.hover_block_underlying_moves;
+        let show_pointer = hover_settings.hover_popover_pointer;
+        let overflow_behavior = hover_settings.hover_overflow_behavior;
+        let pointer_height = if show_pointer {
+            HOVER_POPOVER_POINTER_SIZE
+        } else {
+            Pixels::ZERO
+        };
         let available_space = size(AvailableSpace::MinContent, AvailableSpace::MinContent);
 
         // This is safe because we check on layout whether the required row is available
@@ -2573,36 +2686,108 @@ impl EditorElement {
         let y = position.row().as_f32() * line_height - scroll_pixel_position.y;
         let hovered_point = content_origin + point(x, y);
 
-        let mut overall_height = Pixels::ZERO;
-        let mut measured_hover_popovers = Vec::new();
-        for mut hover_popover in hover_popovers {
-            let size = hover_popover.layout_as_root(available_space, cx);
-            let horizontal_offset =
-                (text_hitbox.upper_right().x - (hovered_point.x + size.width)).min(Pixels::ZERO);
-
-            overall_height += HOVER_POPOVER_GAP + size.height;
-
-            measured_hover_popovers.push(MeasuredHoverPopover {
-                element: hover_popover,
-                size,
-                horizontal_offset,
-            });
+        let measure_hover_popovers = |hover_popovers: Vec<AnyElement>, cx: &mut WindowContext| {
+            hover_popovers
+                .into_iter()
+                .map(|mut hover_popover| {
+                    let size = hover_popover.layout_as_root(available_space, cx);
+                    let horizontal_offset = (text_hitbox.upper_right().x
+                        - (hovered_point.x + size.width))
+                        .min(Pixels::ZERO);
+                    MeasuredHoverPopover {
+                        element: hover_popover,
+                        size,
+                        horizontal_offset,
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut measured_hover_popovers = measure_hover_popovers(hover_popovers, cx);
+
+        // When a diagnostic and info popovers don't fit above or below the hovered point
+        // together, render them again within the heights `hover_overflow_behavior` allots.
+        let hover_state = &self.editor.read(cx).hover_state;
+        let has_diagnostic = hover_state.visible() && hover_state.diagnostic_popover.is_some();
+        if has_diagnostic && measured_hover_popovers.len() > 1 {
+            let gaps = HOVER_POPOVER_GAP * measured_hover_popovers.len() as f32 + pointer_height;
+            let available_height = hovered_point
+                .y
+                .max(cx.viewport_size().height - hovered_point.y - line_height)
+                - gaps;
+            let info_heights = measured_hover_popovers[1..]
+                .iter()
+                .map(|popover| popover.size.height)
+                .collect::<Vec<_>>();
+            let max_heights = hover_popover_heights(
+                overflow_behavior,
+                available_height,
+                measured_hover_popovers[0].size.height,
+                &info_heights,
+                MIN_POPOVER_LINE_HEIGHT * line_height,
+            );
+            if let Some((_, hover_popovers)) =
+                max_heights.and_then(|max_heights| render_hover_popovers(Some(max_heights), cx))
+            {
+                measured_hover_popovers = measure_hover_popovers(hover_popovers, cx);
+            }
         }
-        overall_height += HOVER_POPOVER_GAP;
 
-        fn draw_occluder(width: Pixels, origin: gpui::Point<Pixels>, cx: &mut WindowContext) {
+        let overall_height = measured_hover_popovers
+            .iter()
+            .fold(Pixels::ZERO, |height, popover| {
+                height + HOVER_POPOVER_GAP + popover.size.height
+            })
+            + HOVER_POPOVER_GAP
+            + pointer_height;
+
+        fn draw_occluder(
+            width: Pixels,
+            origin: gpui::Point<Pixels>,
+            priority: usize,
+            block_underlying_moves: bool,
+            cx: &mut WindowContext,
+        ) {
             let mut occlusion = div()
                 .size_full()
                 .occlude()
-                .on_mouse_move(|_, cx| cx.stop_propagation())
+                .when(block_underlying_moves, |div| {
+                    div.on_mouse_move(|_, cx| cx.stop_propagation())
+                })
                 .into_any_element();
             occlusion.layout_as_root(size(width, HOVER_POPOVER_GAP).into(), cx);
-            cx.defer_draw(occlusion, origin, 2);
+            cx.defer_draw(occlusion, origin, priority);
         }
 
+        fn draw_pointer(
+            corners: [gpui::Point<Pixels>; 3],
+            priority: usize,
+            cx: &mut WindowContext,
+        ) {
+            let color = cx.theme().colors().elevated_surface_background;
+            let origin = point(corners[1].x, corners[0].y.min(corners[1].y));
+            let mut pointer = canvas(
+                |_, _| {},
+                move |_, _, cx| {
+                    let mut path = gpui::Path::new(corners[0]);
+                    path.line_to(corners[1]);
+                    path.line_to(corners[2]);
+                    path.line_to(corners[0]);
+                    cx.paint_path(path, color);
+                },
+            )
+            .size_full()
+            .into_any_element();
+            pointer.layout_as_root(
+                size(HOVER_POPOVER_POINTER_SIZE * 2., HOVER_POPOVER_POINTER_SIZE).into(),
+                cx,
+            );
+            cx.defer_draw(pointer, origin, priority);
+        }
+
+        let mut popover_bounds = Vec::new();
         if hovered_point.y > overall_height {
             // There is enough space above. Render popovers above the hovered point
-            let mut current_y = hovered_point.y;
+            let mut current_y = hovered_point.y - pointer_height;
             for (position, popover) in measured_hover_popovers.into_iter().with_position() {
                 let size = popover.size;
                 let popover_origin = point(
@@ -2610,30 +2795,58 @@ impl EditorElement {
                     current_y - size.height,
                 );
 
-                cx.defer_draw(popover.element, popover_origin, 2);
+                cx.defer_draw(popover.element, popover_origin, priority);
+                popover_bounds.push(Bounds::new(popover_origin, size));
+                if show_pointer
+                    && matches!(
+                        position,
+                        itertools::Position::First | itertools::Position::Only
+                    )
+                {
+                    let bounds = Bounds::new(popover_origin, size);
+                    draw_pointer(
+                        hover_popover_pointer(hovered_point, bounds, true),
+                        priority,
+                        cx,
+                    );
+                }
                 if position != itertools::Position::Last {
                     let origin = point(popover_origin.x, popover_origin.y - HOVER_POPOVER_GAP);
-                    draw_occluder(size.width, origin, cx);
+                    draw_occluder(size.width, origin, priority, block_underlying_moves, cx);
                 }
 
                 current_y = popover_origin.y - HOVER_POPOVER_GAP;
             }
         } else {
             // There is not enough space above. Render popovers below the hovered point
-            let mut current_y = hovered_point.y + line_height;
+            let mut current_y = hovered_point.y + line_height + pointer_height;
             for (position, popover) in measured_hover_popovers.into_iter().with_position() {
                 let size = popover.size;
                 let popover_origin = point(hovered_point.x + popover.horizontal_offset, current_y);
 
-                cx.defer_draw(popover.element, popover_origin, 2);
+                cx.defer_draw(popover.element, popover_origin, priority);
+                popover_bounds.push(Bounds::new(popover_origin, size));
+                if show_pointer
+                    && matches!(
+                        position,
+                        itertools::Position::First | itertools::Position::Only
+                    )
+                {
+                    let bounds = Bounds::new(popover_origin, size);
+                    let target = point(hovered_point.x, hovered_point.y + line_height);
+                    draw_pointer(hover_popover_pointer(target, bounds, false), priority, cx);
+                }
                 if position != itertools::Position::Last {
                     let origin = point(popover_origin.x, popover_origin.y + size.height);
-                    draw_occluder(size.width, origin, cx);
+                    draw_occluder(size.width, origin, priority, block_underlying_moves, cx);
                 }
 
                 current_y = popover_origin.y + size.height + HOVER_POPOVER_GAP;
             }
         }
+        self.editor.update(cx, |editor, _| {
+            editor.hover_state.popover_bounds = popover_bounds;
+        });
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -2699,7 +2912,7 @@ impl EditorElement {
                 point.x = window_size.width - size.width;
             }
 
-            cx.defer_draw(element, point, 1)
+            cx.defer_draw(element, point, SIGNATURE_HELP_PRIORITY)
         }
     }
 
@@ -5894,6 +6107,51 @@ mod tests {
     use ui::Context;
     use util::test::sample_text;
 
+    #[test]
+    fn test_hover_popover_pointer() {
+        let popover_bounds = Bounds::new(point(px(100.), px(0.)), size(px(200.), px(80.)));
+
+        // Above the symbol, the pointer hangs from the popover's bottom edge.
+        let target = point(px(150.), px(86.));
+        assert_eq!(
+            hover_popover_pointer(target, popover_bounds, true),
+            [
+                point(px(150.), px(86.)),
+                point(px(144.), px(80.)),
+                point(px(156.), px(80.))
+            ]
+        );
+
+        // Below the symbol, it rises from the top edge instead.
+        let popover_bounds = Bounds::new(point(px(100.), px(106.)), size(px(200.), px(80.)));
+        let target = point(px(150.), px(100.));
+        assert_eq!(
+            hover_popover_pointer(target, popover_bounds, false),
+            [
+                point(px(150.), px(100.)),
+                point(px(144.), px(106.)),
+                point(px(156.), px(106.))
+            ]
+        );
+
+        // A symbol left of a popover shifted to fit the editor gets a pointer at its edge.
+        let target = point(px(20.), px(100.));
+        assert_eq!(
+            hover_popover_pointer(target, popover_bounds, false)[0],
+            point(px(106.), px(100.))
+        );
+    }
+
+    #[test]
+    fn test_hover_popover_priority() {
+        let above = hover_popover_priority(HoverPopoverLayer::AboveSignatureHelp);
+        let below = hover_popover_priority(HoverPopoverLayer::BelowSignatureHelp);
+        assert!(HOVER_BACKDROP_PRIORITY < below);
+        assert!(below < SIGNATURE_HELP_PRIORITY);
+        assert!(SIGNATURE_HELP_PRIORITY < above);
+        assert!(above < CONTEXT_MENU_PRIORITY);
+    }
+
     #[gpui::test]
     fn test_shape_line_numbers(cx: &mut TestAppContext) {
         init_test(cx, |_| {});
@@ -155,12 +155,18 @@ impl Editor {
         modifiers: Modifiers,
         cx: &mut ViewContext<Editor>,
     ) {
+        let clicked_anchor = self
+            .snapshot(cx)
+            .display_snapshot
+            .display_point_to_anchor(point.next_valid, Bias::Left);
         let reveal_task = self.cmd_click_reveal_task(point, modifiers, cx);
         cx.spawn(|editor, mut cx| async move {
             let definition_revealed = reveal_task.await.log_err().unwrap_or(false);
             let find_references = editor
                 .update(&mut cx, |editor, cx| {
-                    if definition_revealed {
+                    if definition_revealed
+                        || hover_popover::hover_at_click(editor, clicked_anchor, cx)
+                    {
                         return None;
                     }
                     editor.find_all_references(&FindAllReferences, cx)
@@ -1,65 +1,678 @@
 use crate::{
     display_map::{InlayOffset, ToDisplayPoint},
+    editor_settings::{
+        HoverDiagnosticAnchor, HoverEnclosingModifier, HoverMarkupKind, HoverOverflowBehavior,
+        HoverPopoverPlacement, HoverTextAlign,
+    },
     hover_links::{InlayHighlight, RangeInEditor},
-    scroll::ScrollAmount,
-    Anchor, AnchorRangeExt, DisplayPoint, DisplayRow, Editor, EditorSettings, EditorSnapshot,
-    EditorStyle, Hover, RangeToAnchorExt,
+    scroll::{Autoscroll, ScrollAmount},
+    Anchor, AnchorRangeExt, ApplyHoverQuickFix, Bias, Direction, DisplayPoint, DisplayRow, Editor,
+    EditorEvent, EditorSettings, EditorSnapshot, EditorStyle, Hover, HoverNextLink,
+    HoverNextSymbol, HoverPrevLink, HoverPrevSymbol, HoverScrollToBottom, HoverScrollToTop,
+    OpenFocusedHoverLink, OpenHoverLink, PinHover, RangeToAnchorExt, RunnableTasks, ShowLastHover,
+    ToggleHoverPopover,
 };
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use futures::{future::Shared, Future, FutureExt};
 use gpui::{
-    div, px, AnyElement, AsyncWindowContext, CursorStyle, FontWeight, Hsla, InteractiveElement,
-    IntoElement, MouseButton, ParentElement, Pixels, ScrollHandle, SharedString, Size,
-    StatefulInteractiveElement, StyleRefinement, Styled, Task, TextStyleRefinement, View,
-    ViewContext, WeakView,
+    div, px, Animation, AnimationExt, AnyElement, AppContext, AsyncWindowContext, Bounds,
+    CursorStyle, FontWeight, Global, Hsla, Img, InteractiveElement, IntoElement, Model, Modifiers,
+    MouseButton, ParentElement, Pixels, ScrollHandle, SharedString, Size,
+    StatefulInteractiveElement, StyleRefinement, Styled, Task, TextAlign, TextStyle,
+    TextStyleRefinement, View, ViewContext, WeakView,
 };
 use itertools::Itertools;
-use language::{DiagnosticEntry, Language, LanguageRegistry};
+use language::{
+    Buffer, BufferSnapshot, CharKind, DiagnosticEntry, Language, LanguageRegistry, Node, Point,
+    PointUtf16, Unclipped,
+};
 use lsp::DiagnosticSeverity;
-use markdown::{Markdown, MarkdownStyle};
-use multi_buffer::ToOffset;
-use project::{HoverBlock, InlayHintLabelPart};
-use settings::Settings;
+use markdown::{
+    parser::{parse_markdown, CodeBlockKind, MarkdownEvent, MarkdownTag, MarkdownTagEnd},
+    Markdown, MarkdownStyle,
+};
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot, ToOffset};
+use project::{
+    CodeAction, Fs, HoverBlock, HoverContentFormat, InlayHintLabelPart, Inventory, Location,
+    LocationLink, Project, TaskRunResult, TaskSourceKind, WorktreeId,
+};
+use settings::{Settings, SettingsStore};
 use std::rc::Rc;
-use std::{borrow::Cow, cell::RefCell};
-use std::{ops::Range, sync::Arc, time::Duration};
-use theme::ThemeSettings;
+use std::{any::TypeId, borrow::Cow, cell::RefCell};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use task::{ResolvedTask, TaskContext, TaskTemplate, TaskVariables, VariableName};
+use text::BufferId;
+use theme::{SyntaxTheme, ThemeRegistry, ThemeSettings};
 use ui::{prelude::*, window_is_transparent, Tooltip};
-use util::TryFutureExt;
+use util::{ResultExt, TryFutureExt};
 use workspace::Workspace;
 pub const HOVER_DELAY_MILLIS: u64 = 350;
 pub const HOVER_REQUEST_DELAY_MILLIS: u64 = 200;
+pub const HOVER_LIVE_UPDATE_DEBOUNCE_MILLIS: u64 = 300;
 
 pub const MIN_POPOVER_CHARACTER_WIDTH: f32 = 20.;
 pub const MIN_POPOVER_LINE_HEIGHT: Pixels = px(4.);
 pub const HOVER_POPOVER_GAP: Pixels = px(10.);
+pub const HOVER_POPOVER_POINTER_SIZE: Pixels = px(6.);
+
+/// The maximum width of hover popovers in an editor. A `max_width_percent` of the window
+/// width replaces the default of 120 characters and half of the editor width.
+pub fn hover_popover_max_width(
+    em_width: Pixels,
+    editor_width: Pixels,
+    window_width: Pixels,
+    max_width_percent: f32,
+) -> Pixels {
+    let width = if max_width_percent > 0. {
+        window_width * (max_width_percent.min(100.) / 100.)
+    } else {
+        (120. * em_width) // Default size
+            .min(editor_width / 2.) // Shrink to half of the editor width
+    };
+    width.max(MIN_POPOVER_CHARACTER_WIDTH * em_width) // Apply minimum width of 20 characters
+}
+
+/// The measurements the maximum width of hover popovers is derived from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoverPopoverWidthBounds {
+    pub em_width: Pixels,
+    pub editor_width: Pixels,
+    pub window_width: Pixels,
+}
+
+/// The maximum width of hover popovers for the language at `anchor`, following its
+/// `hover_popover_max_width_percent` language setting.
+pub fn language_hover_popover_max_width(
+    buffer: &MultiBufferSnapshot,
+    anchor: Anchor,
+    bounds: HoverPopoverWidthBounds,
+    cx: &AppContext,
+) -> Pixels {
+    hover_popover_max_width(
+        bounds.em_width,
+        bounds.editor_width,
+        bounds.window_width,
+        buffer
+            .settings_at(anchor, cx)
+            .hover_popover_max_width_percent,
+    )
+}
+
+/// The maximum heights of the diagnostic and info popovers, when together they would be
+/// taller than the space around the hovered symbol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoverPopoverHeights {
+    pub diagnostic: Pixels,
+    pub info: Pixels,
+}
+
+/// Divides the `available` height between a diagnostic popover and the info popovers,
+/// given their measured heights, following `behavior`. Returns `None` when they fit.
+/// Info popovers are never made shorter than `min_info_height`.
+pub fn hover_popover_heights(
+    behavior: HoverOverflowBehavior,
+    available: Pixels,
+    diagnostic_height: Pixels,
+    info_heights: &[Pixels],
+    min_info_height: Pixels,
+) -> Option<HoverPopoverHeights> {
+    let info_height = info_heights
+        .iter()
+        .fold(Pixels::ZERO, |total, height| total + *height);
+    if info_heights.is_empty() || diagnostic_height + info_height <= available {
+        return None;
+    }
+
+    let info_count = info_heights.len() as f32;
+    let diagnostic = match behavior {
+        HoverOverflowBehavior::ScrollInfo => diagnostic_height,
+        HoverOverflowBehavior::ScrollBoth => {
+            // The diagnostic gets an equal share, or whatever the info popovers don't need.
+            let share = available / (info_count + 1.);
+            diagnostic_height.min(share.max(available - info_height))
+        }
+    };
+    Some(HoverPopoverHeights {
+        diagnostic,
+        info: ((available - diagnostic) / info_count).max(min_info_height),
+    })
+}
+
+/// The lines of hover content laid out in the first frame, the rest following in the next
+/// one. Popovers are at most 16 lines tall, which this fills even when lines wrap.
+const HOVER_INCREMENTAL_LINE_BUDGET: usize = 64;
+
+const HOVER_PIN_COUNT_KEY: &str = "hover_pin_count";
+/// How many times popovers are pinned before the hint of the [`PinHover`] keybinding hides.
+const HOVER_PIN_HINT_LIMIT: usize = 3;
+
+/// Separates hover blocks of different kinds with a horizontal rule.
+const HOVER_BLOCK_SEPARATOR: &str = "\n\n---\n\n";
+
+/// How many hover responses `hover_cache` keeps, forgetting the least recently used first.
+const HOVER_CACHE_CAPACITY: usize = 16;
+
+/// Markers that language servers end the hover content with when they cut it short.
+const HOVER_TRUNCATION_MARKERS: &[&str] = &["[truncated]", "(truncated)"];
+
+/// Session-wide override of the `hover_popover_enabled` setting, set by [`ToggleHoverPopover`].
+/// `None` means the setting is used as is.
+#[derive(Default)]
+pub struct HoverPopoverOverride(pub Option<bool>);
+
+impl Global for HoverPopoverOverride {}
+
+/// How many times popovers were pinned with [`PinHover`], persisted across sessions.
+/// `None` while it's loaded from the database.
+struct HoverPinCount(Option<usize>);
+
+impl Global for HoverPinCount {}
+
+/// How many times popovers were pinned, or `None` until it's loaded. The first call starts
+/// loading it in the background, so that rendering never waits on the database.
+fn hover_pin_count(cx: &mut AppContext) -> Option<usize> {
+    if let Some(pin_count) = cx.try_global::<HoverPinCount>() {
+        return pin_count.0;
+    }
+    cx.set_global(HoverPinCount(None));
+    cx.spawn(|mut cx| async move {
+        let pin_count = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(HOVER_PIN_COUNT_KEY) })
+            .await
+            .log_err()
+            .flatten()
+            .and_then(|pin_count| pin_count.parse().ok())
+            .unwrap_or(0);
+        cx.update(|cx| {
+            cx.set_global(HoverPinCount(Some(pin_count)));
+            cx.refresh();
+        })
+        .ok();
+    })
+    .detach();
+    None
+}
+
+/// Editor settings resolved for the worktree of the buffer containing `anchor`,
+/// so that projects can override hover behavior in their local settings.
+fn hover_settings<'a>(editor: &Editor, anchor: Anchor, cx: &'a AppContext) -> &'a EditorSettings {
+    let file = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(anchor, cx)
+        .and_then(|(buffer, _)| buffer.read(cx).file().cloned());
+    EditorSettings::get(file.as_ref().map(|file| file.as_ref().into()), cx)
+}
+
+/// [`hover_settings`] for a position in a snapshot of the editor's buffer, for when the
+/// editor itself can't be read, such as while its popovers are rendered.
+pub(crate) fn hover_settings_at<'a, T: ToOffset>(
+    buffer: &MultiBufferSnapshot,
+    position: T,
+    cx: &'a AppContext,
+) -> &'a EditorSettings {
+    EditorSettings::get(
+        buffer.file_at(position).map(|file| file.as_ref().into()),
+        cx,
+    )
+}
+
+/// Whether mouse hovers should show popovers, taking the session override into account.
+pub fn hover_popover_enabled(editor: &Editor, anchor: Option<Anchor>, cx: &AppContext) -> bool {
+    cx.try_global::<HoverPopoverOverride>()
+        .and_then(|hover_override| hover_override.0)
+        .unwrap_or_else(|| {
+            let anchor = anchor.unwrap_or_else(|| editor.selections.newest_anchor().head());
+            hover_settings(editor, anchor, cx).hover_popover_enabled
+        })
+}
+
+/// Whether hovering with the mouse shows popovers, rather than only the [`Hover`] action.
+fn mouse_hover_enabled(editor: &Editor, anchor: Option<Anchor>, cx: &AppContext) -> bool {
+    let anchor = anchor.unwrap_or_else(|| editor.selections.newest_anchor().head());
+    hover_settings(editor, anchor, cx).hover_mouse_enabled
+}
+
+/// Bindable action which flips whether mouse hovers show popovers until it is toggled back
+pub fn toggle_hover_popover(
+    editor: &mut Editor,
+    _: &ToggleHoverPopover,
+    cx: &mut ViewContext<Editor>,
+) {
+    let head = editor.selections.newest_anchor().head();
+    let enabled = !hover_popover_enabled(editor, Some(head), cx);
+    let setting_enabled = hover_settings(editor, head, cx).hover_popover_enabled;
+    cx.set_global(HoverPopoverOverride(
+        (enabled != setting_enabled).then_some(enabled),
+    ));
+    if !enabled {
+        hide_hover(editor, cx);
+    }
+}
+
+/// Asks language servers that start for hover documentation in the format of the
+/// `hover_markup_kind` setting.
+pub(crate) fn init(cx: &mut AppContext) {
+    fn update_content_format(cx: &mut AppContext) {
+        let markup_kind = EditorSettings::get_global(cx).hover_markup_kind;
+        cx.set_global(HoverContentFormat(markup_kind.content_format()));
+    }
+    update_content_format(cx);
+    cx.observe_global::<SettingsStore>(update_content_format)
+        .detach();
+}
 
 /// Bindable action which uses the most recent selection head to trigger a hover
 pub fn hover(editor: &mut Editor, _: &Hover, cx: &mut ViewContext<Editor>) {
     let head = editor.selections.newest_anchor().head();
-    show_hover(editor, head, true, cx);
+    let settings = hover_settings(editor, head, cx);
+    if !settings.hover_allow_while_typing
+        && editor.hover_state.typing(settings.hover_popover_delay, cx)
+    {
+        return;
+    }
+    show_hover(editor, head, None, true, cx);
+}
+
+/// Bindable action which pins the visible popovers, so that moving the mouse away doesn't
+/// hide them, or unpins them.
+pub fn pin_hover(editor: &mut Editor, _: &PinHover, cx: &mut ViewContext<Editor>) {
+    if !editor.hover_state.visible() {
+        return;
+    }
+    editor.hover_state.pinned = !editor.hover_state.pinned;
+    // Pins made while the count is still loading aren't counted.
+    if let Some(pin_count) = hover_pin_count(cx).filter(|_| editor.hover_state.pinned) {
+        let pin_count = pin_count + 1;
+        cx.set_global(HoverPinCount(Some(pin_count)));
+        cx.background_executor()
+            .spawn(
+                KEY_VALUE_STORE.write_kvp(HOVER_PIN_COUNT_KEY.to_string(), pin_count.to_string()),
+            )
+            .detach_and_log_err(cx);
+    }
+    cx.notify();
+}
+
+/// Shows the hover of the symbol clicked with the secondary modifier right away, when
+/// `hover_on_modifier_click` is enabled. Returns whether it did.
+pub(crate) fn hover_at_click(
+    editor: &mut Editor,
+    anchor: Anchor,
+    cx: &mut ViewContext<Editor>,
+) -> bool {
+    if !hover_settings(editor, anchor, cx).hover_on_modifier_click {
+        return false;
+    }
+    show_hover(editor, anchor, None, true, cx);
+    true
+}
+
+/// Bindable action which moves the cursor to the next identifier and shows its hover.
+pub fn hover_next_symbol(editor: &mut Editor, _: &HoverNextSymbol, cx: &mut ViewContext<Editor>) {
+    hover_adjacent_symbol(editor, Direction::Next, cx);
+}
+
+/// Bindable action which moves the cursor to the previous identifier and shows its hover.
+pub fn hover_prev_symbol(editor: &mut Editor, _: &HoverPrevSymbol, cx: &mut ViewContext<Editor>) {
+    hover_adjacent_symbol(editor, Direction::Prev, cx);
+}
+
+fn hover_adjacent_symbol(editor: &mut Editor, direction: Direction, cx: &mut ViewContext<Editor>) {
+    let head = editor.selections.newest_anchor().head();
+    let Some((excerpt_id, buffer, _)) = editor.buffer.read(cx).excerpt_containing(head, cx) else {
+        return;
+    };
+    let buffer = buffer.read(cx).snapshot();
+    let offset = buffer.summary_for_anchor::<usize>(&head.text_anchor);
+    let Some(target) = adjacent_identifier(&buffer, offset, direction) else {
+        return;
+    };
+    let Some(anchor) = editor
+        .buffer
+        .read(cx)
+        .snapshot(cx)
+        .anchor_in_excerpt(excerpt_id, buffer.anchor_before(target))
+    else {
+        return;
+    };
+
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_anchor_ranges([anchor..anchor])
+    });
+    show_hover(editor, anchor, None, true, cx);
+}
+
+/// The start of the identifier after or before the one at `offset`, in the syntax tree
+/// of the language at `offset`.
+///
+/// The tree is walked outwards from the node at `offset`, so only the nodes between it and
+/// the adjacent identifier are visited.
+fn adjacent_identifier(
+    buffer: &BufferSnapshot,
+    offset: usize,
+    direction: Direction,
+) -> Option<usize> {
+    let layer = buffer.syntax_layer_at(offset)?;
+    let mut node = layer
+        .node()
+        .named_descendant_for_byte_range(offset, offset)?;
+    let current_start = if is_identifier(&node) {
+        node.start_byte()
+    } else {
+        offset
+    };
+
+    loop {
+        if let Some(start) = identifier_in(node, direction, current_start) {
+            return Some(start);
+        }
+        loop {
+            let sibling = match direction {
+                Direction::Next => node.next_sibling(),
+                Direction::Prev => node.prev_sibling(),
+            };
+            if let Some(sibling) = sibling {
+                node = sibling;
+                break;
+            }
+            node = node.parent()?;
+        }
+    }
+}
+
+fn is_identifier(node: &Node) -> bool {
+    node.kind().ends_with("identifier")
+}
+
+/// The start of the first identifier within `node` that lies after `current_start`, or of the
+/// last one before it, skipping children on the other side of `current_start`.
+fn identifier_in(node: Node, direction: Direction, current_start: usize) -> Option<usize> {
+    if is_identifier(&node) {
+        let start = node.start_byte();
+        return match direction {
+            Direction::Next => (start > current_start).then_some(start),
+            Direction::Prev => (start < current_start).then_some(start),
+        };
+    }
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<_>>();
+    match direction {
+        Direction::Next => children
+            .into_iter()
+            .filter(|child| child.end_byte() > current_start)
+            .find_map(|child| identifier_in(child, direction, current_start)),
+        Direction::Prev => children
+            .into_iter()
+            .rev()
+            .filter(|child| child.start_byte() < current_start)
+            .find_map(|child| identifier_in(child, direction, current_start)),
+    }
+}
+
+/// Bindable action which shows the last hover of the buffer at the newest selection again,
+/// without querying the language server.
+pub fn show_last_hover(editor: &mut Editor, _: &ShowLastHover, cx: &mut ViewContext<Editor>) {
+    let head = editor.selections.newest_anchor().head();
+    if !hover_settings(editor, head, cx).hover_remember_last {
+        return;
+    }
+    let Some((buffer, _)) = editor.buffer.read(cx).text_anchor_for_position(head, cx) else {
+        return;
+    };
+    let Some(info_popovers) = editor
+        .hover_state
+        .last_info_popovers
+        .get(&buffer.read(cx).remote_id())
+        .cloned()
+    else {
+        return;
+    };
+
+    hide_hover(editor, cx);
+    let ranges = info_popovers
+        .iter()
+        .filter_map(|popover| popover.symbol_range.as_text_range())
+        .collect::<Vec<_>>();
+    for popover in &info_popovers {
+        *popover.keyboard_grace.borrow_mut() = true;
+    }
+    update_hover_highlights(editor, &ranges, cx);
+    editor.hover_state.info_popovers = info_popovers;
+    cx.emit(EditorEvent::HoverChanged);
+    cx.notify();
+}
+
+/// Bindable action which scrolls the visible hover popovers to the start of their contents
+pub fn hover_scroll_to_top(
+    editor: &mut Editor,
+    _: &HoverScrollToTop,
+    cx: &mut ViewContext<Editor>,
+) {
+    if !editor.hover_state.scroll_to_top(cx) {
+        cx.propagate();
+    }
+}
+
+/// Bindable action which scrolls the visible hover popovers to the end of their contents
+pub fn hover_scroll_to_bottom(
+    editor: &mut Editor,
+    _: &HoverScrollToBottom,
+    cx: &mut ViewContext<Editor>,
+) {
+    if !editor.hover_state.scroll_to_bottom(cx) {
+        cx.propagate();
+    }
+}
+
+/// Bindable debug action which shows the unprocessed hover responses of the language servers
+/// for the most recent selection head, to tell language server bugs apart from rendering bugs.
+#[cfg(debug_assertions)]
+pub fn show_raw_hover(editor: &mut Editor, _: &crate::ShowRawHover, cx: &mut ViewContext<Editor>) {
+    hide_hover(editor, cx);
+
+    let head = editor.selections.newest_anchor().head();
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let Some((buffer, buffer_position)) = editor.buffer.read(cx).text_anchor_for_position(head, cx)
+    else {
+        return;
+    };
+    let raw_hovers = project.update(cx, |project, cx| {
+        project.raw_hover(&buffer, buffer_position, cx)
+    });
+
+    let task = cx.spawn(|this, mut cx| {
+        async move {
+            let raw_json = raw_hovers.await.join("\n");
+
+            let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
+            let blocks = vec![HoverBlock {
+                text: raw_json,
+                kind: project::HoverBlockKind::Code {
+                    language: "json".to_string(),
+                },
+            }];
+            let parsed_blocks =
+                parse_blocks(&blocks, &language_registry, None, &this, head, &mut cx).await;
+
+            this.update(&mut cx, |editor, cx| {
+                editor.hover_state.info_popovers = vec![InfoPopover::new(
+                    RangeInEditor::Text(head..head),
+                    Some(head),
+                    true,
+                    parsed_blocks,
+                )];
+                cx.emit(EditorEvent::HoverChanged);
+                cx.notify();
+            })?;
+
+            anyhow::Ok(())
+        }
+        .log_err()
+    });
+
+    editor.hover_state.info_task = Some(task);
 }
 
 /// The internal hover action dispatches between `show_hover` or `hide_hover`
 /// depending on whether a point to hover over is provided.
 pub fn hover_at(editor: &mut Editor, anchor: Option<Anchor>, cx: &mut ViewContext<Editor>) {
-    if EditorSettings::get_global(cx).hover_popover_enabled {
+    hover_at_range(editor, anchor, None, cx);
+}
+
+/// Like [`hover_at`], but while the `hover_enclosing_modifier` key is held, requests the
+/// hover of the syntax node enclosing the hovered symbol rather than of the symbol itself.
+pub fn hover_at_with_modifiers(
+    editor: &mut Editor,
+    anchor: Anchor,
+    modifiers: &Modifiers,
+    cx: &mut ViewContext<Editor>,
+) {
+    let enclosing_range = enclosing_hover_range(editor, anchor, modifiers, cx);
+    let anchor = enclosing_range
+        .as_ref()
+        .map_or(anchor, |enclosing_range| enclosing_range.start);
+    hover_at_range(editor, Some(anchor), enclosing_range, cx);
+}
+
+fn hover_at_range(
+    editor: &mut Editor,
+    anchor: Option<Anchor>,
+    enclosing_range: Option<Range<Anchor>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    // Pinned popovers stay until they are dismissed explicitly, and the others while
+    // the mouse is over them.
+    if (editor.hover_state.pinned || editor.hover_state.mouse_over_popover)
+        && editor.hover_state.visible()
+    {
+        return;
+    }
+    if hover_popover_enabled(editor, anchor, cx) && mouse_hover_enabled(editor, anchor, cx) {
         if show_keyboard_hover(editor, cx) {
             return;
         }
-        if let Some(anchor) = anchor {
-            show_hover(editor, anchor, false, cx);
+        let Some(anchor) = anchor else {
+            hide_hover(editor, cx);
+            return;
+        };
+        let snapshot = editor.snapshot(cx);
+        // Short symbols are still hovered when they're underlined by a diagnostic.
+        if !is_short_symbol(editor, &snapshot.buffer_snapshot, anchor, cx)
+            || has_diagnostic_at(&snapshot.buffer_snapshot, anchor)
+        {
+            show_hover_in_snapshot(editor, snapshot, anchor, enclosing_range, false, cx);
         } else {
             hide_hover(editor, cx);
         }
     }
 }
 
+/// The commands of the tasks a gutter run indicator runs, shown when hovering it
+/// if `hover_code_lens_info` is enabled.
+pub(crate) fn run_indicator_info(
+    tasks: &RunnableTasks,
+    buffer: &MultiBufferSnapshot,
+    cx: &AppContext,
+) -> Option<SharedString> {
+    if !hover_settings_at(buffer, tasks.offset.0, cx).hover_code_lens_info
+        || tasks.templates.is_empty()
+    {
+        return None;
+    }
+    let info = tasks
+        .templates
+        .iter()
+        .map(|(_, template)| {
+            let command = std::iter::once(template.command.as_str())
+                .chain(template.args.iter().map(String::as_str))
+                .join(" ");
+            format!("{}: {command}", template.label)
+        })
+        .join("\n");
+    Some(info.into())
+}
+
+/// Whether the symbol at `anchor` is shorter than `hover_min_symbol_length`.
+fn is_short_symbol(
+    editor: &Editor,
+    snapshot: &MultiBufferSnapshot,
+    anchor: Anchor,
+    cx: &AppContext,
+) -> bool {
+    let min_length = hover_settings(editor, anchor, cx).hover_min_symbol_length as usize;
+    if min_length == 0 {
+        return false;
+    }
+    let Some((buffer, offset)) = snapshot.point_to_buffer_offset(anchor) else {
+        return false;
+    };
+    let Some(node) = buffer
+        .syntax_layer_at(offset)
+        .and_then(|layer| layer.node().named_descendant_for_byte_range(offset, offset))
+    else {
+        return false;
+    };
+    if node.child_count() > 0 {
+        return false;
+    }
+    buffer
+        .text_for_range(node.byte_range())
+        .flat_map(str::chars)
+        .count()
+        < min_length
+}
+
+/// Whether a diagnostic's range contains `anchor`.
+fn has_diagnostic_at(snapshot: &MultiBufferSnapshot, anchor: Anchor) -> bool {
+    snapshot
+        .diagnostics_in_range::<_, usize>(anchor..anchor, false)
+        .next()
+        .is_some()
+}
+
+/// The range of the syntax node enclosing the hovered symbol while the
+/// `hover_enclosing_modifier` key is held, so the language server describes the larger
+/// expression instead of its innermost part.
+fn enclosing_hover_range(
+    editor: &Editor,
+    anchor: Anchor,
+    modifiers: &Modifiers,
+    cx: &AppContext,
+) -> Option<Range<Anchor>> {
+    let modifier_held = match hover_settings(editor, anchor, cx).hover_enclosing_modifier {
+        HoverEnclosingModifier::None => false,
+        HoverEnclosingModifier::Alt => modifiers.alt,
+        HoverEnclosingModifier::CmdOrCtrl => modifiers.secondary(),
+        HoverEnclosingModifier::Shift => modifiers.shift,
+    };
+    if !modifier_held {
+        return None;
+    }
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let offset = anchor.to_offset(&snapshot);
+    let symbol_range = snapshot.range_for_syntax_ancestor(offset..offset)?;
+    let enclosing_range = snapshot.range_for_syntax_ancestor(symbol_range)?;
+    Some(snapshot.anchor_before(enclosing_range.start)..snapshot.anchor_after(enclosing_range.end))
+}
+
 pub fn show_keyboard_hover(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
     let info_popovers = editor.hover_state.info_popovers.clone();
     for p in info_popovers {
         let keyboard_grace = p.keyboard_grace.borrow();
         if *keyboard_grace {
             if let Some(anchor) = p.anchor {
-                show_hover(editor, anchor, false, cx);
+                show_hover(editor, anchor, None, false, cx);
                 return true;
             }
         }
@@ -95,7 +708,10 @@ pub fn find_hovered_hint_part(
 }
 
 pub fn hover_at_inlay(editor: &mut Editor, inlay_hover: InlayHover, cx: &mut ViewContext<Editor>) {
-    if EditorSettings::get_global(cx).hover_popover_enabled {
+    let inlay_position = inlay_hover.range.inlay_position;
+    if hover_popover_enabled(editor, Some(inlay_position), cx)
+        && mouse_hover_enabled(editor, Some(inlay_position), cx)
+    {
         if editor.pending_rename.is_some() {
             return;
         }
@@ -121,10 +737,11 @@ pub fn hover_at_inlay(editor: &mut Editor, inlay_hover: InlayHover, cx: &mut Vie
             hide_hover(editor, cx);
         }
 
+        let hover_delay = hover_settings(editor, inlay_position, cx).hover_popover_delay;
         let task = cx.spawn(|this, mut cx| {
             async move {
                 cx.background_executor()
-                    .timer(Duration::from_millis(HOVER_DELAY_MILLIS))
+                    .timer(Duration::from_millis(hover_delay))
                     .await;
                 this.update(&mut cx, |this, _| {
                     this.hover_state.diagnostic_popover = None;
@@ -132,19 +749,27 @@ pub fn hover_at_inlay(editor: &mut Editor, inlay_hover: InlayHover, cx: &mut Vie
 
                 let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
                 let blocks = vec![inlay_hover.tooltip];
-                let parsed_content = parse_blocks(&blocks, &language_registry, None, &mut cx).await;
-
-                let hover_popover = InfoPopover {
-                    symbol_range: RangeInEditor::Inlay(inlay_hover.range.clone()),
-                    parsed_content,
-                    scroll_handle: ScrollHandle::new(),
-                    keyboard_grace: Rc::new(RefCell::new(false)),
-                    anchor: None,
-                };
+                let parsed_blocks = parse_blocks(
+                    &blocks,
+                    &language_registry,
+                    None,
+                    &this,
+                    inlay_position,
+                    &mut cx,
+                )
+                .await;
+
+                let hover_popover = InfoPopover::new(
+                    RangeInEditor::Inlay(inlay_hover.range.clone()),
+                    None,
+                    false,
+                    parsed_blocks,
+                );
 
                 this.update(&mut cx, |this, cx| {
                     // TODO: no background highlights happen for inlays currently
                     this.hover_state.info_popovers = vec![hover_popover];
+                    cx.emit(EditorEvent::HoverChanged);
                     cx.notify();
                 })?;
 
@@ -157,32 +782,132 @@ pub fn hover_at_inlay(editor: &mut Editor, inlay_hover: InlayHover, cx: &mut Vie
     }
 }
 
+/// Hides the popovers unless they were pinned with [`PinHover`], for dismissals that
+/// aren't explicitly asked for, like moving the cursor, scrolling, or blurring the editor.
+pub fn hide_unpinned_hover(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    if editor.hover_state.pinned {
+        return false;
+    }
+    hide_hover(editor, cx)
+}
+
 /// Hides the type information popup.
 /// Triggered by the `Hover` action when the cursor is not over a symbol or when the
 /// selections changed.
 pub fn hide_hover(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
-    let info_popovers = editor.hover_state.info_popovers.drain(..);
-    let diagnostics_popover = editor.hover_state.diagnostic_popover.take();
-    let did_hide = info_popovers.count() > 0 || diagnostics_popover.is_some();
-
+    let buffer = editor.buffer.read(cx).snapshot(cx);
+    let anchor = editor.hover_state.popover_anchor(&buffer, cx);
+    let dismissed_ranges = editor
+        .hover_state
+        .info_popovers
+        .iter()
+        .filter_map(|info_popover| info_popover.symbol_range.as_text_range())
+        .chain(
+            editor
+                .hover_state
+                .diagnostic_popover
+                .iter()
+                .map(|diagnostic_popover| diagnostic_popover.local_diagnostic.range.clone()),
+        )
+        .collect::<Vec<_>>();
+    let info_popovers = editor
+        .hover_state
+        .info_popovers
+        .drain(..)
+        .collect::<Vec<_>>();
+    let diagnostic_popover = editor.hover_state.diagnostic_popover.take();
+    let did_hide = !info_popovers.is_empty() || diagnostic_popover.is_some();
+
+    // Dropping the tasks sends `$/cancelRequest` for the language server requests that
+    // are still in flight.
     editor.hover_state.info_task = None;
+    editor.hover_state.live_update_task = None;
     editor.hover_state.triggered_from = None;
+    editor.hover_state.pinned = false;
+    editor.hover_state.popover_bounds.clear();
+    editor.hover_state.mouse_over_popover = false;
+    editor.hover_state.focused_link = None;
 
     editor.clear_background_highlights::<HoverState>(cx);
 
     if did_hide {
+        editor.hover_state.last_dismissed =
+            Some((dismissed_ranges, cx.background_executor().now()));
+        fade_out_hover(editor, anchor, info_popovers, diagnostic_popover, cx);
+        cx.emit(EditorEvent::HoverChanged);
         cx.notify();
     }
 
     did_hide
 }
 
+/// Keeps the hidden popovers on screen while they fade out, removing them once
+/// `hover_fade_out_millis` have elapsed. Does nothing when the system asks to
+/// reduce motion.
+fn fade_out_hover(
+    editor: &mut Editor,
+    anchor: Option<Anchor>,
+    info_popovers: Vec<InfoPopover>,
+    diagnostic_popover: Option<DiagnosticPopover>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some((anchor, duration)) = anchor
+        .map(|anchor| {
+            let fade_out_millis = hover_settings(editor, anchor, cx).hover_fade_out_millis;
+            (anchor, Duration::from_millis(fade_out_millis))
+        })
+        .filter(|(_, duration)| !duration.is_zero() && !cx.should_reduce_motion())
+    else {
+        editor.hover_state.dismissing = None;
+        return;
+    };
+
+    let task = cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(duration).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.hover_state.dismissing = None;
+                cx.notify();
+            })
+            .ok();
+    });
+    editor.hover_state.dismissing = Some(DismissingHover {
+        info_popovers,
+        diagnostic_popover,
+        anchor,
+        duration,
+        _task: task,
+    });
+}
+
 /// Queries the LSP and shows type info and documentation
 /// about the symbol the mouse is currently hovering over.
 /// Triggered by the `Hover` action when the cursor may be over a symbol.
+/// When an `enclosing_range` is given, the hover of that range is requested instead.
 fn show_hover(
     editor: &mut Editor,
     anchor: Anchor,
+    enclosing_range: Option<Range<Anchor>>,
+    ignore_timeout: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.snapshot(cx);
+    show_hover_in_snapshot(
+        editor,
+        snapshot,
+        anchor,
+        enclosing_range,
+        ignore_timeout,
+        cx,
+    );
+}
+
+/// Like [`show_hover`], reusing a snapshot of the editor the caller already took.
+fn show_hover_in_snapshot(
+    editor: &mut Editor,
+    snapshot: EditorSnapshot,
+    anchor: Anchor,
+    enclosing_range: Option<Range<Anchor>>,
     ignore_timeout: bool,
     cx: &mut ViewContext<Editor>,
 ) {
@@ -190,8 +915,6 @@ fn show_hover(
         return;
     }
 
-    let snapshot = editor.snapshot(cx);
-
     let (buffer, buffer_position) =
         if let Some(output) = editor.buffer.read(cx).text_anchor_for_position(anchor, cx) {
             output
@@ -212,21 +935,136 @@ fn show_hover(
         return;
     };
 
-    if !ignore_timeout {
-        if editor
+    let buffer_version = buffer.read(cx).version();
+    let buffer_id = buffer.read(cx).remote_id();
+    let settings = hover_settings(editor, anchor, cx).clone();
+    let remember_last = settings.hover_remember_last;
+    let highlight_empty_range = settings.hover_highlight_empty_range;
+    let hover_delay = settings.hover_popover_delay;
+    // A delay of 0 shows the popover as soon as the hover is resolved. Unlike hovers that
+    // ignore the timeout, it's still hidden when the mouse moves to another symbol.
+    let skip_delay = ignore_timeout || hover_delay == 0;
+    let show_keyword_docs = settings.hover_keyword_docs;
+    let attribute_docs = if settings.hover_attribute_docs {
+        attribute_docs_at(&buffer.read(cx).snapshot(), buffer_position)
+    } else {
+        None
+    };
+    let regex_explanation = if settings.hover_regex_explain {
+        regex_explanation_at(&buffer.read(cx).snapshot(), buffer_position)
+    } else {
+        None
+    };
+    // Counting the occurrences of the hovered word scans the whole buffer, so it's only
+    // done in the background once the hover turns out to be empty.
+    let empty_fallback_buffer = settings
+        .hover_empty_fallback
+        .then(|| buffer.read(cx).snapshot());
+    let (const_name, mut const_initializer) = if settings.hover_const_value {
+        let snapshot = buffer.read(cx).snapshot();
+        let (range, kind) = snapshot.surrounding_word(buffer_position);
+        let name = (kind == Some(CharKind::Word))
+            .then(|| snapshot.text_for_range(range).collect::<String>());
+        (name, const_initializer_at(&snapshot, buffer_position))
+    } else {
+        (None, None)
+    };
+    let show_raw_doc_comment = settings.hover_raw_doc_comment;
+    let show_import_suggestions = settings.hover_import_suggestions;
+    let show_prominent_quick_fix = settings.hover_prominent_quickfix;
+    let show_dependency_source = settings.hover_dependency_source;
+    let retrigger_cooldown = Duration::from_millis(settings.hover_retrigger_cooldown_millis);
+    // Within a method chain, only query the segment the mouse settles on.
+    let request_delay = if settings.hover_chain_debounce
+        && is_in_method_chain(&buffer.read(cx).snapshot(), buffer_position)
+    {
+        hover_delay
+    } else {
+        HOVER_REQUEST_DELAY_MILLIS.min(hover_delay)
+    };
+    let test_status = if settings.hover_test_status {
+        last_test_run(editor, &project, &buffer, buffer_position, cx)
+    } else {
+        None
+    };
+    // Servers that are notified of edits hover the unsaved text, only warn about the others.
+    let stale_warning = settings.hover_stale_warning
+        && buffer.read(cx).is_dirty()
+        && project
+            .read(cx)
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .any(|(_, server)| !syncs_document_changes(&server.capabilities()));
+    let doctest_context = if settings.hover_run_doctests {
+        doctest_context(&project, &buffer, buffer_position, cx)
+    } else {
+        None
+    };
+    let symbol_kind = if settings.hover_show_symbol_kind {
+        Some(project.update(cx, |project, cx| {
+            project.symbol_kind(&buffer, buffer_position, cx)
+        }))
+    } else {
+        None
+    };
+    // Hovers are cached by buffer rather than by excerpt, so that every excerpt of a
+    // buffer shares them.
+    let cache_hovers = settings.hover_cache && enclosing_range.is_none();
+    let enclosing_range =
+        enclosing_range.map(|range| range.start.text_anchor..range.end.text_anchor);
+    let buffer_offset = buffer
+        .read(cx)
+        .snapshot()
+        .summary_for_anchor::<usize>(&buffer_position);
+    let cached_hovers = if cache_hovers {
+        editor
             .hover_state
-            .info_popovers
-            .iter()
-            .any(|InfoPopover { symbol_range, .. }| {
-                symbol_range
-                    .as_text_range()
-                    .map(|range| {
-                        let hover_range = range.to_offset(&snapshot.buffer_snapshot);
-                        let offset = anchor.to_offset(&snapshot.buffer_snapshot);
-                        // LSP returns a hover result for the end index of ranges that should be hovered, so we need to
-                        // use an inclusive range here to check if we should dismiss the popover
-                        (hover_range.start..=hover_range.end).contains(&offset)
-                    })
+            .cached_hovers(buffer_id, buffer_offset, &buffer_version)
+    } else {
+        None
+    };
+    // Diagnostics of a bracket take precedence over its match, so that they stay visible.
+    let bracket_match = if settings.hover_bracket_match
+        && snapshot
+            .buffer_snapshot
+            .diagnostics_in_range::<_, usize>(anchor..anchor, false)
+            .next()
+            .is_none()
+    {
+        matching_bracket_at(
+            &snapshot.buffer_snapshot,
+            anchor.to_offset(&snapshot.buffer_snapshot),
+        )
+    } else {
+        None
+    };
+
+    // Hovering a symbol again right after its popover was hidden is likely accidental.
+    if !ignore_timeout
+        && editor.hover_state.dismissed_recently(
+            anchor,
+            retrigger_cooldown,
+            &snapshot.buffer_snapshot,
+            cx,
+        )
+    {
+        return;
+    }
+
+    if !ignore_timeout {
+        if editor
+            .hover_state
+            .info_popovers
+            .iter()
+            .any(|InfoPopover { symbol_range, .. }| {
+                symbol_range
+                    .as_text_range()
+                    .map(|range| {
+                        let hover_range = range.to_offset(&snapshot.buffer_snapshot);
+                        let offset = anchor.to_offset(&snapshot.buffer_snapshot);
+                        // LSP returns a hover result for the end index of ranges that should be hovered, so we need to
+                        // use an inclusive range here to check if we should dismiss the popover
+                        (hover_range.start..=hover_range.end).contains(&offset)
+                    })
                     .unwrap_or(false)
             })
         {
@@ -247,45 +1085,76 @@ fn show_hover(
         }
     }
 
+    // A hovered bracket shows the line of its match rather than the language server's hover.
+    if let Some((bracket_range, match_range)) = bracket_match {
+        show_bracket_match_hover(
+            editor,
+            anchor,
+            bracket_range,
+            match_range,
+            ignore_timeout,
+            (!skip_delay).then_some(hover_delay),
+            project,
+            cx,
+        );
+        return;
+    }
+
     let task = cx.spawn(|this, mut cx| {
         async move {
             // If we need to delay, delay a set amount initially before making the lsp request
-            let delay = if ignore_timeout {
+            let delay = if skip_delay {
                 None
             } else {
                 // Construct delay task to wait for later
                 let total_delay = Some(
                     cx.background_executor()
-                        .timer(Duration::from_millis(HOVER_DELAY_MILLIS)),
+                        .timer(Duration::from_millis(hover_delay)),
                 );
 
                 cx.background_executor()
-                    .timer(Duration::from_millis(HOVER_REQUEST_DELAY_MILLIS))
+                    .timer(Duration::from_millis(request_delay))
                     .await;
                 total_delay
             };
 
             // query the LSP for hover info
-            let hover_request = cx.update(|cx| {
-                project.update(cx, |project, cx| {
-                    project.hover(&buffer, buffer_position, cx)
-                })
-            })?;
+            let hover_request = match cached_hovers {
+                Some(hovers) => Task::ready(hovers),
+                None => cx.update(|cx| {
+                    project.update(cx, |project, cx| match enclosing_range {
+                        Some(enclosing_range) => project.hover_range(&buffer, enclosing_range, cx),
+                        None => project.hover(&buffer, buffer_position, cx),
+                    })
+                })?,
+            };
+            let definition_request = if show_raw_doc_comment || show_dependency_source {
+                Some(cx.update(|cx| {
+                    project.update(cx, |project, cx| {
+                        project.definition(&buffer, buffer_position, cx)
+                    })
+                })?)
+            } else {
+                None
+            };
 
             if let Some(delay) = delay {
                 delay.await;
             }
 
-            // If there's a diagnostic, assign it on the hover state and notify
-            let local_diagnostic = snapshot
+            // If there are diagnostics, assign them on the hover state and notify. The most
+            // severe one with the most specific range comes first, and the others are stacked
+            // below it.
+            let mut diagnostics = snapshot
                 .buffer_snapshot
                 .diagnostics_in_range::<_, usize>(anchor..anchor, false)
-                // Find the entry with the most specific range
-                .min_by_key(|entry| entry.range.end - entry.range.start)
+                .sorted_by_key(|entry| (entry.diagnostic.severity, entry.range.len()))
                 .map(|entry| DiagnosticEntry {
                     diagnostic: entry.diagnostic,
                     range: entry.range.to_anchors(&snapshot.buffer_snapshot),
                 });
+            let local_diagnostic = diagnostics.next();
+            let stacked_diagnostics = diagnostics.collect::<Vec<_>>();
 
             // Pull the primary diagnostic out so we can jump to it if the popover is clicked
             let primary_diagnostic = local_diagnostic.as_ref().and_then(|local_diagnostic| {
@@ -299,21 +1168,259 @@ fn show_hover(
                     })
             });
 
+            // The language server's related information in the same file is stored as the
+            // other diagnostics of the group, which link to their locations unless the
+            // popover already shows them. Related information in other files is kept on the
+            // primary diagnostic.
+            let related_information = local_diagnostic
+                .as_ref()
+                .map(|local_diagnostic| {
+                    let buffer = &snapshot.buffer_snapshot;
+                    let shown_diagnostics = std::iter::once(local_diagnostic)
+                        .chain(&stacked_diagnostics)
+                        .map(|entry| (entry.range.to_offset(buffer), &entry.diagnostic.message))
+                        .collect::<Vec<_>>();
+                    let in_buffer = buffer
+                        .diagnostic_group::<usize>(local_diagnostic.diagnostic.group_id)
+                        .filter(|entry| {
+                            !shown_diagnostics.iter().any(|(range, message)| {
+                                *range == entry.range && **message == entry.diagnostic.message
+                            })
+                        })
+                        .map(|entry| RelatedInformation {
+                            location: format!(
+                                "line {}",
+                                buffer.offset_to_point(entry.range.start).row + 1
+                            ),
+                            message: entry.diagnostic.message,
+                            target: RelatedTarget::Buffer(buffer.anchor_before(entry.range.start)),
+                        });
+                    let in_other_files = primary_diagnostic
+                        .iter()
+                        .flat_map(|primary| primary.diagnostic.related_locations.iter())
+                        .map(|location| RelatedInformation {
+                            location: format!(
+                                "{}:{}",
+                                location
+                                    .abs_path
+                                    .file_name()
+                                    .unwrap_or(location.abs_path.as_os_str())
+                                    .to_string_lossy(),
+                                location.position.0.row + 1
+                            ),
+                            message: location.message.clone(),
+                            target: RelatedTarget::File(
+                                location.abs_path.clone(),
+                                location.position,
+                            ),
+                        });
+                    in_buffer.chain(in_other_files).collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            // Offer the imports the language server suggests for unresolved symbols, the
+            // quick fix of errors, and the code actions menu when there are any actions
+            let suggest_imports = local_diagnostic.as_ref().map_or(false, |local_diagnostic| {
+                show_import_suggestions && is_unresolved_symbol(&local_diagnostic.diagnostic)
+            });
+            let suggest_quick_fix = local_diagnostic.as_ref().map_or(false, |local_diagnostic| {
+                show_prominent_quick_fix
+                    && local_diagnostic.diagnostic.severity == DiagnosticSeverity::ERROR
+            });
+            let code_actions_request = local_diagnostic
+                .as_ref()
+                .map(|local_diagnostic| {
+                    let range = local_diagnostic.range.start.text_anchor
+                        ..local_diagnostic.range.end.text_anchor;
+                    project.update(&mut cx, |project, cx| {
+                        project.code_actions(&buffer, range, cx)
+                    })
+                })
+                .transpose()?;
+
             this.update(&mut cx, |this, _| {
                 this.hover_state.diagnostic_popover =
                     local_diagnostic.map(|local_diagnostic| DiagnosticPopover {
                         local_diagnostic,
                         primary_diagnostic,
+                        stacked_diagnostics,
+                        related_information,
+                        hovered_point: anchor,
+                        trace_expanded: Rc::new(RefCell::new(false)),
+                        message_expanded: Rc::new(RefCell::new(false)),
+                        import_suggestions: None,
+                        quick_fix: None,
+                        has_code_actions: false,
                     });
             })?;
 
-            let hovers_response = hover_request.await;
+            // The code actions are filled in when the language server answers, while the info
+            // popovers are requested and shown.
+            let code_actions_task = code_actions_request.map(|code_actions_request| {
+                let this = this.clone();
+                let buffer = buffer.clone();
+                cx.spawn(|mut cx| async move {
+                    let actions = code_actions_request.await;
+                    let has_code_actions = !actions.is_empty();
+                    let quick_fix = prominent_quick_fix(&actions).filter(|_| suggest_quick_fix);
+                    let imports = actions
+                        .into_iter()
+                        .filter(|action| suggest_imports && is_import_action(action))
+                        .filter(|action| {
+                            quick_fix.as_ref().map_or(true, |quick_fix| {
+                                quick_fix.lsp_action.title != action.lsp_action.title
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    if !imports.is_empty() || quick_fix.is_some() || has_code_actions {
+                        this.update(&mut cx, |this, cx| {
+                            if let Some(diagnostic_popover) =
+                                &mut this.hover_state.diagnostic_popover
+                            {
+                                diagnostic_popover.has_code_actions = has_code_actions;
+                                if !imports.is_empty() {
+                                    diagnostic_popover.import_suggestions =
+                                        Some(ImportSuggestions {
+                                            buffer: buffer.clone(),
+                                            actions: imports,
+                                        });
+                                }
+                                diagnostic_popover.quick_fix =
+                                    quick_fix.map(|action| (buffer.clone(), action));
+                                cx.notify();
+                            }
+                        })?;
+                    }
+                    anyhow::Ok(())
+                })
+            });
+
             let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
+            let test_status = match test_status {
+                Some(test_status) => test_status.await,
+                None => None,
+            };
+            let symbol_kind = match symbol_kind {
+                Some(symbol_kind) => symbol_kind.await,
+                None => None,
+            };
+            let definition = match definition_request {
+                Some(definition_request) => definition_request
+                    .await
+                    .log_err()
+                    .and_then(|definitions| definitions.into_iter().next()),
+                None => None,
+            };
+            let raw_doc_comment = definition
+                .as_ref()
+                .filter(|_| show_raw_doc_comment)
+                .and_then(|definition| definition_doc_comment(definition, &cx));
+            let dependency_source = definition
+                .filter(|_| show_dependency_source)
+                .map(|definition| definition.target)
+                .filter(|target| {
+                    project
+                        .read_with(&cx, |project, cx| {
+                            is_dependency_source(project, target.buffer.read(cx), cx)
+                        })
+                        .unwrap_or(false)
+                });
+            let mut hovers_response = hover_request.await;
+            if cache_hovers {
+                this.update(&mut cx, |this, cx| {
+                    this.hover_state.cache_hovers(
+                        &buffer.read(cx).snapshot(),
+                        buffer_offset,
+                        buffer_version.clone(),
+                        hovers_response.clone(),
+                    )
+                })?;
+            }
+            // Inside an injected language the point may only resolve to the host's string, so
+            // ask the servers of the injected language for the embedded expression itself.
+            if hovers_response.is_empty() {
+                let injected_position = buffer.read_with(&cx, |buffer, _| {
+                    injected_expression_position(&buffer.snapshot(), buffer_position)
+                })?;
+                if let Some((injected_language, injected_position)) = injected_position {
+                    hovers_response = cx
+                        .update(|cx| {
+                            project.update(cx, |project, cx| {
+                                project.hover_for_language(
+                                    &buffer,
+                                    injected_position,
+                                    &injected_language,
+                                    cx,
+                                )
+                            })
+                        })?
+                        .await;
+                }
+            }
             let snapshot = this.update(&mut cx, |this, cx| this.snapshot(cx))?;
             let mut hover_highlights = Vec::with_capacity(hovers_response.len());
             let mut info_popovers = Vec::with_capacity(hovers_response.len());
             let mut info_popover_tasks = Vec::with_capacity(hovers_response.len());
 
+            if let Some((task_source_kind, resolved_task, result)) = test_status {
+                let test_run_popover = synthetic_info_popover(
+                    &[test_run_block(result)],
+                    anchor..anchor,
+                    anchor,
+                    ignore_timeout,
+                    &language_registry,
+                    &this,
+                    &mut cx,
+                )
+                .await;
+                info_popovers.push(InfoPopover {
+                    test_task: Some((task_source_kind, resolved_task)),
+                    ..test_run_popover
+                });
+            }
+
+            // The raw doc comment replaces the documentation of the language server's hover,
+            // keeping its signature, while the last run of a hovered test is shown above it.
+            if let Some((origin, doc_comment)) = raw_doc_comment {
+                match hovers_response.iter_mut().find(|hover| !hover.is_empty()) {
+                    Some(hover) => {
+                        hover.contents =
+                            merge_raw_doc_comment(std::mem::take(&mut hover.contents), doc_comment);
+                    }
+                    None => hovers_response.push(project::Hover {
+                        contents: vec![HoverBlock {
+                            text: doc_comment,
+                            kind: project::HoverBlockKind::Markdown,
+                        }],
+                        range: origin.map(|origin| origin.range),
+                        language: None,
+                    }),
+                }
+            }
+
+            let doctest = match doctest_context {
+                Some((symbol, task_context))
+                    if hovers_response
+                        .iter()
+                        .any(|hover| hover_has_runnable_doctest(&hover.contents)) =>
+                {
+                    let inventory =
+                        project.update(&mut cx, |project, _| project.task_inventory().clone())?;
+                    task_context
+                        .await
+                        .and_then(|task_context| doctest_task(&symbol, &task_context))
+                        .map(|resolved_task| HoverDoctest {
+                            task_source_kind: TaskSourceKind::Language {
+                                name: "Rust".into(),
+                            },
+                            resolved_task,
+                            inventory,
+                            run: Rc::default(),
+                        })
+                }
+                _ => None,
+            };
+
             for hover_result in hovers_response {
                 // Create symbol range of anchors for highlighting and filtering of future requests.
                 let range = hover_result
@@ -330,43 +1437,192 @@ fn show_hover(
                     })
                     .unwrap_or_else(|| anchor..anchor);
 
-                let blocks = hover_result.contents;
+                if hover_result.contents.is_empty() {
+                    // The server only reported a symbol range, there is nothing to show.
+                    if highlight_empty_range {
+                        hover_highlights.push(range);
+                    }
+                    continue;
+                }
+
+                let mut blocks = hover_result.contents;
+                // The constant's value leads the hover, falling back to its initializer once.
+                let const_value = const_name
+                    .as_deref()
+                    .and_then(|name| const_value_from_hover(&blocks, name))
+                    .or_else(|| const_initializer.take());
+                if let Some((name, value)) = const_value {
+                    blocks.insert(
+                        0,
+                        HoverBlock {
+                            text: format!("**`{name} = {value}`**"),
+                            kind: project::HoverBlockKind::Markdown,
+                        },
+                    );
+                }
                 let language = hover_result.language;
-                let parsed_content =
-                    parse_blocks(&blocks, &language_registry, language, &mut cx).await;
+                let doctest = doctest
+                    .clone()
+                    .filter(|_| hover_has_runnable_doctest(&blocks));
+                let parsed_blocks = parse_blocks(
+                    &blocks,
+                    &language_registry,
+                    language,
+                    &this,
+                    anchor,
+                    &mut cx,
+                )
+                .await;
                 info_popover_tasks.push((
                     range.clone(),
                     InfoPopover {
-                        symbol_range: RangeInEditor::Text(range),
-                        parsed_content,
-                        scroll_handle: ScrollHandle::new(),
-                        keyboard_grace: Rc::new(RefCell::new(ignore_timeout)),
-                        anchor: Some(anchor),
+                        buffer_version: Some(buffer_version.clone()),
+                        dependency_source: dependency_source.clone(),
+                        doctest,
+                        stale_warning,
+                        symbol_kind,
+                        ..InfoPopover::new(
+                            RangeInEditor::Text(range),
+                            Some(anchor),
+                            ignore_timeout,
+                            parsed_blocks,
+                        )
                     },
                 ));
             }
+            if let Some((range, explanation)) = regex_explanation {
+                let range = snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(excerpt_id, range.start)
+                    .zip(
+                        snapshot
+                            .buffer_snapshot
+                            .anchor_in_excerpt(excerpt_id, range.end),
+                    )
+                    .map_or(anchor..anchor, |(start, end)| start..end);
+                let blocks = [HoverBlock {
+                    text: explanation,
+                    kind: project::HoverBlockKind::Markdown,
+                }];
+                let info_popover = synthetic_info_popover(
+                    &blocks,
+                    range.clone(),
+                    anchor,
+                    ignore_timeout,
+                    &language_registry,
+                    &this,
+                    &mut cx,
+                )
+                .await;
+                info_popover_tasks.push((range, info_popover));
+            }
+            if let Some((range, docs)) = attribute_docs {
+                let range = snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(excerpt_id, range.start)
+                    .zip(
+                        snapshot
+                            .buffer_snapshot
+                            .anchor_in_excerpt(excerpt_id, range.end),
+                    )
+                    .map_or(anchor..anchor, |(start, end)| start..end);
+                let blocks = [HoverBlock {
+                    text: docs.to_string(),
+                    kind: project::HoverBlockKind::Markdown,
+                }];
+                let info_popover = synthetic_info_popover(
+                    &blocks,
+                    range.clone(),
+                    anchor,
+                    ignore_timeout,
+                    &language_registry,
+                    &this,
+                    &mut cx,
+                )
+                .await;
+                info_popover_tasks.push((range, info_popover));
+            }
+            if show_keyword_docs && info_popovers.is_empty() && info_popover_tasks.is_empty() {
+                if let Some((range, docs)) = keyword_docs_at(&snapshot.buffer_snapshot, anchor) {
+                    let blocks = [HoverBlock {
+                        text: docs.to_string(),
+                        kind: project::HoverBlockKind::Markdown,
+                    }];
+                    let info_popover = synthetic_info_popover(
+                        &blocks,
+                        range.clone(),
+                        anchor,
+                        ignore_timeout,
+                        &language_registry,
+                        &this,
+                        &mut cx,
+                    )
+                    .await;
+                    info_popover_tasks.push((range, info_popover));
+                }
+            }
+            // Without anything else to show, at least show how often the hovered word is used.
+            if info_popovers.is_empty() && info_popover_tasks.is_empty() {
+                let empty_fallback = match empty_fallback_buffer {
+                    Some(buffer) => {
+                        cx.background_executor()
+                            .spawn(async move { word_occurrences_at(&buffer, buffer_position) })
+                            .await
+                    }
+                    None => None,
+                };
+                if let Some((range, text)) = empty_fallback {
+                    let range = snapshot
+                        .buffer_snapshot
+                        .anchor_in_excerpt(excerpt_id, range.start)
+                        .zip(
+                            snapshot
+                                .buffer_snapshot
+                                .anchor_in_excerpt(excerpt_id, range.end),
+                        )
+                        .map_or(anchor..anchor, |(start, end)| start..end);
+                    let blocks = [HoverBlock {
+                        text,
+                        kind: project::HoverBlockKind::Markdown,
+                    }];
+                    let info_popover = synthetic_info_popover(
+                        &blocks,
+                        range.clone(),
+                        anchor,
+                        ignore_timeout,
+                        &language_registry,
+                        &this,
+                        &mut cx,
+                    )
+                    .await;
+                    info_popover_tasks.push((range, info_popover));
+                }
+            }
             for (highlight_range, info_popover) in info_popover_tasks {
                 hover_highlights.push(highlight_range);
                 info_popovers.push(info_popover);
             }
 
             this.update(&mut cx, |editor, cx| {
-                if hover_highlights.is_empty() {
-                    editor.clear_background_highlights::<HoverState>(cx);
-                } else {
-                    // Highlight the selected symbol using a background highlight
-                    editor.highlight_background::<HoverState>(
-                        &hover_highlights,
-                        |theme| theme.element_hover, // todo update theme
-                        cx,
-                    );
-                }
+                update_hover_highlights(editor, &hover_highlights, cx);
 
+                if remember_last && !info_popovers.is_empty() {
+                    editor
+                        .hover_state
+                        .last_info_popovers
+                        .insert(buffer_id, info_popovers.clone());
+                }
                 editor.hover_state.info_popovers = info_popovers;
+                editor.hover_state.focused_link = None;
+                cx.emit(EditorEvent::HoverChanged);
                 cx.notify();
                 cx.refresh();
             })?;
 
+            if let Some(code_actions_task) = code_actions_task {
+                code_actions_task.await?;
+            }
+
             anyhow::Ok(())
         }
         .log_err()
@@ -375,412 +1631,10172 @@ fn show_hover(
     editor.hover_state.info_task = Some(task);
 }
 
-async fn parse_blocks(
-    blocks: &[HoverBlock],
-    language_registry: &Arc<LanguageRegistry>,
-    language: Option<Arc<Language>>,
-    cx: &mut AsyncWindowContext,
-) -> Option<View<Markdown>> {
-    let fallback_language_name = if let Some(ref l) = language {
-        let l = Arc::clone(l);
-        Some(l.lsp_id().clone())
-    } else {
-        None
+/// The icon and name shown in hover popovers for the given kind of symbol.
+fn symbol_kind_icon(kind: lsp::SymbolKind) -> Option<(IconName, &'static str)> {
+    let (icon, name) = match kind {
+        lsp::SymbolKind::FUNCTION => (IconName::Code, "function"),
+        lsp::SymbolKind::METHOD => (IconName::Code, "method"),
+        lsp::SymbolKind::CONSTRUCTOR => (IconName::Code, "constructor"),
+        lsp::SymbolKind::OPERATOR => (IconName::Code, "operator"),
+        lsp::SymbolKind::STRUCT => (IconName::ListTree, "struct"),
+        lsp::SymbolKind::CLASS => (IconName::ListTree, "class"),
+        lsp::SymbolKind::OBJECT => (IconName::ListTree, "object"),
+        lsp::SymbolKind::INTERFACE => (IconName::ListTree, "interface"),
+        lsp::SymbolKind::ENUM => (IconName::ListTree, "enum"),
+        lsp::SymbolKind::ENUM_MEMBER => (IconName::Hash, "enum member"),
+        lsp::SymbolKind::CONSTANT => (IconName::Hash, "constant"),
+        lsp::SymbolKind::VARIABLE => (IconName::Hash, "variable"),
+        lsp::SymbolKind::FIELD => (IconName::Hash, "field"),
+        lsp::SymbolKind::PROPERTY => (IconName::Hash, "property"),
+        lsp::SymbolKind::TYPE_PARAMETER => (IconName::Hash, "type parameter"),
+        lsp::SymbolKind::MODULE => (IconName::Folder, "module"),
+        lsp::SymbolKind::NAMESPACE => (IconName::Folder, "namespace"),
+        lsp::SymbolKind::PACKAGE => (IconName::Folder, "package"),
+        lsp::SymbolKind::FILE => (IconName::File, "file"),
+        _ => return None,
     };
+    Some((icon, name))
+}
 
-    let combined_text = blocks
-        .iter()
-        .map(|block| match &block.kind {
-            project::HoverBlockKind::PlainText | project::HoverBlockKind::Markdown => {
-                Cow::Borrowed(block.text.trim())
+/// Local images larger than this aren't rendered in hover popovers.
+const MAX_HOVER_IMAGE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// The local image files linked from the markdown of the given blocks that exist and are small
+/// enough to render, by the destination of their links. Relative paths are resolved against
+/// the given directory.
+async fn local_hover_images(
+    blocks: &[HoverBlock],
+    dir: &Path,
+    fs: &Arc<dyn Fs>,
+) -> HashMap<SharedString, PathBuf> {
+    let mut images = HashMap::default();
+    for block in blocks {
+        if block.kind != project::HoverBlockKind::Markdown {
+            continue;
+        }
+        for (_, event) in parse_markdown(&block.text) {
+            let MarkdownEvent::Start(MarkdownTag::Image { dest_url, .. }) = event else {
+                continue;
+            };
+            let Some(path) = local_image_path(&dest_url, dir) else {
+                continue;
+            };
+            let is_image = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map_or(false, |extension| {
+                    Img::extensions()
+                        .iter()
+                        .any(|supported| extension.eq_ignore_ascii_case(supported))
+                });
+            if !is_image || images.contains_key(&dest_url) {
+                continue;
             }
-            project::HoverBlockKind::Code { language } => {
-                Cow::Owned(format!("```{}\n{}\n```", language, block.text.trim()))
+            if let Ok(Some(metadata)) = fs.metadata(&path).await {
+                if !metadata.is_dir && metadata.len <= MAX_HOVER_IMAGE_BYTES {
+                    images.insert(dest_url, path);
+                }
             }
-        })
-        .join("\n\n");
-
-    let rendered_block = cx
-        .new_view(|cx| {
-            let settings = ThemeSettings::get_global(cx);
-            let buffer_font_family = settings.buffer_font.family.clone();
-            let mut base_style = cx.text_style();
-            base_style.refine(&TextStyleRefinement {
-                font_family: Some(buffer_font_family.clone()),
-                color: Some(cx.theme().colors().editor_foreground),
-                ..Default::default()
-            });
+        }
+    }
+    images
+}
 
-            let markdown_style = MarkdownStyle {
-                base_text_style: base_style,
-                code_block: StyleRefinement::default().mt(rems(1.)).mb(rems(1.)),
-                inline_code: TextStyleRefinement {
-                    background_color: Some(cx.theme().colors().background),
-                    ..Default::default()
-                },
-                rule_color: Color::Muted.color(cx),
-                block_quote_border_color: Color::Muted.color(cx),
-                block_quote: TextStyleRefinement {
-                    color: Some(Color::Muted.color(cx)),
-                    ..Default::default()
-                },
-                link: TextStyleRefinement {
-                    color: Some(cx.theme().colors().editor_foreground),
-                    underline: Some(gpui::UnderlineStyle {
-                        thickness: px(1.),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        wavy: false,
-                    }),
-                    ..Default::default()
-                },
-                syntax: cx.theme().syntax().clone(),
-                selection_background_color: { cx.theme().players().local().selection },
-                break_style: Default::default(),
-                heading: StyleRefinement::default()
-                    .font_weight(FontWeight::BOLD)
-                    .text_base()
-                    .mt(rems(1.))
-                    .mb_0(),
-            };
+/// The path of an image link's destination, when it is a local file rather than a web URL.
+fn local_image_path(dest_url: &str, dir: &Path) -> Option<PathBuf> {
+    if dest_url.starts_with("file://") {
+        return url::Url::parse(dest_url).ok()?.to_file_path().ok();
+    }
+    if dest_url.contains("://") || dest_url.starts_with("data:") || dest_url.is_empty() {
+        return None;
+    }
+    Some(dir.join(dest_url))
+}
 
-            Markdown::new(
-                combined_text,
-                markdown_style.clone(),
-                Some(language_registry.clone()),
-                cx,
-                fallback_language_name,
-            )
+/// The range of the bracket at the given offset and the range of the bracket it matches.
+fn matching_bracket_at(
+    buffer: &MultiBufferSnapshot,
+    offset: usize,
+) -> Option<(Range<usize>, Range<usize>)> {
+    buffer
+        .enclosing_bracket_ranges(offset..offset)?
+        .find_map(|(open, close)| {
+            if open.contains(&offset) {
+                Some((open, close))
+            } else if close.contains(&offset) {
+                Some((close, open))
+            } else {
+                None
+            }
         })
-        .ok();
-
-    rendered_block
 }
 
-#[derive(Default, Debug)]
-pub struct HoverState {
-    pub info_popovers: Vec<InfoPopover>,
-    pub diagnostic_popover: Option<DiagnosticPopover>,
-    pub triggered_from: Option<Anchor>,
-    pub info_task: Option<Task<Option<()>>>,
-}
+/// Shows a popover with the line of the bracket matching the hovered one, which
+/// navigates to it when clicked.
+#[allow(clippy::too_many_arguments)]
+fn show_bracket_match_hover(
+    editor: &mut Editor,
+    anchor: Anchor,
+    bracket_range: Range<usize>,
+    match_range: Range<usize>,
+    ignore_timeout: bool,
+    hover_delay: Option<u64>,
+    project: Model<Project>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let buffer = editor.buffer().read(cx).snapshot(cx);
+    let match_row = buffer.offset_to_point(match_range.start).row;
+    let line: String = buffer
+        .text_for_range(
+            Point::new(match_row, 0)
+                ..Point::new(match_row, buffer.line_len(MultiBufferRow(match_row))),
+        )
+        .collect();
+    let text = format!(
+        "Matches `{}` on line {}\n\n```\n{}\n```",
+        buffer
+            .text_for_range(match_range.clone())
+            .collect::<String>(),
+        match_row + 1,
+        line.trim()
+    );
+    let language = buffer.language_at(match_range.start).cloned();
+    let symbol_range = bracket_range.to_anchors(&buffer);
+    let match_anchor = buffer.anchor_after(match_range.start);
 
-impl HoverState {
-    pub fn visible(&self) -> bool {
-        !self.info_popovers.is_empty() || self.diagnostic_popover.is_some()
-    }
+    let task = cx.spawn(|this, mut cx| {
+        async move {
+            if let Some(hover_delay) = hover_delay {
+                cx.background_executor()
+                    .timer(Duration::from_millis(hover_delay))
+                    .await;
+            }
 
-    pub fn render(
-        &mut self,
-        snapshot: &EditorSnapshot,
-        style: &EditorStyle,
-        visible_rows: Range<DisplayRow>,
-        max_size: Size<Pixels>,
-        _workspace: Option<WeakView<Workspace>>,
-        cx: &mut ViewContext<Editor>,
-    ) -> Option<(DisplayPoint, Vec<AnyElement>)> {
-        // If there is a diagnostic, position the popovers based on that.
-        // Otherwise use the start of the hover range
-        let anchor = self
-            .diagnostic_popover
-            .as_ref()
-            .map(|diagnostic_popover| &diagnostic_popover.local_diagnostic.range.start)
-            .or_else(|| {
-                self.info_popovers.iter().find_map(|info_popover| {
-                    match &info_popover.symbol_range {
-                        RangeInEditor::Text(range) => Some(&range.start),
-                        RangeInEditor::Inlay(_) => None,
-                    }
-                })
-            })
-            .or_else(|| {
-                self.info_popovers.iter().find_map(|info_popover| {
-                    match &info_popover.symbol_range {
-                        RangeInEditor::Text(_) => None,
-                        RangeInEditor::Inlay(range) => Some(&range.inlay_position),
-                    }
-                })
+            let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
+            let blocks = [HoverBlock {
+                text,
+                kind: project::HoverBlockKind::Markdown,
+            }];
+            let parsed_blocks = parse_blocks(
+                &blocks,
+                &language_registry,
+                language,
+                &this,
+                anchor,
+                &mut cx,
+            )
+            .await;
+            this.update(&mut cx, |editor, cx| {
+                update_hover_highlights(editor, &[symbol_range.clone()], cx);
+                editor.hover_state.info_popovers = vec![InfoPopover {
+                    bracket_match: Some(match_anchor),
+                    ..InfoPopover::new(
+                        RangeInEditor::Text(symbol_range),
+                        Some(anchor),
+                        ignore_timeout,
+                        parsed_blocks,
+                    )
+                }];
+                cx.emit(EditorEvent::HoverChanged);
+                cx.notify();
+                cx.refresh();
             })?;
-        let point = anchor.to_display_point(&snapshot.display_snapshot);
 
-        // Don't render if the relevant point isn't on screen
-        if !self.visible() || !visible_rows.contains(&point.row()) {
-            return None;
+            anyhow::Ok(())
         }
+        .log_err()
+    });
 
-        let mut elements = Vec::new();
+    editor.hover_state.info_task = Some(task);
+}
 
-        if let Some(diagnostic_popover) = self.diagnostic_popover.as_ref() {
-            elements.push(diagnostic_popover.render(style, max_size, cx));
-        }
-        for info_popover in &mut self.info_popovers {
-            elements.push(info_popover.render(max_size, cx));
-        }
+/// Whether info popovers are shown next to the hovered symbol, rather than summarized in
+/// the status bar per `hover_popover_placement`.
+fn info_popovers_in_editor(settings: &EditorSettings) -> bool {
+    settings.hover_popover_placement == HoverPopoverPlacement::Popover
+}
 
-        Some((point, elements))
+/// A footer showing the keybinding of [`PinHover`] until popovers have been pinned a few
+/// times, when `hover_show_hints` is enabled.
+fn render_pin_hint(show_hints: bool, cx: &mut WindowContext) -> Option<AnyElement> {
+    if !show_hints
+        || hover_pin_count(cx).map_or(true, |pin_count| pin_count >= HOVER_PIN_HINT_LIMIT)
+    {
+        return None;
     }
+    let key_binding = ui::KeyBinding::for_action(&PinHover, cx)?;
+    Some(
+        h_flex()
+            .mt_2()
+            .pt_1()
+            .gap_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(key_binding)
+            .child(
+                Label::new("to pin")
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted),
+            )
+            .into_any_element(),
+    )
+}
 
-    pub fn focused(&self, cx: &mut ViewContext<Editor>) -> bool {
-        let mut hover_popover_is_focused = false;
-        for info_popover in &self.info_popovers {
-            for markdown_view in &info_popover.parsed_content {
-                if markdown_view.focus_handle(cx).is_focused(cx) {
-                    hover_popover_is_focused = true;
-                }
+/// The first line of the visible hover documentation, such as the hovered symbol's signature,
+/// shown in the status bar when `hover_popover_placement` is `status_bar`.
+pub fn hover_status_text(editor: &Editor, cx: &AppContext) -> Option<String> {
+    editor
+        .hover_state
+        .info_popovers
+        .iter()
+        .filter(|info_popover| {
+            !info_popovers_in_editor(hover_settings(editor, info_popover.settings_anchor(), cx))
+        })
+        .find_map(|info_popover| info_popover.first_line(cx))
+}
+
+/// Fetches the language server's hover for `anchor` and renders its info popovers the way
+/// [`HoverState::render`] does, leaving the editor's hover state untouched.
+pub fn build_hover_elements(
+    editor: &mut Editor,
+    anchor: Anchor,
+    max_size: Size<Pixels>,
+    cx: &mut ViewContext<Editor>,
+) -> Task<Option<Vec<AnyElement>>> {
+    let Some(project) = editor.project.clone() else {
+        return Task::ready(None);
+    };
+    let Some((buffer, buffer_position)) =
+        editor.buffer.read(cx).text_anchor_for_position(anchor, cx)
+    else {
+        return Task::ready(None);
+    };
+    let Some((excerpt_id, _, _)) = editor.buffer().read(cx).excerpt_containing(anchor, cx) else {
+        return Task::ready(None);
+    };
+    let buffer_version = buffer.read(cx).version();
+    let hover_request = project.update(cx, |project, cx| {
+        project.hover(&buffer, buffer_position, cx)
+    });
+
+    cx.spawn(|this, mut cx| async move {
+        let hovers_response = hover_request.await;
+        let language_registry = project
+            .update(&mut cx, |project, _| project.languages().clone())
+            .ok()?;
+        let snapshot = this.update(&mut cx, |this, cx| this.snapshot(cx)).ok()?;
+        let mut info_popovers = Vec::with_capacity(hovers_response.len());
+        for hover_result in hovers_response {
+            if hover_result.contents.is_empty() {
+                continue;
             }
+            let range = hover_result
+                .range
+                .and_then(|range| {
+                    let start = snapshot
+                        .buffer_snapshot
+                        .anchor_in_excerpt(excerpt_id, range.start)?;
+                    let end = snapshot
+                        .buffer_snapshot
+                        .anchor_in_excerpt(excerpt_id, range.end)?;
+                    Some(start..end)
+                })
+                .unwrap_or_else(|| anchor..anchor);
+            let parsed_blocks = parse_blocks(
+                &hover_result.contents,
+                &language_registry,
+                hover_result.language,
+                &this,
+                anchor,
+                &mut cx,
+            )
+            .await;
+            info_popovers.push(InfoPopover {
+                buffer_version: Some(buffer_version.clone()),
+                ..InfoPopover::new(
+                    RangeInEditor::Text(range),
+                    Some(anchor),
+                    false,
+                    parsed_blocks,
+                )
+            });
         }
-        return hover_popover_is_focused;
-    }
+
+        this.update(&mut cx, |_, cx| {
+            info_popovers
+                .iter_mut()
+                .map(|info_popover| {
+                    info_popover.render(max_size, None, &snapshot.buffer_snapshot, cx)
+                })
+                .collect()
+        })
+        .ok()
+    })
 }
 
-#[derive(Debug, Clone)]
+/// Highlights the hovered symbols, leaving the editor untouched when the highlight
+/// already covers the same ranges so sweeping the mouse over a symbol doesn't redraw it.
+fn update_hover_highlights(
+    editor: &mut Editor,
+    ranges: &[Range<Anchor>],
+    cx: &mut ViewContext<Editor>,
+) {
+    if ranges.is_empty() {
+        editor.clear_background_highlights::<HoverState>(cx);
+        return;
+    }
 
-pub struct InfoPopover {
-    pub symbol_range: RangeInEditor,
-    pub parsed_content: Option<View<Markdown>>,
-    pub scroll_handle: ScrollHandle,
-    pub keyboard_grace: Rc<RefCell<bool>>,
-    pub anchor: Option<Anchor>,
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let unchanged = editor
+        .background_highlights
+        .get(&TypeId::of::<HoverState>())
+        .map_or(false, |(_, highlighted)| {
+            highlighted.len() == ranges.len()
+                && highlighted.iter().zip(ranges).all(|(highlighted, range)| {
+                    highlighted.start.cmp(&range.start, &snapshot).is_eq()
+                        && highlighted.end.cmp(&range.end, &snapshot).is_eq()
+                })
+        });
+    if !unchanged {
+        // Highlight the selected symbol using a background highlight
+        editor.highlight_background::<HoverState>(
+            ranges,
+            |theme| theme.element_hover, // todo update theme
+            cx,
+        );
+    }
 }
 
-impl InfoPopover {
-    pub fn render(&mut self, max_size: Size<Pixels>, cx: &mut ViewContext<Editor>) -> AnyElement {
-        let keyboard_grace = Rc::clone(&self.keyboard_grace);
-        let mut d = div()
-            .id("info_popover")
-            .elevation_2(cx)
-            .overflow_y_scroll()
-            .track_scroll(&self.scroll_handle)
-            .max_w(max_size.width)
-            .max_h(max_size.height)
-            // Prevent a mouse down/move on the popover from being propagated to the editor,
-            // because that would dismiss the popover.
-            .on_mouse_move(|_, cx| cx.stop_propagation())
-            .on_mouse_down(MouseButton::Left, move |_, cx| {
-                let mut keyboard_grace = keyboard_grace.borrow_mut();
-                *keyboard_grace = false;
-                cx.stop_propagation();
-            })
-            .p_2();
+/// When the given position is inside a language injected into the buffer, such as a script
+/// embedded in markup, returns the injected language along with the start of the innermost
+/// expression of the injection enclosing the position.
+fn injected_expression_position(
+    buffer: &BufferSnapshot,
+    position: text::Anchor,
+) -> Option<(Arc<Language>, text::Anchor)> {
+    let offset = buffer.summary_for_anchor::<usize>(&position);
+    let layer = buffer.syntax_layer_at(offset)?;
+    if layer.depth() == 0 {
+        return None;
+    }
+    let node = layer
+        .node()
+        .named_descendant_for_byte_range(offset, offset)?;
+    if node.id() == layer.node().id() {
+        return None;
+    }
+    Some((
+        layer.language.clone(),
+        buffer.anchor_before(node.start_byte()),
+    ))
+}
 
-        if let Some(markdown) = &self.parsed_content {
-            d = d.child(markdown.clone());
+/// Reads the doc comment above the hovered symbol's definition, returning it along
+/// with the hovered symbol's location.
+fn definition_doc_comment(
+    definition: &LocationLink,
+    cx: &AsyncWindowContext,
+) -> Option<(Option<Location>, String)> {
+    use text::ToPoint;
+
+    let doc_comment = definition
+        .target
+        .buffer
+        .read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+            let row = definition.target.range.start.to_point(&snapshot).row;
+            doc_comment_above(&snapshot, row)
+        })
+        .ok()??;
+    Some((definition.origin.clone(), doc_comment))
+}
+
+/// Whether a buffer belongs to a dependency rather than to the project, such as the
+/// registry sources language servers resolve definitions to, which live in invisible worktrees.
+fn is_dependency_source(project: &Project, buffer: &Buffer, cx: &AppContext) -> bool {
+    buffer.file().map_or(false, |file| {
+        project
+            .worktree_for_id(WorktreeId::from_usize(file.worktree_id()), cx)
+            .map_or(false, |worktree| !worktree.read(cx).is_visible())
+    })
+}
+
+/// Opens the source of a dependency in a read-only editor, selecting the definition.
+fn open_dependency_source(editor: &mut Editor, location: Location, cx: &mut ViewContext<Editor>) {
+    use text::ToOffset as _;
+
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let pane = workspace.read(cx).active_pane().clone();
+    let source_editor = workspace.update(cx, |workspace, cx| {
+        workspace.open_project_item::<Editor>(pane, location.buffer.clone(), cx)
+    });
+    source_editor.update(cx, |source_editor, cx| {
+        source_editor.set_read_only(true);
+        let offset = location.range.start.to_offset(location.buffer.read(cx));
+        source_editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+            s.select_ranges([offset..offset])
+        });
+    });
+}
+
+/// Reads the doc comment directly above the given row, skipping attributes between the
+/// comment and the definition. Only doc comment syntax is accepted, that is `///` and `//!`
+/// line comments and `/** */` and `/*! */` block comments. The comment markers are removed,
+/// the rest is kept verbatim.
+fn doc_comment_above(buffer: &BufferSnapshot, row: u32) -> Option<String> {
+    let layer = buffer.syntax_layer_at(Point::new(row, 0))?;
+    let root = layer.node();
+    let mut lines = Vec::new();
+    let mut row = row;
+    while row > 0 {
+        row -= 1;
+        let line_start = Point::new(row, buffer.indent_size_for_line(row).len);
+        let line_end = Point::new(row, buffer.line_len(row));
+        if line_start == line_end {
+            break;
+        }
+        let line = buffer
+            .text_for_range(line_start..line_end)
+            .collect::<String>();
+        let offset = buffer.point_to_offset(line_start);
+        let mut comment = root.descendant_for_byte_range(offset, offset);
+        while let Some(node) = comment.filter(|node| !node.kind().contains("comment")) {
+            comment = node.parent();
+        }
+        if let Some(comment) = comment {
+            let comment_text = buffer
+                .text_for_range(comment.byte_range())
+                .collect::<String>();
+            if let Some(block_lines) = doc_block_comment_lines(&comment_text) {
+                lines.extend(block_lines.into_iter().rev());
+                row = comment.start_position().row as u32;
+            } else if let Some(line) = doc_line_comment(&line) {
+                lines.push(line.to_string());
+            } else {
+                break;
+            }
+        } else if lines.is_empty() && (line.starts_with("#[") || line.starts_with('@')) {
+            continue;
+        } else {
+            break;
         }
-        d.into_any_element()
     }
-
-    pub fn scroll(&self, amount: &ScrollAmount, cx: &mut ViewContext<Editor>) {
-        let mut current = self.scroll_handle.offset();
-        current.y -= amount.pixels(
-            cx.line_height(),
-            self.scroll_handle.bounds().size.height - px(16.),
-        ) / 2.0;
-        cx.notify();
-        self.scroll_handle.set_offset(current);
+    lines.reverse();
+    let doc_comment = lines.join("\n");
+    let doc_comment = doc_comment.trim_matches('\n');
+    if doc_comment.is_empty() {
+        None
+    } else {
+        Some(doc_comment.to_string())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DiagnosticPopover {
-    local_diagnostic: DiagnosticEntry<Anchor>,
-    primary_diagnostic: Option<DiagnosticEntry<Anchor>>,
+/// The text of a `///` or `//!` doc comment line, without its marker.
+fn doc_line_comment(line: &str) -> Option<&str> {
+    if line.starts_with("////") {
+        return None;
+    }
+    let text = line
+        .strip_prefix("///")
+        .or_else(|| line.strip_prefix("//!"))?
+        .trim_end();
+    Some(text.strip_prefix(' ').unwrap_or(text))
 }
 
-impl DiagnosticPopover {
-    pub fn render(
-        &self,
-        style: &EditorStyle,
-        max_size: Size<Pixels>,
-        cx: &mut ViewContext<Editor>,
-    ) -> AnyElement {
-        let text = match &self.local_diagnostic.diagnostic.source {
-            Some(source) => format!("{source}: {}", self.local_diagnostic.diagnostic.message),
-            None => self.local_diagnostic.diagnostic.message.clone(),
-        };
-
-        let status_colors = cx.theme().status();
+/// The lines of a `/** */` or `/*! */` doc comment, without its markers and the leading
+/// `*` of each line.
+fn doc_block_comment_lines(comment: &str) -> Option<Vec<&str>> {
+    if comment.starts_with("/***") || comment == "/**/" {
+        return None;
+    }
+    let text = comment
+        .strip_prefix("/**")
+        .or_else(|| comment.strip_prefix("/*!"))?
+        .strip_suffix("*/")?;
+    Some(
+        text.lines()
+            .map(|line| {
+                let line = line.trim();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line)
+            })
+            .collect(),
+    )
+}
 
-        struct DiagnosticColors {
-            pub background: Hsla,
-            pub border: Hsla,
+/// Replaces the documentation of a language server's hover with the raw doc comment,
+/// keeping the code blocks the hover starts with, such as the symbol's signature.
+fn merge_raw_doc_comment(blocks: Vec<HoverBlock>, doc_comment: String) -> Vec<HoverBlock> {
+    let mut merged = Vec::new();
+    for block in blocks {
+        match block.kind {
+            project::HoverBlockKind::Code { .. } => merged.push(block),
+            project::HoverBlockKind::PlainText => break,
+            project::HoverBlockKind::Markdown => {
+                let code_len = leading_code_blocks_len(&block.text);
+                let has_documentation = !block.text[code_len..].trim().is_empty();
+                if code_len > 0 {
+                    merged.push(HoverBlock {
+                        text: block.text[..code_len].to_string(),
+                        kind: project::HoverBlockKind::Markdown,
+                    });
+                }
+                if has_documentation {
+                    break;
+                }
+            }
         }
+    }
+    merged.push(HoverBlock {
+        text: doc_comment,
+        kind: project::HoverBlockKind::Markdown,
+    });
+    merged
+}
 
-        let diagnostic_colors = match self.local_diagnostic.diagnostic.severity {
-            DiagnosticSeverity::ERROR => DiagnosticColors {
-                background: status_colors.error_background,
-                border: status_colors.error_border,
-            },
-            DiagnosticSeverity::WARNING => DiagnosticColors {
-                background: status_colors.warning_background,
-                border: status_colors.warning_border,
-            },
-            DiagnosticSeverity::INFORMATION => DiagnosticColors {
-                background: status_colors.info_background,
-                border: status_colors.info_border,
-            },
-            DiagnosticSeverity::HINT => DiagnosticColors {
-                background: status_colors.hint_background,
-                border: status_colors.hint_border,
-            },
-            _ => DiagnosticColors {
-                background: status_colors.ignored_background,
-                border: status_colors.ignored_border,
-            },
-        };
+/// The length of the code blocks a Markdown source starts with.
+fn leading_code_blocks_len(source: &str) -> usize {
+    let mut len = 0;
+    let mut within_code_block = false;
+    for (range, event) in parse_markdown(source) {
+        match event {
+            MarkdownEvent::Start(MarkdownTag::CodeBlock(_)) => within_code_block = true,
+            MarkdownEvent::End(MarkdownTagEnd::CodeBlock) => {
+                within_code_block = false;
+                len = range.end;
+            }
+            _ if within_code_block => {}
+            _ => break,
+        }
+    }
+    len
+}
 
-        div()
-            .id("diagnostic")
-            .block()
-            .elevation_2_borderless(cx)
-            // Don't draw the background color if the theme
-            // allows transparent surfaces.
-            .when(window_is_transparent(cx), |this| {
-                this.bg(gpui::transparent_black())
+/// Looks up the last run of the test runnable on the hovered row, resolving its
+/// task the same way the gutter run indicator does so that the task ids match.
+fn last_test_run(
+    editor: &Editor,
+    project: &Model<Project>,
+    buffer: &Model<Buffer>,
+    buffer_position: text::Anchor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<Task<Option<(TaskSourceKind, ResolvedTask, TaskRunResult)>>> {
+    use text::ToPoint;
+
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let buffer_row = buffer_position.to_point(&buffer_snapshot).row;
+    let tasks = editor
+        .tasks
+        .get(&(buffer_snapshot.remote_id(), buffer_row))?
+        .clone();
+    let task_context = tasks.task_context(project, buffer, buffer_row, cx);
+    let inventory = project.read(cx).task_inventory().clone();
+    Some(cx.spawn(|_, cx| async move {
+        let task_context = task_context.await?;
+        inventory
+            .read_with(&cx, |inventory, _| {
+                tasks.templates.iter().find_map(|(kind, template)| {
+                    let resolved_task = template.resolve_task(&kind.to_id_base(), &task_context)?;
+                    let result = inventory.last_task_result(&resolved_task.id)?;
+                    Some((kind.clone(), resolved_task, result))
+                })
             })
-            .max_w(max_size.width)
-            .max_h(max_size.height)
-            .cursor(CursorStyle::PointingHand)
-            .tooltip(move |cx| Tooltip::for_action("Go To Diagnostic", &crate::GoToDiagnostic, cx))
-            // Prevent a mouse move on the popover from being propagated to the editor,
-            // because that would dismiss the popover.
-            .on_mouse_move(|_, cx| cx.stop_propagation())
-            // Prevent a mouse down on the popover from being propagated to the editor,
-            // because that would move the cursor.
-            .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
-            .on_click(cx.listener(|editor, _, cx| editor.go_to_diagnostic(&Default::default(), cx)))
-            .child(
-                div()
-                    .id("diagnostic-inner")
-                    .overflow_y_scroll()
-                    .px_2()
-                    .py_1()
-                    .bg(diagnostic_colors.background)
-                    .text_color(style.text.color)
-                    .border_1()
-                    .border_color(diagnostic_colors.border)
-                    .rounded_lg()
-                    .child(SharedString::from(text)),
-            )
-            .into_any_element()
+            .ok()
+            .flatten()
+    }))
+}
+
+/// Starts resolving the task context of the hovered Rust item, to run the examples of
+/// its documentation as a doctest. Returns the item's name along with the context, unless
+/// the item belongs to a dependency rather than to the project's own sources.
+fn doctest_context(
+    project: &Model<Project>,
+    buffer: &Model<Buffer>,
+    buffer_position: text::Anchor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<(String, Task<Option<TaskContext>>)> {
+    if buffer.read(cx).file().is_none()
+        || is_dependency_source(project.read(cx), buffer.read(cx), cx)
+    {
+        return None;
+    }
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let language = buffer_snapshot.language_at(buffer_position)?;
+    if language.name().as_ref() != "Rust" {
+        return None;
     }
+    let (range, kind) = buffer_snapshot.surrounding_word(buffer_position);
+    if kind != Some(CharKind::Word) {
+        return None;
+    }
+    let symbol = buffer_snapshot.text_for_range(range).collect::<String>();
+    let location = Location {
+        buffer: buffer.clone(),
+        range: buffer_position..buffer_position,
+    };
+    let task_context = project.update(cx, |project, cx| {
+        project.task_context_for_location(TaskVariables::default(), location, cx)
+    });
+    Some((symbol, task_context))
+}
 
-    pub fn activation_info(&self) -> (usize, Anchor) {
-        let entry = self
-            .primary_diagnostic
-            .as_ref()
-            .unwrap_or(&self.local_diagnostic);
+/// Whether a language server is sent the edits of open documents, according to the kind of
+/// document sync it announced.
+fn syncs_document_changes(capabilities: &lsp::ServerCapabilities) -> bool {
+    let kind = match &capabilities.text_document_sync {
+        Some(lsp::TextDocumentSyncCapability::Kind(kind)) => Some(*kind),
+        Some(lsp::TextDocumentSyncCapability::Options(options)) => options.change,
+        None => None,
+    };
+    kind.map_or(false, |kind| kind != lsp::TextDocumentSyncKind::NONE)
+}
 
-        (entry.diagnostic.group_id, entry.range.start)
+/// Resolves the task running the doctests of the Rust item named `symbol`, in the package
+/// that contains it. `None` when the item isn't part of a package of the workspace.
+fn doctest_task(symbol: &str, task_context: &TaskContext) -> Option<ResolvedTask> {
+    let package = VariableName::Custom("RUST_PACKAGE".into());
+    task_context.task_variables.get(&package)?;
+    TaskTemplate {
+        label: format!("cargo test -p {} --doc {symbol}", package.template_value()),
+        command: "cargo".into(),
+        args: vec![
+            "test".into(),
+            "-p".into(),
+            package.template_value(),
+            "--doc".into(),
+            symbol.into(),
+        ],
+        cwd: Some("$ZED_DIRNAME".to_owned()),
+        ..TaskTemplate::default()
     }
+    .resolve_task("hover-doctest", task_context)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        actions::ConfirmCompletion,
-        editor_tests::{handle_completion_request, init_test},
-        hover_links::update_inlay_link_and_hover_points,
-        inlay_hint_cache::tests::{cached_hint_labels, visible_hint_labels},
-        test::editor_lsp_test_context::EditorLspTestContext,
-        InlayId, PointForPosition,
+fn hover_has_runnable_doctest(blocks: &[HoverBlock]) -> bool {
+    blocks.iter().any(|block| {
+        matches!(block.kind, project::HoverBlockKind::Markdown) && has_runnable_doctest(&block.text)
+    })
+}
+
+/// Whether the markdown has a code block that rustdoc runs as a doctest: one tagged as
+/// Rust or not at all, without attributes like `ignore` or `no_run`. Language servers
+/// put the documentation after a rule, below the item's signature, which isn't an example.
+fn has_runnable_doctest(source: &str) -> bool {
+    let events = parse_markdown(source);
+    let Some(documentation_start) = events
+        .iter()
+        .position(|(_, event)| *event == MarkdownEvent::Rule)
+    else {
+        return false;
     };
-    use collections::BTreeSet;
-    use indoc::indoc;
-    use language::{language_settings::InlayHintSettings, Diagnostic, DiagnosticSet};
-    use lsp::LanguageServerId;
-    use markdown::parser::MarkdownEvent;
-    use smol::stream::StreamExt;
-    use std::sync::atomic;
-    use std::sync::atomic::AtomicUsize;
-    use text::Bias;
+    events[documentation_start..]
+        .iter()
+        .any(|(_, event)| match event {
+            MarkdownEvent::Start(MarkdownTag::CodeBlock(CodeBlockKind::Fenced(info))) => info
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|attribute| !attribute.is_empty())
+                .all(|attribute| {
+                    attribute == "rust"
+                        || attribute == "should_panic"
+                        || attribute.starts_with("edition")
+                }),
+            _ => false,
+        })
+}
 
-    impl InfoPopover {
-        fn get_rendered_text(&self, cx: &gpui::AppContext) -> String {
-            let mut rendered_text = String::new();
-            if let Some(parsed_content) = self.parsed_content.clone() {
-                let markdown = parsed_content.read(cx);
-                let text = markdown.parsed_markdown().source().to_string();
-                let data = markdown.parsed_markdown().events();
-                let slice = data;
+fn doctest_result_text(result: TaskRunResult) -> String {
+    let status = if result.success {
+        "✓ Doctest passed"
+    } else {
+        "✗ Doctest failed"
+    };
+    format!("{status} in {:.2}s", result.duration.as_secs_f64())
+}
 
-                for (range, event) in slice.iter() {
-                    if [MarkdownEvent::Text, MarkdownEvent::Code].contains(event) {
-                        rendered_text.push_str(&text[range.clone()])
-                    }
+fn test_run_block(result: TaskRunResult) -> HoverBlock {
+    let status = if result.success {
+        "✓ Test passed"
+    } else {
+        "✗ Test failed"
+    };
+    HoverBlock {
+        text: format!("**{status}** in {:.2}s", result.duration.as_secs_f64()),
+        kind: project::HoverBlockKind::Markdown,
+    }
+}
+
+/// Finds built-in documentation for the language keyword at `anchor`, along with its range.
+fn keyword_docs_at(
+    buffer: &MultiBufferSnapshot,
+    anchor: Anchor,
+) -> Option<(Range<Anchor>, &'static str)> {
+    let offset = anchor.to_offset(buffer);
+    let (range, kind) = buffer.surrounding_word(offset);
+    if kind != Some(CharKind::Word) {
+        return None;
+    }
+    let language = buffer.language_at(offset)?;
+    let keyword = buffer.text_for_range(range.clone()).collect::<String>();
+    let docs = keyword_docs(&language.name(), &keyword)?;
+
+    // The same word in a comment, a string or an identifier isn't the keyword.
+    let (excerpt_buffer, excerpt_offset) = buffer.point_to_buffer_offset(offset)?;
+    let keyword_start = excerpt_offset - (offset - range.start);
+    let node = excerpt_buffer
+        .syntax_layer_at(excerpt_offset)?
+        .node()
+        .descendant_for_byte_range(keyword_start, keyword_start + keyword.len())?;
+    if node.is_named() || node.kind() != keyword {
+        return None;
+    }
+
+    Some((
+        buffer.anchor_before(range.start)..buffer.anchor_after(range.end),
+        docs,
+    ))
+}
+
+/// Describes how many times the word at `position` occurs in the buffer, as whole words,
+/// along with the word's range.
+fn word_occurrences_at(
+    buffer: &BufferSnapshot,
+    position: text::Anchor,
+) -> Option<(Range<text::Anchor>, String)> {
+    let (range, kind) = buffer.surrounding_word(position);
+    if kind != Some(CharKind::Word) {
+        return None;
+    }
+    let word = buffer.text_for_range(range.clone()).collect::<String>();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut count = 0;
+    let mut current_word = String::new();
+    for chunk in buffer.text_for_range(0..buffer.len()) {
+        for c in chunk.chars() {
+            if is_word_char(c) {
+                current_word.push(c);
+            } else {
+                if current_word == word {
+                    count += 1;
                 }
+                current_word.clear();
             }
-            rendered_text
         }
     }
+    if current_word == word {
+        count += 1;
+    }
+    let count = count.max(1);
+    let occurrences = match count {
+        1 => "once".to_string(),
+        count => format!("{count} times"),
+    };
+    Some((
+        buffer.anchor_before(range.start)..buffer.anchor_after(range.end),
+        format!("`{word}` appears {occurrences} in this buffer"),
+    ))
+}
 
-    #[gpui::test]
-    async fn test_mouse_hover_info_popover_with_autocomplete_popover(
-        cx: &mut gpui::TestAppContext,
-    ) {
-        init_test(cx, |_| {});
-        const HOVER_DELAY_MILLIS: u64 = 350;
+/// The name and value of the constant named `name` declared in a hover's signature, like
+/// the `const MAX: u32 = 10 (0xA)` rust-analyzer shows with the evaluated value.
+fn const_value_from_hover(blocks: &[HoverBlock], name: &str) -> Option<(String, String)> {
+    let signature = match &blocks.first()?.kind {
+        project::HoverBlockKind::Code { .. } => vec![blocks[0].text.clone()],
+        project::HoverBlockKind::Markdown => leading_code_blocks(&blocks[0].text),
+        project::HoverBlockKind::PlainText => return None,
+    };
+    signature
+        .iter()
+        .flat_map(|code| code.lines())
+        .find_map(|line| {
+            let (visibility, declaration) = line.trim().split_once("const ")?;
+            if !visibility.is_empty() && !visibility.starts_with("pub") {
+                return None;
+            }
+            let (name_and_type, value) = declaration.split_once(" = ")?;
+            let value = value.trim().trim_end_matches(';').trim_end();
+            if name_and_type.split(':').next()?.trim() != name || value.is_empty() {
+                return None;
+            }
+            Some((name.to_string(), value.to_string()))
+        })
+}
 
-        let mut cx = EditorLspTestContext::new_rust(
-            lsp::ServerCapabilities {
-                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
-                completion_provider: Some(lsp::CompletionOptions {
-                    trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
-                    resolve_provider: Some(true),
-                    ..Default::default()
-                }),
-                ..Default::default()
+/// The code blocks a markdown hover starts with, before any prose. That's where servers
+/// like rust-analyzer put the hovered item's path and signature.
+fn leading_code_blocks(source: &str) -> Vec<String> {
+    let mut code_blocks = Vec::new();
+    let mut code_block: Option<String> = None;
+    for (range, event) in parse_markdown(source) {
+        match event {
+            MarkdownEvent::Start(MarkdownTag::CodeBlock(_)) => code_block = Some(String::new()),
+            MarkdownEvent::Text => match code_block.as_mut() {
+                Some(code_block) => code_block.push_str(&source[range]),
+                None => break,
             },
-            cx,
-        )
-        .await;
-        let counter = Arc::new(AtomicUsize::new(0));
-        // Basic hover delays and then pops without moving the mouse
-        cx.set_state(indoc! {"
-                oneˇ
-                two
-                three
-                fn test() { println!(); }
-            "});
+            MarkdownEvent::End(MarkdownTagEnd::CodeBlock) => code_blocks.extend(code_block.take()),
+            _ if code_block.is_none() => break,
+            _ => {}
+        }
+    }
+    code_blocks
+}
 
-        //prompt autocompletion menu
-        cx.simulate_keystroke(".");
-        handle_completion_request(
-            &mut cx,
-            indoc! {"
-                        one.|<>
-                        two
-                        three
-                    "},
-            vec!["first_completion", "second_completion"],
-            counter.clone(),
-        )
-        .await;
-        cx.condition(|editor, _| editor.context_menu_visible()) // wait until completion menu is visible
-            .await;
-        assert_eq!(counter.load(atomic::Ordering::Acquire), 1); // 1 completion request
+/// Resolves the identifier at `position` to the literal that a `const` item of the same
+/// name is initialized with, among the items of the scopes enclosing the identifier.
+fn const_initializer_at(
+    buffer: &BufferSnapshot,
+    position: text::Anchor,
+) -> Option<(String, String)> {
+    let offset = buffer.summary_for_anchor::<usize>(&position);
+    let layer = buffer.syntax_layer_at(offset)?;
+    let node = layer
+        .node()
+        .named_descendant_for_byte_range(offset, offset)?;
+    if !node.kind().ends_with("identifier") {
+        return None;
+    }
+    let name = buffer.text_for_range(node.byte_range()).collect::<String>();
+
+    let mut scope = node.parent();
+    while let Some(parent) = scope {
+        let mut cursor = parent.walk();
+        for item in parent.named_children(&mut cursor) {
+            if item.kind() != "const_item" {
+                continue;
+            }
+            let declared_name = item.child_by_field_name("name");
+            let value = item.child_by_field_name("value");
+            if let Some((declared_name, value)) = declared_name.zip(value) {
+                if value.kind().ends_with("literal")
+                    && buffer
+                        .text_for_range(declared_name.byte_range())
+                        .collect::<String>()
+                        == name
+                {
+                    let value = buffer
+                        .text_for_range(value.byte_range())
+                        .collect::<String>();
+                    return Some((name, value));
+                }
+            }
+        }
+        scope = parent.parent();
+    }
+    None
+}
+
+fn keyword_docs(language_name: &str, keyword: &str) -> Option<&'static str> {
+    let docs = match (language_name, keyword) {
+        ("Rust", "async") => "Returns a `Future` instead of blocking the current thread.",
+        ("Rust", "await") => "Suspends execution until the result of a `Future` is ready.",
+        ("Rust", "unsafe") => {
+            "Code or interfaces whose memory safety cannot be verified by the type system."
+        }
+        ("Rust", "match") => "Control flow based on pattern matching.",
+        ("Rust", "impl") => "Implements some functionality for a type.",
+        ("Rust", "dyn") => {
+            "Prefix of a trait object's type, whose methods are dispatched dynamically."
+        }
+        ("Rust", "move") => "Captures a closure's environment by value.",
+        ("Rust", "where") => "Adds constraints that must be upheld to use an item.",
+        ("Rust", "loop") => "Loops indefinitely until a `break`.",
+        ("JavaScript" | "TypeScript" | "TSX", "async") => {
+            "Declares a function that returns a `Promise` and may use `await`."
+        }
+        ("JavaScript" | "TypeScript" | "TSX", "await") => {
+            "Pauses an `async` function until the awaited `Promise` settles."
+        }
+        ("JavaScript" | "TypeScript" | "TSX", "yield") => {
+            "Pauses a generator function and hands a value to its caller."
+        }
+        ("TypeScript" | "TSX", "satisfies") => {
+            "Checks that an expression matches a type without changing its inferred type."
+        }
+        ("TypeScript" | "TSX", "keyof") => "Produces a union of the property names of a type.",
+        ("Python", "async") => "Declares a coroutine function, or an asynchronous `for` or `with`.",
+        ("Python", "await") => "Suspends the coroutine until the awaitable completes.",
+        ("Python", "yield") => "Turns the function into a generator that produces a value.",
+        ("Python", "with") => "Wraps a block in the enter and exit methods of a context manager.",
+        ("Python", "lambda") => "Creates an anonymous function from a single expression.",
+        ("Go", "defer") => "Runs a function call when the surrounding function returns.",
+        ("Go", "go") => "Starts a function call in a new goroutine.",
+        ("Go", "select") => "Waits on multiple channel operations.",
+        ("Go", "chan") => "Declares a channel type for communicating between goroutines.",
+        _ => return None,
+    };
+    Some(docs)
+}
+
+/// Whether `position` is in a chain of at least two member accesses, like `a.b().c()`.
+fn is_in_method_chain(buffer: &BufferSnapshot, position: text::Anchor) -> bool {
+    const MEMBER_KINDS: &[&str] = &[
+        "field_expression",
+        "member_expression",
+        "attribute",
+        "selector_expression",
+    ];
+    const CALL_KINDS: &[&str] = &["call_expression", "call"];
+
+    let offset = buffer.summary_for_anchor::<usize>(&position);
+    let Some(layer) = buffer.syntax_layer_at(offset) else {
+        return false;
+    };
+    let Some(mut chain) = layer.node().named_descendant_for_byte_range(offset, offset) else {
+        return false;
+    };
+    while let Some(parent) = chain.parent() {
+        if !MEMBER_KINDS.contains(&parent.kind()) && !CALL_KINDS.contains(&parent.kind()) {
+            break;
+        }
+        chain = parent;
+    }
+
+    let mut member_count = 0;
+    let mut cursor = chain.walk();
+    loop {
+        if MEMBER_KINDS.contains(&cursor.node().kind()) {
+            member_count += 1;
+            if member_count > 1 {
+                return true;
+            }
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                return false;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+/// Explains the regular expression at `position`, either a language's regex literal or
+/// an injected regex, along with the range of its pattern.
+fn regex_explanation_at(
+    buffer: &BufferSnapshot,
+    position: text::Anchor,
+) -> Option<(Range<text::Anchor>, String)> {
+    let offset = buffer.summary_for_anchor::<usize>(&position);
+    let layer = buffer.syntax_layer_at(offset)?;
+    let range = if layer.language.name().as_ref() == "Regex" {
+        layer.node().byte_range()
+    } else {
+        let mut node = layer
+            .node()
+            .named_descendant_for_byte_range(offset, offset)?;
+        loop {
+            match node.kind() {
+                "regex_pattern" => break node.byte_range(),
+                "regex" => break node.child_by_field_name("pattern")?.byte_range(),
+                _ => node = node.parent()?,
+            }
+        }
+    };
+    let pattern = buffer.text_for_range(range.clone()).collect::<String>();
+    let explanation = explain_regex(&pattern)?;
+    Some((
+        buffer.anchor_before(range.start)..buffer.anchor_after(range.end),
+        explanation,
+    ))
+}
+
+enum RegexPart {
+    Literal { source: String },
+    Token { source: String, explanation: String },
+}
+
+impl RegexPart {
+    fn into_source_and_explanation(self) -> (String, String) {
+        match self {
+            RegexPart::Literal { source } if source.chars().count() == 1 => {
+                (source, "The literal character".to_string())
+            }
+            RegexPart::Literal { source } => (source, "The literal text".to_string()),
+            RegexPart::Token {
+                source,
+                explanation,
+            } => (source, explanation),
+        }
+    }
+}
+
+/// Describes each part of a regular expression in plain language, as a markdown list.
+fn explain_regex(pattern: &str) -> Option<String> {
+    let chars = pattern.chars().collect::<Vec<_>>();
+    let is_quantifier_at = |ix: usize| match chars.get(ix) {
+        Some('*' | '+' | '?') => true,
+        Some('{') => repetition_at(&chars, ix).is_some(),
+        _ => false,
+    };
+
+    let mut parts = Vec::new();
+    let mut group_count = 0;
+    let mut ix = 0;
+    while ix < chars.len() {
+        let start = ix;
+        let c = chars[ix];
+        ix += 1;
+
+        let quantified_part = if is_quantifier_at(start) {
+            parts.pop()
+        } else {
+            None
+        };
+        if let Some(part) = quantified_part {
+            let quantifier = match c {
+                '*' => "zero or more times".to_string(),
+                '+' => "one or more times".to_string(),
+                '?' => "optionally".to_string(),
+                _ => {
+                    let (end, description) = repetition_at(&chars, start)?;
+                    ix = end;
+                    description
+                }
+            };
+            let lazy = chars.get(ix) == Some(&'?');
+            if lazy {
+                ix += 1;
+            }
+            let (source, explanation) = part.into_source_and_explanation();
+            let quantified = chars[start..ix].iter().collect::<String>();
+            parts.push(RegexPart::Token {
+                source: source + &quantified,
+                explanation: if lazy {
+                    format!("{explanation}, {quantifier}, as few times as possible")
+                } else {
+                    format!("{explanation}, {quantifier}")
+                },
+            });
+            continue;
+        }
+
+        let explanation = match c {
+            '^' => "Start of the line".to_string(),
+            '$' => "End of the line".to_string(),
+            '.' => "Any character".to_string(),
+            '|' => "Or".to_string(),
+            ')' => "End of the group".to_string(),
+            '(' if chars.get(ix) == Some(&'?') => {
+                let rest = chars[ix + 1..].iter().collect::<String>();
+                let (prefix_len, explanation) = if rest.starts_with(':') {
+                    (2, "Start of a non-capturing group".to_string())
+                } else if rest.starts_with('=') {
+                    (2, "Start of a lookahead".to_string())
+                } else if rest.starts_with('!') {
+                    (2, "Start of a negative lookahead".to_string())
+                } else if rest.starts_with("<=") {
+                    (3, "Start of a lookbehind".to_string())
+                } else if rest.starts_with("<!") {
+                    (3, "Start of a negative lookbehind".to_string())
+                } else if let Some(name) = rest
+                    .strip_prefix("P<")
+                    .or_else(|| rest.strip_prefix('<'))
+                    .and_then(|rest| rest.split_once('>'))
+                    .map(|(name, _)| name)
+                {
+                    group_count += 1;
+                    // Counted in characters, past the `?` and up to and including the `>`.
+                    let prefix_len = rest.chars().take_while(|c| *c != '>').count() + 2;
+                    (prefix_len, format!("Start of the capture group `{name}`"))
+                } else {
+                    (1, "Start of a group with modifiers".to_string())
+                };
+                ix += prefix_len;
+                explanation
+            }
+            '(' => {
+                group_count += 1;
+                format!("Start of capture group {group_count}")
+            }
+            '[' => {
+                let negated = chars.get(ix) == Some(&'^');
+                let mut end = ix + negated as usize;
+                if chars.get(end) == Some(&']') {
+                    end += 1;
+                }
+                while end < chars.len() && chars[end] != ']' {
+                    if chars[end] == '\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                ix = (end + 1).min(chars.len());
+                if negated {
+                    "Any character except these".to_string()
+                } else {
+                    "One of these characters".to_string()
+                }
+            }
+            '\\' => {
+                let escaped = chars.get(ix).copied();
+                ix = (ix + 1).min(chars.len());
+                let explanation = match escaped {
+                    Some('d') => "A digit",
+                    Some('D') => "Any character except a digit",
+                    Some('w') => "A word character",
+                    Some('W') => "Any character except a word character",
+                    Some('s') => "A whitespace character",
+                    Some('S') => "Any character except whitespace",
+                    Some('b') => "A word boundary",
+                    Some('B') => "Not a word boundary",
+                    Some('n') => "A newline",
+                    Some('t') => "A tab",
+                    None => "A trailing backslash",
+                    Some(_) => {
+                        push_regex_literal(&mut parts, &chars[start..ix], is_quantifier_at(ix));
+                        continue;
+                    }
+                };
+                explanation.to_string()
+            }
+            _ => {
+                push_regex_literal(&mut parts, &chars[start..ix], is_quantifier_at(ix));
+                continue;
+            }
+        };
+        parts.push(RegexPart::Token {
+            source: chars[start..ix].iter().collect(),
+            explanation,
+        });
+    }
+
+    if parts.is_empty() {
+        return None;
+    }
+    let mut markdown = "Regular expression:\n".to_string();
+    for part in parts {
+        let (source, explanation) = part.into_source_and_explanation();
+        markdown.push_str(&format!("\n- `{source}` {explanation}"));
+    }
+    Some(markdown)
+}
+
+/// Appends literal characters to the literal text before them, unless a quantifier
+/// follows that should only apply to these characters.
+fn push_regex_literal(parts: &mut Vec<RegexPart>, source: &[char], quantified: bool) {
+    if !quantified {
+        if let Some(RegexPart::Literal { source: text }) = parts.last_mut() {
+            text.extend(source);
+            return;
+        }
+    }
+    parts.push(RegexPart::Literal {
+        source: source.iter().collect(),
+    });
+}
+
+/// The end and description of a `{n}`, `{n,}` or `{n,m}` repetition starting at `start`.
+fn repetition_at(chars: &[char], start: usize) -> Option<(usize, String)> {
+    let end = start + chars[start..].iter().position(|c| *c == '}')?;
+    let inner = chars[start + 1..end].iter().collect::<String>();
+    let description = match inner.split_once(',') {
+        None => format!("exactly {} times", inner.parse::<usize>().ok()?),
+        Some((min, "")) => format!("{} or more times", min.parse::<usize>().ok()?),
+        Some((min, max)) => format!(
+            "between {} and {} times",
+            min.parse::<usize>().ok()?,
+            max.parse::<usize>().ok()?
+        ),
+    };
+    Some((end + 1, description))
+}
+
+/// Finds built-in documentation for the attribute, or the trait derived by an attribute,
+/// at `position`, along with the range of its name.
+fn attribute_docs_at(
+    buffer: &BufferSnapshot,
+    position: text::Anchor,
+) -> Option<(Range<text::Anchor>, &'static str)> {
+    let offset = buffer.summary_for_anchor::<usize>(&position);
+    let layer = buffer.syntax_layer_at(offset)?;
+    let node = layer
+        .node()
+        .named_descendant_for_byte_range(offset, offset)?;
+    if node.kind() != "identifier" {
+        return None;
+    }
+    let mut attribute = node;
+    while attribute.kind() != "attribute" {
+        attribute = attribute.parent()?;
+    }
+
+    let path = attribute.child(0)?;
+    let path_text = buffer.text_for_range(path.byte_range()).collect::<String>();
+    let (range, docs) = if path.byte_range().contains(&node.start_byte()) {
+        let docs = attribute_docs(&layer.language.name(), &path_text, None)?;
+        (path.byte_range(), docs)
+    } else {
+        let argument = buffer.text_for_range(node.byte_range()).collect::<String>();
+        let docs = attribute_docs(&layer.language.name(), &path_text, Some(&argument))?;
+        (node.byte_range(), docs)
+    };
+    Some((
+        buffer.anchor_before(range.start)..buffer.anchor_after(range.end),
+        docs,
+    ))
+}
+
+fn attribute_docs(
+    language_name: &str,
+    attribute: &str,
+    argument: Option<&str>,
+) -> Option<&'static str> {
+    let docs = match (language_name, attribute, argument) {
+        ("Rust", "derive", None) => "Generates implementations of the listed traits for the item.",
+        ("Rust", "derive", Some("Debug")) => "Formats the value with `{:?}` for debugging.",
+        ("Rust", "derive", Some("Clone")) => "Creates a copy of the value with `clone()`.",
+        ("Rust", "derive", Some("Copy")) => {
+            "Makes the type copied instead of moved when it is assigned or passed by value."
+        }
+        ("Rust", "derive", Some("PartialEq")) => "Compares values field by field with `==`.",
+        ("Rust", "derive", Some("Eq")) => "Marks `==` as an equivalence relation.",
+        ("Rust", "derive", Some("PartialOrd")) => {
+            "Orders values lexicographically by their fields, in declaration order."
+        }
+        ("Rust", "derive", Some("Ord")) => "Marks the ordering of values as total.",
+        ("Rust", "derive", Some("Hash")) => "Hashes the value by hashing each of its fields.",
+        ("Rust", "derive", Some("Default")) => {
+            "Creates a value whose fields all have their default values."
+        }
+        ("Rust", "derive", Some("Serialize")) => "Serializes the value with `serde`.",
+        ("Rust", "derive", Some("Deserialize")) => "Deserializes the value with `serde`.",
+        ("Rust", "test", None) => "Marks a function to be run by `cargo test`.",
+        ("Rust", "cfg", None) => "Only compiles the item when the configuration predicate holds.",
+        ("Rust", "cfg_attr", None) => {
+            "Applies the attributes when the configuration predicate holds."
+        }
+        ("Rust", "inline", None) => "Suggests that the function be inlined at its call sites.",
+        ("Rust", "must_use", None) => "Warns when the value is unused.",
+        ("Rust", "deprecated", None) => "Warns when the item is used.",
+        ("Rust", "non_exhaustive", None) => {
+            "Requires other crates to handle variants or fields that may be added later."
+        }
+        ("Rust", "repr", None) => "Controls the in-memory layout of the type.",
+        ("Rust", "allow" | "warn" | "deny" | "forbid", None) => {
+            "Sets the level of the listed lints."
+        }
+        ("Rust", "tokio::main", None) => "Runs the async function on a Tokio runtime.",
+        ("Rust", "tokio::test", None) => "Runs the async test on a Tokio runtime.",
+        _ => return None,
+    };
+    Some(docs)
+}
+
+/// Re-queries the language server for a visible info popover whose symbol range
+/// was touched by an edit, replacing its contents and symbol range in place once the
+/// edits settle. A pinned popover is hidden instead.
+pub fn update_hover_on_edit(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let multi_buffer = editor.buffer.read(cx);
+    let Some(anchor) = editor.hover_state.info_popovers.iter().find_map(|popover| {
+        let RangeInEditor::Text(range) = &popover.symbol_range else {
+            return None;
+        };
+        let buffer = multi_buffer.buffer(range.start.buffer_id?)?;
+        let edited = buffer.read(cx).has_edits_since_in_range(
+            popover.buffer_version.as_ref()?,
+            range.start.text_anchor..range.end.text_anchor,
+        );
+        if edited {
+            popover.anchor
+        } else {
+            None
+        }
+    }) else {
+        return;
+    };
+    if editor.hover_state.pinned {
+        hide_hover(editor, cx);
+        return;
+    }
+    if !hover_settings(editor, anchor, cx).hover_live_update {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let multi_buffer = editor.buffer.read(cx);
+    let Some((buffer, buffer_position)) = multi_buffer.text_anchor_for_position(anchor, cx) else {
+        return;
+    };
+    let Some((excerpt_id, _, _)) = multi_buffer.excerpt_containing(anchor, cx) else {
+        return;
+    };
+
+    let task = cx.spawn(|this, mut cx| {
+        async move {
+            cx.background_executor()
+                .timer(Duration::from_millis(HOVER_LIVE_UPDATE_DEBOUNCE_MILLIS))
+                .await;
+
+            let (buffer_version, hover_request) = cx.update(|cx| {
+                let buffer_version = buffer.read(cx).version();
+                let hover_request = project.update(cx, |project, cx| {
+                    project.hover(&buffer, buffer_position, cx)
+                });
+                (buffer_version, hover_request)
+            })?;
+            let hovers_response = hover_request.await;
+
+            this.update(&mut cx, |editor, cx| {
+                let settings = hover_settings(editor, anchor, cx);
+                let typographic_replacements = settings.hover_typographic_replacements;
+                let preserve_server_spacing = settings.hover_preserve_server_spacing;
+                let plain_text = settings.hover_markup_kind == HoverMarkupKind::PlainText;
+                let snapshot = editor.buffer.read(cx).snapshot(cx);
+                // The popovers of the language server's hovers are the ones with a buffer
+                // version, in the order of its hovers. The others, such as a test's last run
+                // or a regex explanation, are kept as they are.
+                let server_popovers = editor
+                    .hover_state
+                    .info_popovers
+                    .iter_mut()
+                    .filter(|info_popover| info_popover.buffer_version.is_some());
+                for (info_popover, hover_result) in server_popovers.zip(
+                    hovers_response
+                        .into_iter()
+                        .filter(|hover| !hover.contents.is_empty()),
+                ) {
+                    info_popover.buffer_version = Some(buffer_version.clone());
+                    if let Some(range) = hover_result.range.and_then(|range| {
+                        let start = snapshot.anchor_in_excerpt(excerpt_id, range.start)?;
+                        let end = snapshot.anchor_in_excerpt(excerpt_id, range.end)?;
+                        Some(start..end)
+                    }) {
+                        info_popover.symbol_range = RangeInEditor::Text(range);
+                    }
+                    if let Some(markdown) = &info_popover.parsed_content {
+                        let text = if plain_text {
+                            plain_text_blocks(&hover_result.contents)
+                        } else {
+                            combine_blocks(
+                                &hover_result.contents,
+                                hover_result.language.as_ref(),
+                                typographic_replacements,
+                                preserve_server_spacing,
+                            )
+                        };
+                        markdown.update(cx, |markdown, cx| markdown.reset(text, cx));
+                    }
+                }
+                let ranges = editor
+                    .hover_state
+                    .info_popovers
+                    .iter()
+                    .filter_map(|info_popover| info_popover.symbol_range.as_text_range())
+                    .collect::<Vec<_>>();
+                update_hover_highlights(editor, &ranges, cx);
+                cx.notify();
+            })?;
+
+            anyhow::Ok(())
+        }
+        .log_err()
+    });
+
+    editor.hover_state.live_update_task = Some(task);
+}
+
+/// Whether the server cut the hover content short: it either ends with a
+/// truncation marker, or inside a code block whose fence is never closed.
+fn hover_content_truncated(source: &str) -> bool {
+    let source = source.trim_end();
+    if HOVER_TRUNCATION_MARKERS
+        .iter()
+        .any(|marker| source.ends_with(marker))
+    {
+        return true;
+    }
+    let mut open_fence: Option<&str> = None;
+    for line in source.lines() {
+        let line = line.trim_start();
+        match open_fence {
+            Some(fence) if line.trim_end() == fence => open_fence = None,
+            Some(_) => {}
+            None if line.starts_with("```") => open_fence = Some("```"),
+            None if line.starts_with("~~~") => open_fence = Some("~~~"),
+            None => {}
+        }
+    }
+    open_fence.is_some()
+}
+
+/// Joins hover blocks into a single markdown source, separating blocks of different
+/// kinds, like a signature and its docs, with a rule. Code blocks without a
+/// language tag are highlighted using the hovered buffer's language.
+fn combine_blocks(
+    blocks: &[HoverBlock],
+    buffer_language: Option<&Arc<Language>>,
+    typographic_replacements: bool,
+    preserve_server_spacing: bool,
+) -> String {
+    let mut combined = String::new();
+    let mut previous_kind = None;
+    for block in blocks {
+        let kind = std::mem::discriminant(&block.kind);
+        match previous_kind {
+            Some(previous_kind) if previous_kind != kind => {
+                combined.push_str(HOVER_BLOCK_SEPARATOR)
+            }
+            Some(_) => combined.push_str("\n\n"),
+            None => {}
+        }
+        previous_kind = Some(kind);
+        let text = match &block.kind {
+            // Markdown would join the lines of a paragraph and drop their indentation, break
+            // them where the server did and indent them with non-breaking spaces.
+            project::HoverBlockKind::PlainText if preserve_server_spacing => {
+                let text = strip_doc_comment_spaces(&block.text)
+                    .trim_end()
+                    .lines()
+                    .skip_while(|line| line.trim().is_empty())
+                    .map(|line| {
+                        let content = line.trim_start_matches([' ', '\t']);
+                        let indent = line.len() - content.len();
+                        let mut line = "\u{a0}".repeat(indent);
+                        line.push_str(content.trim_end());
+                        line
+                    })
+                    .join("  \n");
+                if typographic_replacements {
+                    Cow::Owned(apply_typographic_replacements(&text))
+                } else {
+                    Cow::Owned(text)
+                }
+            }
+            project::HoverBlockKind::PlainText | project::HoverBlockKind::Markdown => {
+                match strip_doc_comment_spaces(&block.text) {
+                    text if typographic_replacements => {
+                        Cow::Owned(apply_typographic_replacements(text.trim()))
+                    }
+                    Cow::Borrowed(text) => Cow::Borrowed(text.trim()),
+                    Cow::Owned(text) => Cow::Owned(text.trim().to_string()),
+                }
+            }
+            project::HoverBlockKind::Code { language } => {
+                let language = match buffer_language {
+                    Some(buffer_language) if language.trim().is_empty() => buffer_language.name(),
+                    _ => language.as_str().into(),
+                };
+                Cow::Owned(format!("```{}\n{}\n```", language, block.text.trim()))
+            }
+        };
+        combined.push_str(&text);
+    }
+    combined
+}
+
+/// Joins hover blocks into the plain text shown when `hover_markup_kind` is `plaintext`.
+fn plain_text_blocks(blocks: &[HoverBlock]) -> String {
+    blocks
+        .iter()
+        .map(|block| block.text.trim())
+        .filter(|text| !text.is_empty())
+        .join("\n\n")
+}
+
+/// Removes the single space that every line of documentation extracted from `/// ` comments
+/// starts with. Text whose lines are all indented further, like an indented code block,
+/// is left alone.
+fn strip_doc_comment_spaces(text: &str) -> Cow<str> {
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+    if lines.peek().is_none() {
+        return Cow::Borrowed(text);
+    }
+    let mut has_single_space = false;
+    for line in lines {
+        match line.strip_prefix(' ') {
+            Some(rest) => has_single_space |= !rest.starts_with(char::is_whitespace),
+            None => return Cow::Borrowed(text),
+        }
+    }
+    if !has_single_space {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.lines()
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+            .join("\n"),
+    )
+}
+
+/// Replaces straight quotes with curly ones and `--` with an em dash in the
+/// prose of a markdown source, leaving code and links untouched.
+fn apply_typographic_replacements(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut copied_up_to = 0;
+    let mut within_code_block = false;
+    let mut within_link = false;
+    for (range, event) in parse_markdown(source) {
+        match event {
+            MarkdownEvent::Start(MarkdownTag::CodeBlock(_)) => within_code_block = true,
+            MarkdownEvent::End(MarkdownTagEnd::CodeBlock) => within_code_block = false,
+            MarkdownEvent::Start(MarkdownTag::Link { .. }) => within_link = true,
+            MarkdownEvent::End(MarkdownTagEnd::Link) => within_link = false,
+            MarkdownEvent::Text
+                if !within_code_block && !within_link && range.start >= copied_up_to =>
+            {
+                result.push_str(&source[copied_up_to..range.start]);
+                push_typographic_text(&source[range.clone()], &mut result);
+                copied_up_to = range.end;
+            }
+            _ => {}
+        }
+    }
+    result.push_str(&source[copied_up_to..]);
+    result
+}
+
+fn push_typographic_text(text: &str, result: &mut String) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                result.push('—');
+            }
+            '"' | '\'' => {
+                let opening = result.chars().last().map_or(true, |previous| {
+                    previous.is_whitespace() || "([{—".contains(previous)
+                });
+                result.push(match (c, opening) {
+                    ('"', true) => '“',
+                    ('"', false) => '”',
+                    (_, true) => '‘',
+                    (_, false) => '’',
+                });
+            }
+            c => result.push(c),
+        }
+    }
+}
+
+/// Restyles the visible hover popovers if the settings their style is derived from changed.
+pub fn refresh_hover_styles(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    for info_popover in &editor.hover_state.info_popovers {
+        if let Some(markdown) = info_popover.parsed_content.as_ref() {
+            let style = hover_markdown_style(
+                hover_settings(editor, info_popover.settings_anchor(), cx),
+                cx,
+            );
+            let fingerprint = HoverStyleFingerprint::new(&style);
+            markdown.update(cx, |markdown, cx| {
+                if HoverStyleFingerprint::new(markdown.style()) != fingerprint {
+                    // The inline code language and the images come from the hovered buffer,
+                    // not the settings.
+                    let inline_code_language = markdown.style().inline_code_language.clone();
+                    let local_images = markdown.style().local_images.clone();
+                    markdown.set_style(
+                        MarkdownStyle {
+                            inline_code_language,
+                            local_images,
+                            ..style.clone()
+                        },
+                        cx,
+                    );
+                }
+            });
+        }
+    }
+}
+
+/// The parts of the hover markdown style that depend on the font, theme and hover settings.
+#[derive(PartialEq)]
+struct HoverStyleFingerprint {
+    base_text_style: TextStyle,
+    syntax: Arc<SyntaxTheme>,
+    selection_background_color: Hsla,
+    code_block_line_numbers: bool,
+    code_block_line_number_color: Hsla,
+    highlight_inline_code_with_doc_language: bool,
+    text_align: TextAlign,
+    paragraph_indent: f32,
+}
+
+impl HoverStyleFingerprint {
+    fn new(style: &MarkdownStyle) -> Self {
+        Self {
+            base_text_style: style.base_text_style.clone(),
+            syntax: style.syntax.clone(),
+            selection_background_color: style.selection_background_color,
+            code_block_line_numbers: style.code_block_line_numbers,
+            code_block_line_number_color: style.code_block_line_number_color,
+            highlight_inline_code_with_doc_language: style.highlight_inline_code_with_doc_language,
+            text_align: style.text_align,
+            paragraph_indent: style.paragraph_indent,
+        }
+    }
+}
+
+fn hover_markdown_style(editor_settings: &EditorSettings, cx: &WindowContext) -> MarkdownStyle {
+    let settings = ThemeSettings::get_global(cx);
+    let buffer_font_family = settings.buffer_font.family.clone();
+    let mut base_style = cx.text_style();
+    base_style.refine(&TextStyleRefinement {
+        font_family: Some(buffer_font_family.clone()),
+        color: Some(cx.theme().colors().editor_foreground),
+        ..Default::default()
+    });
+
+    MarkdownStyle {
+        base_text_style: base_style,
+        code_block: StyleRefinement::default().mt(rems(1.)).mb(rems(1.)),
+        inline_code: TextStyleRefinement {
+            background_color: Some(cx.theme().colors().background),
+            ..Default::default()
+        },
+        rule_color: Color::Muted.color(cx),
+        block_quote_border_color: Color::Muted.color(cx),
+        block_quote: TextStyleRefinement {
+            color: Some(Color::Muted.color(cx)),
+            ..Default::default()
+        },
+        link: TextStyleRefinement {
+            color: Some(cx.theme().colors().editor_foreground),
+            underline: Some(gpui::UnderlineStyle {
+                thickness: px(1.),
+                color: Some(cx.theme().colors().editor_foreground),
+                wavy: false,
+            }),
+            ..Default::default()
+        },
+        syntax: hover_syntax_theme(editor_settings, cx),
+        selection_background_color: { cx.theme().players().local().selection },
+        break_style: Default::default(),
+        heading: StyleRefinement::default()
+            .font_weight(FontWeight::BOLD)
+            .text_base()
+            .mt(rems(1.))
+            .mb_0(),
+        code_block_line_numbers: editor_settings.hover_code_line_numbers,
+        code_block_line_number_color: cx.theme().colors().editor_line_number,
+        highlight_inline_code_with_doc_language: editor_settings
+            .hover_highlight_inline_with_doc_language,
+        text_align: match editor_settings.hover_text_align {
+            HoverTextAlign::Left => TextAlign::Left,
+            HoverTextAlign::Justify => TextAlign::Justify,
+        },
+        paragraph_indent: editor_settings.hover_paragraph_indent,
+        images_as_links: true,
+        local_images: HashMap::default(),
+    }
+}
+
+/// The syntax theme of code in hovers, which is the one of `hover_code_theme` when it names
+/// a known theme, or else the current theme's.
+fn hover_syntax_theme(editor_settings: &EditorSettings, cx: &AppContext) -> Arc<SyntaxTheme> {
+    editor_settings
+        .hover_code_theme
+        .as_ref()
+        .and_then(|name| ThemeRegistry::global(cx).get(name).log_err())
+        .map_or_else(
+            || cx.theme().syntax().clone(),
+            |theme| theme.syntax().clone(),
+        )
+}
+
+/// Hover blocks parsed into markdown, along with what the popover derives from their source.
+#[derive(Default)]
+struct ParsedHoverBlocks {
+    markdown: Option<View<Markdown>>,
+    /// Loads of code block languages that weren't available yet.
+    pending_languages: Option<Shared<Task<()>>>,
+    generics_fold: Option<GenericsFold>,
+    toc_headings: Vec<HoverHeading>,
+    /// Whether to show that the language server cut the content short.
+    truncated: bool,
+}
+
+/// Parses a popover that isn't the language server's hover, such as a regex's explanation.
+async fn synthetic_info_popover(
+    blocks: &[HoverBlock],
+    range: Range<Anchor>,
+    anchor: Anchor,
+    keyboard_grace: bool,
+    language_registry: &Arc<LanguageRegistry>,
+    editor: &WeakView<Editor>,
+    cx: &mut AsyncWindowContext,
+) -> InfoPopover {
+    let parsed_blocks = parse_blocks(blocks, language_registry, None, editor, anchor, cx).await;
+    InfoPopover::new(
+        RangeInEditor::Text(range),
+        Some(anchor),
+        keyboard_grace,
+        parsed_blocks,
+    )
+}
+
+async fn parse_blocks(
+    blocks: &[HoverBlock],
+    language_registry: &Arc<LanguageRegistry>,
+    language: Option<Arc<Language>>,
+    editor: &WeakView<Editor>,
+    anchor: Anchor,
+    cx: &mut AsyncWindowContext,
+) -> ParsedHoverBlocks {
+    let fallback_language_name = if let Some(ref l) = language {
+        let l = Arc::clone(l);
+        Some(l.lsp_id().clone())
+    } else {
+        None
+    };
+
+    let Ok((
+        typographic_replacements,
+        preserve_server_spacing,
+        preload_code_languages,
+        fold_generics,
+        show_toc,
+        truncation_hint,
+        highlight_inline_with_buffer_language,
+        plain_text,
+        markdown_style,
+        local_images_dir,
+        fs,
+    )) = editor.update(cx, |editor, cx| {
+        let settings = hover_settings(editor, anchor, cx);
+        // Relative image paths are resolved against the directory of the hovered file.
+        let local_images_dir = anchor
+            .buffer_id
+            .and_then(|buffer_id| editor.buffer().read(cx).buffer(buffer_id))
+            .filter(|_| settings.hover_render_local_images)
+            .and_then(|buffer| {
+                let file = buffer.read(cx).file()?.as_local()?;
+                Some(file.abs_path(cx).parent()?.to_path_buf())
+            });
+        let fs = editor
+            .project
+            .as_ref()
+            .map(|project| project.read(cx).fs().clone());
+        (
+            settings.hover_typographic_replacements,
+            settings.hover_preserve_server_spacing,
+            settings.hover_preload_code_languages,
+            settings.hover_fold_generics,
+            settings.hover_show_toc,
+            settings.hover_truncation_hint,
+            settings.hover_highlight_inline_with_buffer_language,
+            settings.hover_markup_kind == HoverMarkupKind::PlainText,
+            hover_markdown_style(settings, cx),
+            local_images_dir,
+            fs,
+        )
+    })
+    else {
+        return ParsedHoverBlocks::default();
+    };
+    // Plain text is shown as is, without parsing it as markdown.
+    if plain_text {
+        let text = plain_text_blocks(blocks);
+        let truncated = truncation_hint && hover_content_truncated(&text);
+        let rendered_block = cx
+            .new_view(|cx| Markdown::new_text(text, markdown_style, cx))
+            .ok();
+        return ParsedHoverBlocks {
+            markdown: rendered_block,
+            truncated,
+            ..ParsedHoverBlocks::default()
+        };
+    }
+    let inline_code_language = language
+        .clone()
+        .filter(|_| highlight_inline_with_buffer_language);
+    let local_images = match local_images_dir.zip(fs) {
+        Some((dir, fs)) => local_hover_images(blocks, &dir, &fs).await,
+        None => HashMap::default(),
+    };
+    let combined_text = combine_blocks(
+        blocks,
+        language.as_ref(),
+        typographic_replacements,
+        preserve_server_spacing,
+    );
+    let truncated = truncation_hint && hover_content_truncated(&combined_text);
+    let loading_languages = if preload_code_languages {
+        loading_code_block_languages(&combined_text, language_registry)
+    } else {
+        Vec::new()
+    };
+    let generics_fold = if fold_generics {
+        fold_signature_generics(&combined_text, language_registry, language.as_ref())
+            .await
+            .map(|folded_source| GenericsFold {
+                full_headings: if show_toc {
+                    toc_headings(&combined_text)
+                } else {
+                    Vec::new()
+                },
+                folded_source,
+                full_source: combined_text.clone(),
+                expanded: Rc::new(RefCell::new(false)),
+            })
+    } else {
+        None
+    };
+    let source = match &generics_fold {
+        Some(generics_fold) => generics_fold.folded_source.clone(),
+        None => combined_text,
+    };
+    let toc_headings = if show_toc {
+        toc_headings(&source)
+    } else {
+        Vec::new()
+    };
+
+    let rendered_block = cx
+        .new_view(|cx| {
+            let mut style = markdown_style;
+            style.inline_code_language = inline_code_language;
+            style.local_images = local_images;
+            Markdown::new(
+                source,
+                style,
+                Some(language_registry.clone()),
+                cx,
+                fallback_language_name,
+            )
+            .on_url_click({
+                let editor = editor.clone();
+                move |url, cx| {
+                    if let Some(editor) = editor.upgrade() {
+                        let focus_handle = editor.read(cx).focus_handle.clone();
+                        focus_handle.dispatch_action(
+                            &OpenHoverLink {
+                                url: url.to_string(),
+                            },
+                            cx,
+                        );
+                    }
+                }
+            })
+            .render_incrementally(HOVER_INCREMENTAL_LINE_BUDGET)
+        })
+        .ok();
+
+    // Code blocks in languages that weren't loaded yet render without highlighting,
+    // so render them again once their languages are available.
+    let pending_languages = rendered_block
+        .clone()
+        .filter(|_| !loading_languages.is_empty())
+        .map(|markdown| {
+            cx.spawn(|mut cx| async move {
+                futures::future::join_all(loading_languages).await;
+                markdown.update(&mut cx, |_, cx| cx.notify()).ok();
+            })
+            .shared()
+        });
+
+    ParsedHoverBlocks {
+        markdown: rendered_block,
+        pending_languages,
+        generics_fold,
+        toc_headings,
+        truncated,
+    }
+}
+
+/// Folds the generic parameters and the `where` clause of the first code block of a markdown
+/// source that has any, e.g. `fn foo<T: Clone>(t: T)` becomes `fn foo<...>(t: T)`. Code blocks
+/// are parsed in their own language, or in `fallback_language` when they don't name one.
+async fn fold_signature_generics(
+    source: &str,
+    language_registry: &Arc<LanguageRegistry>,
+    fallback_language: Option<&Arc<Language>>,
+) -> Option<String> {
+    let mut code_blocks = Vec::new();
+    let mut code_block: Option<(Option<String>, Option<Range<usize>>)> = None;
+    for (range, event) in parse_markdown(source) {
+        match event {
+            MarkdownEvent::Start(MarkdownTag::CodeBlock(kind)) => {
+                code_block = Some((kind.language().map(str::to_string), None));
+            }
+            MarkdownEvent::Text => {
+                if let Some((_, code_range)) = code_block.as_mut() {
+                    *code_range = Some(match code_range.take() {
+                        Some(code_range) => code_range.start..range.end,
+                        None => range,
+                    });
+                }
+            }
+            MarkdownEvent::End(MarkdownTagEnd::CodeBlock) => {
+                if let Some((language_name, Some(code_range))) = code_block.take() {
+                    code_blocks.push((language_name, code_range));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (language_name, code_range) in code_blocks {
+        let language = match language_name {
+            Some(language_name) => language_registry
+                .language_for_name_or_extension(&language_name)
+                .await
+                .ok(),
+            None => fallback_language.cloned(),
+        };
+        let Some(language) = language else {
+            continue;
+        };
+        if let Some(folded_code) = fold_generics(&source[code_range.clone()], &language) {
+            let mut folded = source.to_string();
+            folded.replace_range(code_range, &folded_code);
+            return Some(folded);
+        }
+    }
+    None
+}
+
+/// Folds the generic parameter list of the item declared by a signature and its `where`
+/// clause, leaving the generic arguments of other types in the signature as they are.
+fn fold_generics(signature: &str, language: &Language) -> Option<String> {
+    let grammar = language.grammar()?;
+    // Hovers show declarations without their bodies, so parse them with an empty one when they
+    // don't parse on their own.
+    let tree = language::with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).ok()?;
+        let tree = parser.parse(signature, None)?;
+        if !tree.root_node().has_error() {
+            return Some(tree);
+        }
+        parser
+            .parse(format!("{signature} {{}}"), None)
+            .filter(|tree| !tree.root_node().has_error())
+            .or(Some(tree))
+    })?;
+
+    let mut item = tree.root_node();
+    while item.child_by_field_name("type_parameters").is_none() {
+        item = item.named_child(0)?;
+    }
+    let type_parameters = item.child_by_field_name("type_parameters")?;
+    let mut cursor = item.walk();
+    let where_clause = item
+        .children(&mut cursor)
+        .find(|child| child.kind() == "where_clause");
+
+    let mut folded = signature.to_string();
+    // Fold the `where` clause first, so that the offsets of the generics stay valid.
+    if let Some(where_clause) = where_clause {
+        let bounds_start = where_clause.start_byte() + "where".len();
+        let bounds_end = where_clause.end_byte().min(signature.trim_end().len());
+        if bounds_end > bounds_start {
+            folded.replace_range(bounds_start..bounds_end, " ...");
+        }
+    }
+    let generics_start = type_parameters.start_byte() + 1;
+    let generics_end = type_parameters.end_byte().saturating_sub(1);
+    if generics_end > generics_start {
+        folded.replace_range(generics_start..generics_end, "...");
+    }
+
+    (folded != signature).then_some(folded)
+}
+
+/// Starts loading the languages of the code blocks in a markdown source which aren't
+/// loaded yet, returning the loads that are still in progress.
+fn loading_code_block_languages(
+    source: &str,
+    language_registry: &Arc<LanguageRegistry>,
+) -> Vec<impl Future<Output = anyhow::Result<Arc<Language>>>> {
+    parse_markdown(source)
+        .into_iter()
+        .filter_map(|(_, event)| match event {
+            MarkdownEvent::Start(MarkdownTag::CodeBlock(kind)) => {
+                kind.language().map(str::to_string)
+            }
+            _ => None,
+        })
+        .unique()
+        .filter(|language| {
+            language_registry
+                .language_for_name(language)
+                .now_or_never()
+                .is_none()
+        })
+        .map(|language| language_registry.language_for_name(&language))
+        .collect()
+}
+
+/// Bindable action which focuses the next link of the visible hover popovers, for
+/// [`OpenFocusedHoverLink`] to open.
+pub fn hover_next_link(editor: &mut Editor, _: &HoverNextLink, cx: &mut ViewContext<Editor>) {
+    if !navigating_hover_links(editor, cx)
+        || !focus_adjacent_hover_link(editor, Direction::Next, cx)
+    {
+        cx.propagate();
+    }
+}
+
+/// Bindable action which focuses the previous link of the visible hover popovers, for
+/// [`OpenFocusedHoverLink`] to open.
+pub fn hover_prev_link(editor: &mut Editor, _: &HoverPrevLink, cx: &mut ViewContext<Editor>) {
+    if !navigating_hover_links(editor, cx)
+        || !focus_adjacent_hover_link(editor, Direction::Prev, cx)
+    {
+        cx.propagate();
+    }
+}
+
+/// Bindable action which opens the focused hover link the way clicking it would.
+pub fn open_focused_hover_link(
+    editor: &mut Editor,
+    _: &OpenFocusedHoverLink,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some((markdown, _, url)) = editor
+        .hover_state
+        .focused_link
+        .filter(|_| navigating_hover_links(editor, cx))
+        .and_then(|ix| hover_links(editor, cx).into_iter().nth(ix))
+    else {
+        cx.propagate();
+        return;
+    };
+    markdown.update(cx, |markdown, cx| markdown.open_url(url, cx));
+}
+
+/// Whether the hover link keys should go to the popovers rather than to the editor, which
+/// is only the case when a popover has the keyboard focus, was shown with the keyboard, or
+/// has a focused link. A hover shown by the mouse leaves tab and enter to the editor.
+fn navigating_hover_links(editor: &Editor, cx: &mut ViewContext<Editor>) -> bool {
+    editor.hover_state.focused_link.is_some()
+        || editor.hover_state.keyboard_invoked()
+        || editor.hover_state.focused(cx)
+}
+
+/// Focuses the link after or before the focused one, wrapping around, and selects it in
+/// its popover. Returns whether the visible popovers have links.
+fn focus_adjacent_hover_link(
+    editor: &mut Editor,
+    direction: Direction,
+    cx: &mut ViewContext<Editor>,
+) -> bool {
+    let links = hover_links(editor, cx);
+    let Some(last_ix) = links.len().checked_sub(1) else {
+        return false;
+    };
+    let focused_ix = editor.hover_state.focused_link.filter(|ix| *ix <= last_ix);
+    let ix = match (focused_ix, direction) {
+        (None, Direction::Next) => 0,
+        (None, Direction::Prev) => last_ix,
+        (Some(ix), Direction::Next) if ix == last_ix => 0,
+        (Some(ix), Direction::Next) => ix + 1,
+        (Some(ix), Direction::Prev) => ix.checked_sub(1).unwrap_or(last_ix),
+    };
+    editor.hover_state.focused_link = Some(ix);
+
+    let (focused_markdown, focused_range, _) = &links[ix];
+    for markdown in editor
+        .hover_state
+        .info_popovers
+        .iter()
+        .filter_map(|popover| popover.parsed_content.as_ref())
+    {
+        markdown.update(cx, |markdown, cx| markdown.clear_selection(cx));
+    }
+    focused_markdown.update(cx, |markdown, cx| {
+        markdown.select_source_range(focused_range.clone(), cx)
+    });
+    cx.notify();
+    true
+}
+
+/// The links of the visible info popovers in order, with the documentation each is in and
+/// its range in the documentation's source.
+fn hover_links(
+    editor: &Editor,
+    cx: &AppContext,
+) -> Vec<(View<Markdown>, Range<usize>, SharedString)> {
+    editor
+        .hover_state
+        .info_popovers
+        .iter()
+        .filter_map(|popover| popover.parsed_content.clone())
+        .flat_map(|markdown| {
+            markdown
+                .read(cx)
+                .parsed_markdown()
+                .events()
+                .iter()
+                .filter_map(|(range, event)| match event {
+                    MarkdownEvent::Start(MarkdownTag::Link { dest_url, .. }) => {
+                        Some((markdown.clone(), range.clone(), dest_url.clone()))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Handles [`OpenHoverLink`], which clicking a link in hover documentation dispatches.
+pub fn open_hover_link(_: &mut Editor, action: &OpenHoverLink, cx: &mut ViewContext<Editor>) {
+    cx.emit(EditorEvent::HoverLinkClicked(action.url.clone()));
+    open_hover_url(&cx.view().downgrade(), &action.url, cx);
+}
+
+/// Opens a link clicked in hover documentation. Links to local files, such as the definition
+/// links rust-analyzer includes, and relative links, which are resolved against the hovered
+/// buffer's worktree, are opened in the workspace instead of by the platform.
+pub fn open_hover_url(editor: &WeakView<Editor>, url: &str, cx: &mut WindowContext) {
+    let editor = editor.upgrade();
+    let workspace = editor
+        .as_ref()
+        .and_then(|editor| editor.read(cx).workspace());
+    let location = file_url_location(url)
+        .or_else(|| relative_link_location(editor.as_ref()?.read(cx), url, cx));
+    let (Some(workspace), Some((abs_path, position))) = (workspace, location) else {
+        cx.open_url(url);
+        return;
+    };
+
+    let open_task = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(abs_path, true, cx)
+    });
+    cx.spawn(|mut cx| async move {
+        let item = open_task.await?;
+        let Some(position) = position else {
+            return anyhow::Ok(());
+        };
+        if let Some(editor) = item.downcast::<Editor>() {
+            editor.update(&mut cx, |editor, cx| {
+                let buffer = editor.buffer().read(cx).snapshot(cx);
+                let point = buffer.clip_point(position, Bias::Left);
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Resolves a relative link, like `src/lib.rs#L12`, against the root of the worktree of
+/// the buffer whose hover documentation links to it. Links to a fragment of the docs, like
+/// `#examples`, and to paths that aren't files of the worktree aren't resolved.
+fn relative_link_location(
+    editor: &Editor,
+    url: &str,
+    cx: &AppContext,
+) -> Option<(PathBuf, Option<Point>)> {
+    if url.starts_with('#') || url::Url::parse(url) != Err(url::ParseError::RelativeUrlWithoutBase)
+    {
+        return None;
+    }
+    let buffer_id = editor
+        .hover_state
+        .info_popovers
+        .iter()
+        .find_map(|popover| popover.anchor?.buffer_id)?;
+    let buffer = editor.buffer().read(cx).buffer(buffer_id)?;
+    let file = buffer.read(cx).file()?;
+    let worktree = editor
+        .project
+        .as_ref()?
+        .read(cx)
+        .worktree_for_id(WorktreeId::from_usize(file.worktree_id()), cx)?;
+    let worktree = worktree.read(cx);
+    let root = url::Url::from_directory_path(worktree.abs_path()).ok()?;
+    let (abs_path, position) = file_url_location(root.join(url).ok()?.as_str())?;
+    let path = abs_path.strip_prefix(worktree.abs_path()).ok()?;
+    worktree
+        .entry_for_path(path)
+        .filter(|entry| entry.is_file())?;
+    Some((abs_path, position))
+}
+
+/// Parses a `file://` URL into its path and the position in its fragment, which
+/// may be written as `L12`, `L12C5`, `12` or `12:5`, with 1-based lines and columns.
+fn file_url_location(url: &str) -> Option<(PathBuf, Option<Point>)> {
+    let url = url::Url::parse(url).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    let abs_path = url.to_file_path().ok()?;
+    let position = url.fragment().and_then(|fragment| {
+        let fragment = fragment.strip_prefix('L').unwrap_or(fragment);
+        let (row, column) = match fragment.split_once(|c| c == 'C' || c == ':') {
+            Some((row, column)) => (row, Some(column)),
+            None => (fragment, None),
+        };
+        let row = row.parse::<u32>().ok()?;
+        let column = column.and_then(|column| column.parse::<u32>().ok());
+        Some(Point::new(
+            row.saturating_sub(1),
+            column.unwrap_or(1).saturating_sub(1),
+        ))
+    });
+    Some((abs_path, position))
+}
+
+#[derive(Default, Debug)]
+pub struct HoverState {
+    pub info_popovers: Vec<InfoPopover>,
+    pub diagnostic_popover: Option<DiagnosticPopover>,
+    pub triggered_from: Option<Anchor>,
+    pub info_task: Option<Task<Option<()>>>,
+    pub live_update_task: Option<Task<Option<()>>>,
+    pub last_edit_at: Option<Instant>,
+    /// Popovers that were hidden and are fading out before being removed.
+    pub dismissing: Option<DismissingHover>,
+    /// The symbol ranges of the last hidden popovers and when they were hidden.
+    pub last_dismissed: Option<(Vec<Range<Anchor>>, Instant)>,
+    /// The info popovers last shown for each buffer, when `hover_remember_last` is enabled.
+    pub last_info_popovers: HashMap<BufferId, Vec<InfoPopover>>,
+    /// The language server's last hovers, the most recently used last, when `hover_cache`
+    /// is enabled.
+    pub cached_hovers: Vec<CachedHovers>,
+    /// Whether the popovers were pinned with [`PinHover`], keeping them visible when the
+    /// mouse moves away, the cursor moves, or the editor scrolls.
+    pub pinned: bool,
+    /// The bounds of the visible popovers, as laid out in the last frame.
+    pub popover_bounds: Vec<Bounds<Pixels>>,
+    /// Whether the mouse is over a popover or close enough to one to be on its way there.
+    pub mouse_over_popover: bool,
+    /// The index of the link that [`HoverNextLink`] and [`HoverPrevLink`] focused among the
+    /// links of the visible info popovers.
+    pub focused_link: Option<usize>,
+}
+
+/// The language server's hovers at an offset of a buffer, which also answer for the other
+/// offsets of the hovered symbols until the buffer changes.
+#[derive(Debug)]
+pub struct CachedHovers {
+    pub buffer_id: BufferId,
+    pub offset: usize,
+    pub symbol_ranges: Vec<Range<usize>>,
+    pub version: clock::Global,
+    pub hovers: Vec<project::Hover>,
+}
+
+#[derive(Debug)]
+pub struct DismissingHover {
+    pub info_popovers: Vec<InfoPopover>,
+    pub diagnostic_popover: Option<DiagnosticPopover>,
+    pub anchor: Anchor,
+    pub duration: Duration,
+    _task: Task<()>,
+}
+
+impl HoverState {
+    pub fn visible(&self) -> bool {
+        !self.info_popovers.is_empty() || self.diagnostic_popover.is_some()
+    }
+
+    /// [`hover_settings`] for the hovered diagnostic or symbol, if there is any.
+    fn settings<'a>(
+        &self,
+        buffer: &MultiBufferSnapshot,
+        cx: &'a AppContext,
+    ) -> Option<&'a EditorSettings> {
+        match &self.diagnostic_popover {
+            Some(diagnostic_popover) => Some(diagnostic_popover.settings(buffer, cx)),
+            None => Some(self.info_popovers.first()?.settings(buffer, cx)),
+        }
+    }
+
+    /// Whether a visible info popover was shown with the keyboard, rather than by the mouse,
+    /// and wasn't clicked since.
+    pub fn keyboard_invoked(&self) -> bool {
+        self.info_popovers
+            .iter()
+            .any(|popover| *popover.keyboard_grace.borrow())
+    }
+
+    /// Whether the buffer was edited within the last `hover_delay` milliseconds, which is
+    /// considered an edit sequence in progress.
+    pub fn typing(&self, hover_delay: u64, cx: &AppContext) -> bool {
+        self.last_edit_at.map_or(false, |last_edit_at| {
+            cx.background_executor().now() - last_edit_at < Duration::from_millis(hover_delay)
+        })
+    }
+
+    /// Tracks whether the mouse is over a popover, counting the [`HOVER_POPOVER_GAP`] around
+    /// it, so that moving from the symbol into its popover doesn't dismiss it.
+    pub fn update_mouse_over_popover(&mut self, position: gpui::Point<Pixels>) {
+        self.mouse_over_popover = self.popover_bounds.iter().any(|bounds| {
+            let mut bounds = *bounds;
+            bounds.dilate(HOVER_POPOVER_GAP);
+            bounds.contains(&position)
+        });
+    }
+
+    /// Remembers the language server's hovers at an offset of a buffer, forgetting the ones
+    /// of older versions of the buffer and the least recently used ones beyond
+    /// [`HOVER_CACHE_CAPACITY`]. Hovers requested for an older version aren't remembered.
+    fn cache_hovers(
+        &mut self,
+        buffer: &BufferSnapshot,
+        offset: usize,
+        version: clock::Global,
+        hovers: Vec<project::Hover>,
+    ) {
+        if buffer.version() != &version {
+            return;
+        }
+        let buffer_id = buffer.remote_id();
+        self.cached_hovers.retain(|cached| {
+            cached.buffer_id != buffer_id || (cached.version == version && cached.offset != offset)
+        });
+        if self.cached_hovers.len() == HOVER_CACHE_CAPACITY {
+            self.cached_hovers.remove(0);
+        }
+        let symbol_ranges = hovers
+            .iter()
+            .filter_map(|hover| Some(hover.range.as_ref()?.to_offset(buffer)))
+            .collect();
+        self.cached_hovers.push(CachedHovers {
+            buffer_id,
+            offset,
+            symbol_ranges,
+            version,
+            hovers,
+        });
+    }
+
+    /// The cached hovers of the symbol at an offset of a buffer, if the buffer didn't change
+    /// since they were requested.
+    fn cached_hovers(
+        &mut self,
+        buffer_id: BufferId,
+        offset: usize,
+        version: &clock::Global,
+    ) -> Option<Vec<project::Hover>> {
+        let ix = self.cached_hovers.iter().position(|cached| {
+            cached.buffer_id == buffer_id
+                && cached.version == *version
+                && (cached.offset == offset
+                    || cached
+                        .symbol_ranges
+                        .iter()
+                        .any(|range| range.contains(&offset)))
+        })?;
+        let cached = self.cached_hovers.remove(ix);
+        let hovers = cached.hovers.clone();
+        self.cached_hovers.push(cached);
+        Some(hovers)
+    }
+
+    /// Whether the anchor is in a symbol whose popover was hidden less than `cooldown` ago.
+    fn dismissed_recently(
+        &self,
+        anchor: Anchor,
+        cooldown: Duration,
+        snapshot: &MultiBufferSnapshot,
+        cx: &AppContext,
+    ) -> bool {
+        self.last_dismissed
+            .as_ref()
+            .map_or(false, |(ranges, dismissed_at)| {
+                cx.background_executor().now() - *dismissed_at < cooldown
+                    && ranges.iter().any(|range| {
+                        range.start.cmp(&anchor, snapshot).is_le()
+                            && range.end.cmp(&anchor, snapshot).is_ge()
+                    })
+            })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        snapshot: &EditorSnapshot,
+        style: &EditorStyle,
+        visible_rows: Range<DisplayRow>,
+        max_size: Size<Pixels>,
+        max_heights: Option<HoverPopoverHeights>,
+        width_bounds: Option<HoverPopoverWidthBounds>,
+        _workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<(DisplayPoint, Vec<AnyElement>)> {
+        if !self.visible() {
+            return self.render_dismissing(
+                snapshot,
+                style,
+                visible_rows,
+                max_size,
+                width_bounds,
+                cx,
+            );
+        }
+
+        let anchor = self.popover_anchor(&snapshot.buffer_snapshot, cx)?;
+        let point = anchor.to_display_point(&snapshot.display_snapshot);
+
+        // Don't render if the relevant point isn't on screen
+        if !self.visible() || !visible_rows.contains(&point.row()) {
+            return None;
+        }
+
+        let mut elements = Vec::new();
+
+        if let Some(diagnostic_popover) = self.diagnostic_popover.as_ref() {
+            let max_size = Size {
+                height: max_heights.map_or(max_size.height, |heights| heights.diagnostic),
+                ..max_size
+            };
+            elements.push(diagnostic_popover.render(
+                style,
+                max_size,
+                &snapshot.buffer_snapshot,
+                cx,
+            ));
+        }
+        let max_size = Size {
+            height: max_heights.map_or(max_size.height, |heights| heights.info),
+            ..max_size
+        };
+        for info_popover in &mut self.info_popovers {
+            if info_popovers_in_editor(info_popover.settings(&snapshot.buffer_snapshot, cx)) {
+                elements.push(info_popover.render(
+                    max_size,
+                    width_bounds,
+                    &snapshot.buffer_snapshot,
+                    cx,
+                ));
+            }
+        }
+
+        if elements.is_empty() {
+            return None;
+        }
+        Some((point, elements))
+    }
+
+    /// Renders the translucent backdrop dimming the editor behind visible popovers
+    /// when `hover_backdrop` is enabled. Clicking it dismisses the popovers.
+    pub fn render_backdrop(
+        &self,
+        buffer: &MultiBufferSnapshot,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<AnyElement> {
+        let settings = self.settings(buffer, cx)?;
+        if !settings.hover_backdrop
+            || (self.diagnostic_popover.is_none()
+                && (self.info_popovers.is_empty() || !info_popovers_in_editor(settings)))
+        {
+            return None;
+        }
+
+        let mut background = cx.theme().colors().editor_background;
+        background.fade_out(0.5);
+        Some(
+            div()
+                .id("hover_backdrop")
+                .size_full()
+                .bg(background)
+                // The backdrop doesn't occlude the editor, so that the click dismissing the
+                // popovers also reaches the text beneath it.
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(|editor, _, cx| {
+                        hide_hover(editor, cx);
+                    }),
+                )
+                .into_any_element(),
+        )
+    }
+
+    fn render_dismissing(
+        &mut self,
+        snapshot: &EditorSnapshot,
+        style: &EditorStyle,
+        visible_rows: Range<DisplayRow>,
+        max_size: Size<Pixels>,
+        width_bounds: Option<HoverPopoverWidthBounds>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<(DisplayPoint, Vec<AnyElement>)> {
+        let dismissing = self.dismissing.as_mut()?;
+        let point = dismissing
+            .anchor
+            .to_display_point(&snapshot.display_snapshot);
+        if !visible_rows.contains(&point.row()) {
+            return None;
+        }
+
+        let mut elements = Vec::new();
+        if let Some(diagnostic_popover) = dismissing.diagnostic_popover.as_ref() {
+            elements.push(diagnostic_popover.render(
+                style,
+                max_size,
+                &snapshot.buffer_snapshot,
+                cx,
+            ));
+        }
+        for info_popover in &mut dismissing.info_popovers {
+            if info_popovers_in_editor(info_popover.settings(&snapshot.buffer_snapshot, cx)) {
+                elements.push(info_popover.render(
+                    max_size,
+                    width_bounds,
+                    &snapshot.buffer_snapshot,
+                    cx,
+                ));
+            }
+        }
+
+        let duration = dismissing.duration;
+        let elements = elements
+            .into_iter()
+            .enumerate()
+            .map(|(ix, element)| {
+                // A fading popover is no longer interactive: cover it so that it
+                // doesn't handle any mouse input, nor let it through to the editor.
+                div()
+                    .relative()
+                    .child(element)
+                    .child(div().absolute().inset_0().occlude())
+                    .with_animation(
+                        ("hover-fade-out", ix),
+                        Animation::new(duration),
+                        |popover, delta| popover.opacity(1. - delta),
+                    )
+                    .into_any_element()
+            })
+            .collect();
+
+        Some((point, elements))
+    }
+
+    /// The anchor the popovers are positioned at. If there is a diagnostic, position
+    /// the popovers based on that. Otherwise use the start of the hover range.
+    pub(crate) fn popover_anchor(
+        &self,
+        buffer: &MultiBufferSnapshot,
+        cx: &AppContext,
+    ) -> Option<Anchor> {
+        self.diagnostic_popover
+            .as_ref()
+            .map(|diagnostic_popover| {
+                match diagnostic_popover
+                    .settings(buffer, cx)
+                    .hover_diagnostic_anchor
+                {
+                    HoverDiagnosticAnchor::RangeStart => {
+                        diagnostic_popover.local_diagnostic.range.start
+                    }
+                    HoverDiagnosticAnchor::MousePoint => diagnostic_popover.hovered_point,
+                }
+            })
+            .or_else(|| {
+                self.info_popovers.iter().find_map(|info_popover| {
+                    match &info_popover.symbol_range {
+                        RangeInEditor::Text(range) => Some(range.start),
+                        RangeInEditor::Inlay(_) => None,
+                    }
+                })
+            })
+            .or_else(|| {
+                self.info_popovers.iter().find_map(|info_popover| {
+                    match &info_popover.symbol_range {
+                        RangeInEditor::Text(_) => None,
+                        RangeInEditor::Inlay(range) => Some(range.inlay_position),
+                    }
+                })
+            })
+    }
+
+    /// Scrolls every visible info popover to the top, returning whether any popover was scrolled.
+    pub fn scroll_to_top(&self, cx: &mut ViewContext<Editor>) -> bool {
+        for info_popover in &self.info_popovers {
+            info_popover.scroll_to(Pixels::ZERO, cx);
+        }
+        !self.info_popovers.is_empty()
+    }
+
+    /// Scrolls every visible info popover to the bottom, returning whether any popover was scrolled.
+    pub fn scroll_to_bottom(&self, cx: &mut ViewContext<Editor>) -> bool {
+        for info_popover in &self.info_popovers {
+            // The offset is clamped to the scrollable extent when the popover is laid out.
+            info_popover.scroll_to(-Pixels::MAX, cx);
+        }
+        !self.info_popovers.is_empty()
+    }
+
+    pub fn focused(&self, cx: &mut ViewContext<Editor>) -> bool {
+        let mut hover_popover_is_focused = false;
+        for info_popover in &self.info_popovers {
+            for markdown_view in &info_popover.parsed_content {
+                if markdown_view.focus_handle(cx).is_focused(cx) {
+                    hover_popover_is_focused = true;
+                }
+            }
+        }
+        return hover_popover_is_focused;
+    }
+}
+
+#[derive(Debug, Clone)]
+
+pub struct InfoPopover {
+    pub symbol_range: RangeInEditor,
+    pub parsed_content: Option<View<Markdown>>,
+    pub scroll_handle: ScrollHandle,
+    pub keyboard_grace: Rc<RefCell<bool>>,
+    pub anchor: Option<Anchor>,
+    /// The version of the hovered buffer at the time the hover was requested,
+    /// used to detect edits to the symbol while the popover is visible.
+    pub buffer_version: Option<clock::Global>,
+    /// The test whose last run result is shown, offered to be run again.
+    pub test_task: Option<(TaskSourceKind, ResolvedTask)>,
+    pub toc_expanded: Rc<RefCell<bool>>,
+    /// The headings listed in the table of contents, empty unless there are several of them.
+    pub toc_headings: Vec<HoverHeading>,
+    /// Whether the content looks cut short by the language server, shown in a footer.
+    pub content_truncated: bool,
+    /// Loads of code block languages that weren't available when the popover was rendered.
+    pub pending_languages: Option<Shared<Task<()>>>,
+    /// The definition of the hovered symbol, when it is in a dependency's source.
+    pub dependency_source: Option<Location>,
+    pub generics_fold: Option<GenericsFold>,
+    /// The examples of the documentation that can be run as a doctest.
+    pub doctest: Option<HoverDoctest>,
+    /// Whether the hovered buffer had unsaved changes when the hover was requested, which
+    /// servers that read files from disk don't know about.
+    pub stale_warning: bool,
+    /// The matching bracket of the hovered bracket, navigated to from the popover.
+    pub bracket_match: Option<Anchor>,
+    /// The kind of the hovered symbol, when the language server lists it among the document's symbols.
+    pub symbol_kind: Option<lsp::SymbolKind>,
+}
+
+/// A doctest of the hovered Rust item, run as a task by the popover's "Run doctest" button.
+#[derive(Debug, Clone)]
+pub struct HoverDoctest {
+    pub task_source_kind: TaskSourceKind,
+    pub resolved_task: ResolvedTask,
+    pub inventory: Model<Inventory>,
+    /// The result of the doctest before it was last run from the popover, and the observation
+    /// of the task inventory that redraws the popover once the run finishes.
+    run: Rc<RefCell<Option<(Option<TaskRunResult>, Task<()>)>>>,
+}
+
+impl HoverDoctest {
+    /// Schedules the doctest in the workspace, showing its result in the popover when it finishes.
+    pub fn run(&self, editor: &Editor, cx: &mut ViewContext<Editor>) {
+        let Some(workspace) = editor.workspace() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            workspace::tasks::schedule_resolved_task(
+                workspace,
+                self.task_source_kind.clone(),
+                self.resolved_task.clone(),
+                false,
+                cx,
+            );
+        });
+        let previous_result = self.last_result(cx);
+        let subscription = cx.observe(&self.inventory, |_, _, cx| cx.notify());
+        let observation = cx.spawn(|_, _| async move {
+            let _subscription = subscription;
+            futures::future::pending::<()>().await
+        });
+        *self.run.borrow_mut() = Some((previous_result, observation));
+        cx.notify();
+    }
+
+    /// The outcome of the last finished run of the doctest, if any.
+    pub fn last_result(&self, cx: &AppContext) -> Option<TaskRunResult> {
+        self.inventory
+            .read(cx)
+            .last_task_result(&self.resolved_task.id)
+    }
+
+    /// Whether the doctest was run from the popover and hasn't finished yet.
+    pub fn running(&self, cx: &AppContext) -> bool {
+        self.run
+            .borrow()
+            .as_ref()
+            .map_or(false, |(previous_result, _)| {
+                *previous_result == self.last_result(cx)
+            })
+    }
+}
+
+/// The sources of hover documentation whose signature's generics were folded.
+#[derive(Debug, Clone)]
+pub struct GenericsFold {
+    pub folded_source: String,
+    pub full_source: String,
+    /// The table of contents of the full source, whose headings are at other source indices.
+    pub full_headings: Vec<HoverHeading>,
+    pub expanded: Rc<RefCell<bool>>,
+}
+
+impl GenericsFold {
+    /// Shows the full signature if it was folded, and folds it again otherwise.
+    pub fn toggle(&self, markdown: &View<Markdown>, cx: &mut WindowContext) {
+        let mut expanded = self.expanded.borrow_mut();
+        *expanded = !*expanded;
+        let source = if *expanded {
+            self.full_source.clone()
+        } else {
+            self.folded_source.clone()
+        };
+        markdown.update(cx, |markdown, cx| markdown.reset(source, cx));
+    }
+}
+
+/// A heading of the hover documentation, listed in the table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoverHeading {
+    pub level: usize,
+    pub title: String,
+    /// The index of the heading's first character in the rendered markdown source.
+    pub source_index: usize,
+}
+
+impl InfoPopover {
+    /// A popover of the parsed hover blocks over the given range, without any of the extras
+    /// that only some popovers show.
+    fn new(
+        symbol_range: RangeInEditor,
+        anchor: Option<Anchor>,
+        keyboard_grace: bool,
+        parsed_blocks: ParsedHoverBlocks,
+    ) -> Self {
+        Self {
+            symbol_range,
+            parsed_content: parsed_blocks.markdown,
+            scroll_handle: ScrollHandle::new(),
+            keyboard_grace: Rc::new(RefCell::new(keyboard_grace)),
+            anchor,
+            buffer_version: None,
+            test_task: None,
+            toc_expanded: Rc::new(RefCell::new(false)),
+            toc_headings: parsed_blocks.toc_headings,
+            content_truncated: parsed_blocks.truncated,
+            pending_languages: parsed_blocks.pending_languages,
+            dependency_source: None,
+            generics_fold: parsed_blocks.generics_fold,
+            doctest: None,
+            stale_warning: false,
+            bracket_match: None,
+            symbol_kind: None,
+        }
+    }
+
+    /// The anchor of the hovered symbol, where the popover's settings are resolved.
+    fn settings_anchor(&self) -> Anchor {
+        match &self.symbol_range {
+            RangeInEditor::Text(range) => range.start,
+            RangeInEditor::Inlay(range) => range.inlay_position,
+        }
+    }
+
+    fn settings<'a>(&self, buffer: &MultiBufferSnapshot, cx: &'a AppContext) -> &'a EditorSettings {
+        hover_settings_at(buffer, self.settings_anchor(), cx)
+    }
+
+    /// Renders the popover within `max_size`, whose width is replaced by the one for the
+    /// hovered buffer's language when `width_bounds` are given.
+    pub fn render(
+        &mut self,
+        max_size: Size<Pixels>,
+        width_bounds: Option<HoverPopoverWidthBounds>,
+        buffer: &MultiBufferSnapshot,
+        cx: &mut ViewContext<Editor>,
+    ) -> AnyElement {
+        let max_size = match width_bounds.zip(self.anchor) {
+            Some((width_bounds, anchor)) => Size {
+                width: language_hover_popover_max_width(buffer, anchor, width_bounds, cx),
+                ..max_size
+            },
+            None => max_size,
+        };
+        let keyboard_grace = Rc::clone(&self.keyboard_grace);
+        let settings = self.settings(buffer, cx);
+        let block_underlying_moves = settings.hover_block_underlying_moves;
+        let show_hints = settings.hover_show_hints;
+        let mut d = div()
+            .id("info_popover")
+            .elevation_2(cx)
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .max_w(max_size.width)
+            .max_h(max_size.height)
+            // Prevent a mouse down/move on the popover from being propagated to the editor,
+            // because that would dismiss the popover.
+            .when(block_underlying_moves, |d| {
+                d.on_mouse_move(|_, cx| cx.stop_propagation())
+            })
+            .on_mouse_down(MouseButton::Left, move |_, cx| {
+                let mut keyboard_grace = keyboard_grace.borrow_mut();
+                *keyboard_grace = false;
+                cx.stop_propagation();
+            })
+            .p_2();
+
+        if let Some((icon, name)) = self.symbol_kind.and_then(symbol_kind_icon) {
+            d = d.child(
+                h_flex()
+                    .mb_1()
+                    .gap_1()
+                    .child(Icon::new(icon).size(IconSize::XSmall).color(Color::Muted))
+                    .child(Label::new(name).size(LabelSize::XSmall).color(Color::Muted)),
+            );
+        }
+        if !self.toc_headings().is_empty() {
+            d = d.child(self.render_toc(cx));
+        }
+        if let Some(markdown) = &self.parsed_content {
+            d = d.child(markdown.clone());
+            if let Some(generics_fold) = self.generics_fold.clone() {
+                let markdown = markdown.clone();
+                let expanded = *generics_fold.expanded.borrow();
+                d = d.child(
+                    div()
+                        .id("hover-generics-toggle")
+                        .mt_1()
+                        .child(
+                            Label::new(if expanded {
+                                "Fold generics"
+                            } else {
+                                "Show generics"
+                            })
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        )
+                        .on_click(cx.listener(move |_, _, cx| {
+                            generics_fold.toggle(&markdown, cx);
+                            cx.stop_propagation();
+                            cx.notify();
+                        })),
+                );
+            }
+        }
+        if let Some(doctest) = self.doctest.clone() {
+            let running = doctest.running(cx);
+            let result = doctest.last_result(cx).filter(|_| !running);
+            d = d.child(
+                h_flex()
+                    .mt_2()
+                    .gap_2()
+                    .child(
+                        Button::new(
+                            "run-doctest",
+                            if running {
+                                "Running doctest…"
+                            } else {
+                                "Run doctest"
+                            },
+                        )
+                        .icon(IconName::Play)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .label_size(LabelSize::Small)
+                        .disabled(running)
+                        .on_click(cx.listener(move |editor, _, cx| {
+                            doctest.run(editor, cx);
+                            cx.stop_propagation();
+                        })),
+                    )
+                    .children(result.map(|result| {
+                        Label::new(doctest_result_text(result))
+                            .size(LabelSize::Small)
+                            .color(if result.success {
+                                Color::Success
+                            } else {
+                                Color::Error
+                            })
+                    })),
+            );
+        }
+        if let Some((task_source_kind, resolved_task)) = self.test_task.clone() {
+            d = d.child(
+                div().mt_2().child(
+                    Button::new("run-test", "Run test")
+                        .icon(IconName::Play)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(move |editor, _, cx| {
+                            if let Some(workspace) = editor.workspace() {
+                                workspace.update(cx, |workspace, cx| {
+                                    workspace::tasks::schedule_resolved_task(
+                                        workspace,
+                                        task_source_kind.clone(),
+                                        resolved_task.clone(),
+                                        false,
+                                        cx,
+                                    );
+                                });
+                            }
+                            hide_hover(editor, cx);
+                        })),
+                ),
+            );
+        }
+        if let Some(bracket_match) = self.bracket_match {
+            d = d.child(
+                div().mt_2().child(
+                    Button::new("go-to-matching-bracket", "Go to matching bracket")
+                        .icon(IconName::ArrowUpRight)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(move |editor, _, cx| {
+                            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                                s.select_anchor_ranges([bracket_match..bracket_match])
+                            });
+                            hide_hover(editor, cx);
+                        })),
+                ),
+            );
+        }
+        if let Some(location) = self.dependency_source.clone() {
+            d = d.child(
+                div().mt_2().child(
+                    Button::new("view-dependency-source", "View source")
+                        .icon(IconName::Code)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(move |editor, _, cx| {
+                            open_dependency_source(editor, location.clone(), cx);
+                            hide_hover(editor, cx);
+                        })),
+                ),
+            );
+        }
+        if self.stale_warning {
+            d = d.child(
+                div()
+                    .mt_2()
+                    .pt_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new("Unsaved changes, may be stale")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Warning)
+                            .italic(true),
+                    ),
+            );
+        }
+        if self.content_truncated {
+            d = d.child(
+                div()
+                    .mt_2()
+                    .pt_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        Label::new("Content may be truncated")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .italic(true),
+                    ),
+            );
+        }
+        if let Some(pin_hint) = render_pin_hint(show_hints, cx) {
+            d = d.child(pin_hint);
+        }
+        d.into_any_element()
+    }
+
+    /// The first non-empty line of the rendered documentation.
+    pub fn first_line(&self, cx: &AppContext) -> Option<String> {
+        let parsed_markdown = self.parsed_content.as_ref()?.read(cx).parsed_markdown();
+        let mut line = String::new();
+        for (range, event) in parsed_markdown.events().iter() {
+            match event {
+                MarkdownEvent::Text | MarkdownEvent::Code => {
+                    let text = &parsed_markdown.source()[range.clone()];
+                    match text.split_once('\n') {
+                        Some((first_line, _)) => {
+                            line.push_str(first_line);
+                            if !line.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        None => line.push_str(text),
+                    }
+                }
+                MarkdownEvent::SoftBreak | MarkdownEvent::HardBreak | MarkdownEvent::End(_)
+                    if !line.trim().is_empty() =>
+                {
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let line = line.trim();
+        (!line.is_empty()).then(|| line.to_string())
+    }
+
+    /// The headings of the shown source to list in the table of contents.
+    pub fn toc_headings(&self) -> &[HoverHeading] {
+        match &self.generics_fold {
+            Some(generics_fold) if *generics_fold.expanded.borrow() => &generics_fold.full_headings,
+            _ => &self.toc_headings,
+        }
+    }
+
+    fn render_toc(&self, cx: &mut ViewContext<Editor>) -> AnyElement {
+        let toc_expanded = *self.toc_expanded.borrow();
+        let toggle_toc = Rc::clone(&self.toc_expanded);
+        v_flex()
+            .mb_2()
+            .pb_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div()
+                    .id("hover-toc-toggle")
+                    .child(
+                        Label::new(if toc_expanded {
+                            "Hide contents"
+                        } else {
+                            "Show contents"
+                        })
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    )
+                    .on_click(cx.listener(move |_, _, cx| {
+                        let mut toc_expanded = toggle_toc.borrow_mut();
+                        *toc_expanded = !*toc_expanded;
+                        cx.stop_propagation();
+                        cx.notify();
+                    })),
+            )
+            .when(toc_expanded, |this| {
+                this.children(self.toc_headings().iter().enumerate().map(|(ix, heading)| {
+                    let scroll_handle = self.scroll_handle.clone();
+                    let markdown = self.parsed_content.clone();
+                    let source_index = heading.source_index;
+                    div()
+                        .id(("hover-toc-heading", ix))
+                        .pl(px(8. * heading.level.saturating_sub(1) as f32))
+                        .cursor(CursorStyle::PointingHand)
+                        .child(
+                            Label::new(heading.title.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Accent),
+                        )
+                        .on_click(cx.listener(move |_, _, cx| {
+                            if let Some(markdown) = markdown.as_ref() {
+                                scroll_to_source_index(&scroll_handle, markdown, source_index, cx);
+                            }
+                            cx.stop_propagation();
+                        }))
+                }))
+            })
+            .into_any_element()
+    }
+
+    pub fn scroll(&self, amount: &ScrollAmount, cx: &mut ViewContext<Editor>) {
+        let mut current = self.scroll_handle.offset();
+        current.y -= amount.pixels(
+            cx.line_height(),
+            self.scroll_handle.bounds().size.height - px(16.),
+        ) / 2.0;
+        cx.notify();
+        self.scroll_handle.set_offset(current);
+    }
+
+    fn scroll_to(&self, y: Pixels, cx: &mut ViewContext<Editor>) {
+        let mut current = self.scroll_handle.offset();
+        current.y = y;
+        cx.notify();
+        self.scroll_handle.set_offset(current);
+    }
+}
+
+/// Scrolls the popover so that the given markdown source index is at its top.
+fn scroll_to_source_index(
+    scroll_handle: &ScrollHandle,
+    markdown: &View<Markdown>,
+    source_index: usize,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(position) = markdown.read(cx).position_for_source_index(source_index) else {
+        return;
+    };
+    let mut offset = scroll_handle.offset();
+    offset.y -= position.y - scroll_handle.bounds().top();
+    scroll_handle.set_offset(offset);
+    cx.notify();
+}
+
+/// The headings of the markdown source for the table of contents, empty unless there are
+/// several of them.
+fn toc_headings(source: &str) -> Vec<HoverHeading> {
+    let headings = markdown_headings(source, &parse_markdown(source));
+    if headings.len() > 1 {
+        headings
+    } else {
+        Vec::new()
+    }
+}
+
+fn markdown_headings(source: &str, events: &[(Range<usize>, MarkdownEvent)]) -> Vec<HoverHeading> {
+    let mut headings = Vec::new();
+    let mut current_heading: Option<HoverHeading> = None;
+    for (range, event) in events {
+        match event {
+            MarkdownEvent::Start(MarkdownTag::Heading { level, .. }) => {
+                current_heading = Some(HoverHeading {
+                    level: *level as usize,
+                    title: String::new(),
+                    source_index: range.start,
+                });
+            }
+            MarkdownEvent::Text | MarkdownEvent::Code => {
+                if let Some(heading) = current_heading.as_mut() {
+                    if heading.title.is_empty() {
+                        heading.source_index = range.start;
+                    }
+                    // Code spans include their backticks.
+                    heading
+                        .title
+                        .push_str(source[range.clone()].trim_matches('`'));
+                }
+            }
+            MarkdownEvent::End(MarkdownTagEnd::Heading(_)) => {
+                if let Some(heading) = current_heading.take() {
+                    if !heading.title.is_empty() {
+                        headings.push(heading);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Code actions importing an unresolved symbol, offered in its diagnostic popover.
+#[derive(Debug, Clone)]
+pub struct ImportSuggestions {
+    buffer: Model<Buffer>,
+    actions: Vec<CodeAction>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagnosticPopover {
+    local_diagnostic: DiagnosticEntry<Anchor>,
+    primary_diagnostic: Option<DiagnosticEntry<Anchor>>,
+    /// The other diagnostics at the hovered point, less severe or broader than
+    /// `local_diagnostic`, shown below it.
+    stacked_diagnostics: Vec<DiagnosticEntry<Anchor>>,
+    /// The locations related to `local_diagnostic`, shown as links below its message.
+    related_information: Vec<RelatedInformation>,
+    hovered_point: Anchor,
+    trace_expanded: Rc<RefCell<bool>>,
+    message_expanded: Rc<RefCell<bool>>,
+    import_suggestions: Option<ImportSuggestions>,
+    /// The quick fix of an error, shown above its message when `hover_prominent_quickfix` is enabled.
+    quick_fix: Option<(Model<Buffer>, CodeAction)>,
+    /// Whether the language server has code actions for the diagnostic, which a button
+    /// opens the code actions menu for.
+    has_code_actions: bool,
+}
+
+/// A location the language server relates to a diagnostic, such as where a conflicting
+/// item was first defined.
+#[derive(Clone, Debug)]
+struct RelatedInformation {
+    message: String,
+    /// Where the location is, like `line 3` or `main.rs:3`.
+    location: String,
+    target: RelatedTarget,
+}
+
+/// Where clicking a [`RelatedInformation`] link goes.
+#[derive(Clone, Debug)]
+enum RelatedTarget {
+    /// A location in the hovered buffer.
+    Buffer(Anchor),
+    /// A location in another file, opened in the workspace.
+    File(PathBuf, Unclipped<PointUtf16>),
+}
+
+impl DiagnosticPopover {
+    /// [`hover_settings`] for the hovered diagnostic.
+    fn settings<'a>(&self, buffer: &MultiBufferSnapshot, cx: &'a AppContext) -> &'a EditorSettings {
+        hover_settings_at(buffer, self.local_diagnostic.range.start, cx)
+    }
+
+    pub fn render(
+        &self,
+        style: &EditorStyle,
+        max_size: Size<Pixels>,
+        buffer: &MultiBufferSnapshot,
+        cx: &mut ViewContext<Editor>,
+    ) -> AnyElement {
+        let (text, trace) = self.message_and_trace(buffer, cx);
+        let truncated_text = self
+            .truncated_message(&text, buffer, cx)
+            .map(|head| format!("{head}\n…"));
+        let message_expanded = *self.message_expanded.borrow();
+        let code = self
+            .code(buffer, cx)
+            .map(|code| SharedString::from(code.to_string()));
+        let code_url = self.code_url().map(SharedString::from);
+        let block_underlying_moves = self.settings(buffer, cx).hover_block_underlying_moves;
+
+        let diagnostic_colors = diagnostic_colors(self.local_diagnostic.diagnostic.severity, cx);
+
+        div()
+            .id("diagnostic")
+            .block()
+            .elevation_2_borderless(cx)
+            // Don't draw the background color if the theme
+            // allows transparent surfaces.
+            .when(window_is_transparent(cx), |this| {
+                this.bg(gpui::transparent_black())
+            })
+            .max_w(max_size.width)
+            .max_h(max_size.height)
+            .cursor(CursorStyle::PointingHand)
+            .tooltip(move |cx| Tooltip::for_action("Go To Diagnostic", &crate::GoToDiagnostic, cx))
+            // Prevent a mouse move on the popover from being propagated to the editor,
+            // because that would dismiss the popover.
+            .when(block_underlying_moves, |this| {
+                this.on_mouse_move(|_, cx| cx.stop_propagation())
+            })
+            // Prevent a mouse down on the popover from being propagated to the editor,
+            // because that would move the cursor.
+            .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
+            .on_click(cx.listener(|editor, _, cx| editor.go_to_diagnostic(&Default::default(), cx)))
+            .child(
+                div()
+                    .id("diagnostic-inner")
+                    .overflow_y_scroll()
+                    .px_2()
+                    .py_1()
+                    .bg(diagnostic_colors.background)
+                    .text_color(style.text.color)
+                    .border_1()
+                    .border_color(diagnostic_colors.border)
+                    .rounded_lg()
+                    .when_some(self.quick_fix.clone(), |this, (buffer, action)| {
+                        this.child(
+                            h_flex().mb_1().child(
+                                Button::new(
+                                    "diagnostic-quick-fix",
+                                    action.lsp_action.title.clone(),
+                                )
+                                .style(ButtonStyle::Filled)
+                                .icon(IconName::MagicWand)
+                                .icon_position(IconPosition::Start)
+                                .icon_size(IconSize::Small)
+                                .label_size(LabelSize::Small)
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Apply Quick Fix",
+                                        &crate::ApplyHoverQuickFix,
+                                        cx,
+                                    )
+                                })
+                                .on_click(cx.listener(
+                                    move |editor, _, cx| {
+                                        apply_hover_code_action(
+                                            editor,
+                                            buffer.clone(),
+                                            action.clone(),
+                                            cx,
+                                        );
+                                        cx.stop_propagation();
+                                    },
+                                )),
+                            ),
+                        )
+                    })
+                    .child(SharedString::from(match &truncated_text {
+                        Some(truncated_text) if !message_expanded => truncated_text.clone(),
+                        _ => text,
+                    }))
+                    .when_some(code, |this, code| {
+                        this.child(
+                            div()
+                                .id("diagnostic-code")
+                                .debug_selector(|| "diagnostic-code".into())
+                                .child(Label::new(code).size(LabelSize::Small).color(Color::Muted))
+                                // The code links to its documentation when the language server
+                                // provides it.
+                                .when_some(code_url, |this, url| {
+                                    this.text_decoration_1()
+                                        .tooltip({
+                                            let url = url.clone();
+                                            move |cx| Tooltip::text(url.clone(), cx)
+                                        })
+                                        .on_click(move |_, cx| {
+                                            cx.open_url(&url);
+                                            cx.stop_propagation();
+                                        })
+                                }),
+                        )
+                    })
+                    .when(truncated_text.is_some(), |this| {
+                        let toggle_message = Rc::clone(&self.message_expanded);
+                        this.child(
+                            div()
+                                .id("diagnostic-message-toggle")
+                                .mt_1()
+                                .child(
+                                    Label::new(if message_expanded {
+                                        "Show less"
+                                    } else {
+                                        "Show full message"
+                                    })
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                )
+                                .on_click(cx.listener(move |_, _, cx| {
+                                    let mut message_expanded = toggle_message.borrow_mut();
+                                    *message_expanded = !*message_expanded;
+                                    cx.stop_propagation();
+                                    cx.notify();
+                                })),
+                        )
+                    })
+                    .children(self.related_information.iter().enumerate().map(
+                        |(ix, related_information)| {
+                            let target = related_information.target.clone();
+                            div()
+                                .id(("diagnostic-related-information", ix))
+                                .mt_1()
+                                .text_decoration_1()
+                                .child(
+                                    Label::new(format!(
+                                        "{} ({})",
+                                        related_information.message, related_information.location
+                                    ))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Accent),
+                                )
+                                .on_click(cx.listener(move |editor, _, cx| {
+                                    jump_to_related_information(editor, target.clone(), cx);
+                                    cx.stop_propagation();
+                                }))
+                        },
+                    ))
+                    .when_some(self.import_suggestions.clone(), |this, suggestions| {
+                        this.children(suggestions.actions.into_iter().enumerate().map(
+                            |(ix, action)| {
+                                let buffer = suggestions.buffer.clone();
+                                div()
+                                    .id(("diagnostic-import", ix))
+                                    .mt_1()
+                                    .child(
+                                        Label::new(action.lsp_action.title.clone())
+                                            .size(LabelSize::Small)
+                                            .color(Color::Accent),
+                                    )
+                                    .on_click(cx.listener(move |editor, _, cx| {
+                                        apply_hover_code_action(
+                                            editor,
+                                            buffer.clone(),
+                                            action.clone(),
+                                            cx,
+                                        );
+                                        cx.stop_propagation();
+                                    }))
+                            },
+                        ))
+                    })
+                    .when(self.has_code_actions, |this| {
+                        let target = self.local_diagnostic.range.start;
+                        this.child(
+                            h_flex().mt_1().child(
+                                Button::new("diagnostic-code-actions", "Quick Fix…")
+                                    .style(ButtonStyle::Filled)
+                                    .icon(IconName::Bolt)
+                                    .icon_position(IconPosition::Start)
+                                    .icon_size(IconSize::Small)
+                                    .label_size(LabelSize::Small)
+                                    .on_click(cx.listener(move |editor, _, cx| {
+                                        show_diagnostic_code_actions(editor, target, cx);
+                                        cx.stop_propagation();
+                                    })),
+                            ),
+                        )
+                    })
+                    .when_some(trace, |this, trace| {
+                        let trace_expanded = *self.trace_expanded.borrow();
+                        let toggle_trace = Rc::clone(&self.trace_expanded);
+                        this.child(
+                            div()
+                                .id("diagnostic-trace-toggle")
+                                .mt_1()
+                                .child(
+                                    Label::new(if trace_expanded {
+                                        "Hide trace"
+                                    } else {
+                                        "Show trace"
+                                    })
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                )
+                                .on_click(cx.listener(move |_, _, cx| {
+                                    let mut trace_expanded = toggle_trace.borrow_mut();
+                                    *trace_expanded = !*trace_expanded;
+                                    cx.stop_propagation();
+                                    cx.notify();
+                                })),
+                        )
+                        .when(trace_expanded, |this| {
+                            this.child(
+                                div()
+                                    .mt_1()
+                                    .font_family(style.text.font_family.clone())
+                                    .text_color(cx.theme().colors().text_muted)
+                                    .child(SharedString::from(trace)),
+                            )
+                        })
+                    }),
+            )
+            .children(
+                self.stacked_diagnostics
+                    .iter()
+                    .zip(self.stacked_messages())
+                    .map(|(entry, message)| {
+                        let colors = diagnostic_colors(entry.diagnostic.severity, cx);
+                        div()
+                            .mt_1()
+                            .px_2()
+                            .py_1()
+                            .bg(colors.background)
+                            .text_color(style.text.color)
+                            .border_1()
+                            .border_color(colors.border)
+                            .rounded_lg()
+                            .child(SharedString::from(message))
+                    }),
+            )
+            .into_any_element()
+    }
+
+    /// The diagnostic message to show, and the stack trace at its end to collapse if
+    /// `hover_collapse_stack_traces` is enabled and one was detected.
+    fn message_and_trace(
+        &self,
+        buffer: &MultiBufferSnapshot,
+        cx: &AppContext,
+    ) -> (String, Option<String>) {
+        let message = &self.local_diagnostic.diagnostic.message;
+        let (message, trace) = if self.settings(buffer, cx).hover_collapse_stack_traces {
+            split_stack_trace(message)
+        } else {
+            (message.as_str(), None)
+        };
+        let text = diagnostic_text(&self.local_diagnostic.diagnostic, message);
+        (text, trace.map(ToString::to_string))
+    }
+
+    /// The messages of the diagnostics stacked below the main one.
+    fn stacked_messages(&self) -> Vec<String> {
+        self.stacked_diagnostics
+            .iter()
+            .map(|entry| diagnostic_text(&entry.diagnostic, &entry.diagnostic.message))
+            .collect()
+    }
+
+    /// The diagnostic's code, shown after its message when `diagnostic_hover_show_code` is
+    /// enabled or the code links to its documentation.
+    fn code(&self, buffer: &MultiBufferSnapshot, cx: &AppContext) -> Option<&str> {
+        let code = self
+            .local_diagnostic
+            .diagnostic
+            .code
+            .as_deref()
+            .filter(|code| !code.is_empty());
+        if self.code_url().is_some() {
+            return Some(code.unwrap_or("Documentation"));
+        }
+        code.filter(|_| self.settings(buffer, cx).diagnostic_hover_show_code)
+    }
+
+    /// The URL of the documentation of the diagnostic's code, which its code links to.
+    fn code_url(&self) -> Option<&str> {
+        self.local_diagnostic
+            .diagnostic
+            .code_description
+            .as_deref()
+            .filter(|url| !url.is_empty())
+    }
+
+    /// The first `diagnostic_hover_max_lines` lines of a diagnostic message that has more,
+    /// shown until the full message is expanded.
+    fn truncated_message<'a>(
+        &self,
+        message: &'a str,
+        buffer: &MultiBufferSnapshot,
+        cx: &AppContext,
+    ) -> Option<&'a str> {
+        let max_lines = self.settings(buffer, cx).diagnostic_hover_max_lines as usize;
+        if max_lines == 0 {
+            return None;
+        }
+        let message = message.trim_end();
+        let (end, _) = message.match_indices('\n').nth(max_lines - 1)?;
+        Some(&message[..end])
+    }
+
+    pub fn activation_info(&self) -> (usize, Anchor) {
+        let entry = self
+            .primary_diagnostic
+            .as_ref()
+            .unwrap_or(&self.local_diagnostic);
+
+        (entry.diagnostic.group_id, entry.range.start)
+    }
+}
+
+struct DiagnosticColors {
+    background: Hsla,
+    border: Hsla,
+}
+
+fn diagnostic_colors(severity: DiagnosticSeverity, cx: &AppContext) -> DiagnosticColors {
+    let status_colors = cx.theme().status();
+    match severity {
+        DiagnosticSeverity::ERROR => DiagnosticColors {
+            background: status_colors.error_background,
+            border: status_colors.error_border,
+        },
+        DiagnosticSeverity::WARNING => DiagnosticColors {
+            background: status_colors.warning_background,
+            border: status_colors.warning_border,
+        },
+        DiagnosticSeverity::INFORMATION => DiagnosticColors {
+            background: status_colors.info_background,
+            border: status_colors.info_border,
+        },
+        DiagnosticSeverity::HINT => DiagnosticColors {
+            background: status_colors.hint_background,
+            border: status_colors.hint_border,
+        },
+        _ => DiagnosticColors {
+            background: status_colors.ignored_background,
+            border: status_colors.ignored_border,
+        },
+    }
+}
+
+/// A diagnostic message, prefixed with the diagnostic's source if it has one.
+fn diagnostic_text(diagnostic: &language::Diagnostic, message: &str) -> String {
+    match diagnostic
+        .source
+        .as_deref()
+        .filter(|source| !source.is_empty())
+    {
+        Some(source) => format!("{source}: {message}"),
+        None => message.to_string(),
+    }
+}
+
+/// Opens the code actions menu at the hovered diagnostic.
+fn show_diagnostic_code_actions(editor: &mut Editor, target: Anchor, cx: &mut ViewContext<Editor>) {
+    hide_hover(editor, cx);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_anchor_ranges([target..target])
+    });
+    editor.toggle_code_actions(
+        &crate::ToggleCodeActions {
+            deployed_from_indicator: None,
+        },
+        cx,
+    );
+}
+
+/// Moves the cursor to a location related to the hovered diagnostic, opening the file of
+/// locations in other files in the workspace.
+fn jump_to_related_information(
+    editor: &mut Editor,
+    target: RelatedTarget,
+    cx: &mut ViewContext<Editor>,
+) {
+    hide_hover(editor, cx);
+    let (abs_path, position) = match target {
+        RelatedTarget::Buffer(target) => {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_anchor_ranges([target..target])
+            });
+            return;
+        }
+        RelatedTarget::File(abs_path, position) => (abs_path, position),
+    };
+    let Some(workspace) = editor.workspace() else {
+        return;
+    };
+    let open_task = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(abs_path, true, cx)
+    });
+    cx.spawn(|_, mut cx| async move {
+        if let Some(editor) = open_task.await?.downcast::<Editor>() {
+            editor.update(&mut cx, |editor, cx| {
+                let buffer = editor.buffer().read(cx).snapshot(cx);
+                let point = buffer.clip_point_utf16(position, Bias::Left);
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Whether a diagnostic reports a symbol that couldn't be resolved, such as a missing import.
+fn is_unresolved_symbol(diagnostic: &language::Diagnostic) -> bool {
+    const UNRESOLVED_CODES: &[&str] = &[
+        "E0412",
+        "E0422",
+        "E0425",
+        "E0433",
+        "E0531",
+        "unresolved-import",
+        "2304",
+        "2552",
+        "reportUndefinedVariable",
+        "undefined-variable",
+    ];
+    if diagnostic
+        .code
+        .as_deref()
+        .map_or(false, |code| UNRESOLVED_CODES.contains(&code))
+    {
+        return true;
+    }
+    let message = diagnostic.message.to_lowercase();
+    message.starts_with("cannot find")
+        || message.starts_with("unresolved")
+        || message.contains("is not defined")
+}
+
+/// The quick fix to show prominently among a diagnostic's code actions, preferring the one the
+/// language server marks as preferred.
+fn prominent_quick_fix(actions: &[CodeAction]) -> Option<CodeAction> {
+    let mut quick_fixes = actions.iter().filter(|action| {
+        action.lsp_action.kind.as_ref().map_or(false, |kind| {
+            kind.as_str()
+                .starts_with(lsp::CodeActionKind::QUICKFIX.as_str())
+        })
+    });
+    quick_fixes
+        .clone()
+        .find(|action| action.lsp_action.is_preferred == Some(true))
+        .or_else(|| quick_fixes.next())
+        .cloned()
+}
+
+/// Bindable action which applies the quick fix shown in the diagnostic popover.
+pub fn apply_hover_quick_fix(
+    editor: &mut Editor,
+    _: &ApplyHoverQuickFix,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some((buffer, action)) = editor
+        .hover_state
+        .diagnostic_popover
+        .as_ref()
+        .and_then(|popover| popover.quick_fix.clone())
+    else {
+        cx.propagate();
+        return;
+    };
+    apply_hover_code_action(editor, buffer, action, cx);
+}
+
+fn is_import_action(action: &CodeAction) -> bool {
+    let is_quickfix = action.lsp_action.kind.as_ref().map_or(true, |kind| {
+        kind.as_str()
+            .starts_with(lsp::CodeActionKind::QUICKFIX.as_str())
+    });
+    is_quickfix && action.lsp_action.title.to_lowercase().contains("import")
+}
+
+/// Applies a code action suggested in a diagnostic popover and dismisses the popover.
+fn apply_hover_code_action(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    action: CodeAction,
+    cx: &mut ViewContext<Editor>,
+) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let apply_action = project.update(cx, |project, cx| {
+        project.apply_code_action(buffer, action, true, cx)
+    });
+    hide_hover(editor, cx);
+    cx.spawn(|_, _| async move {
+        apply_action.await?;
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Splits a diagnostic message into its head and a trailing stack trace, which is
+/// detected as a run of at least two frame-like lines reaching the end of the message.
+fn split_stack_trace(message: &str) -> (&str, Option<&str>) {
+    const MIN_TRACE_LINES: usize = 2;
+
+    fn is_frame_number(frame: &str) -> bool {
+        !frame.is_empty() && frame.chars().all(|c| c.is_ascii_digit())
+    }
+
+    fn is_trace_line(line: &str) -> bool {
+        let line = line.trim_start();
+        line.starts_with("at ")
+            || line.starts_with("File \"")
+            || line.starts_with("Traceback (most recent call last)")
+            || line.starts_with("stack backtrace:")
+            || line
+                .split_once(':')
+                .map_or(false, |(frame, _)| is_frame_number(frame))
+            || line
+                .strip_prefix('#')
+                .and_then(|line| line.split_once(' '))
+                .map_or(false, |(frame, _)| is_frame_number(frame))
+    }
+
+    let mut trace_start = None;
+    let mut trace_lines = 0;
+    let mut offset = 0;
+    for line in message.split_inclusive('\n') {
+        if is_trace_line(line) {
+            trace_start.get_or_insert(offset);
+            trace_lines += 1;
+        } else if !line.trim().is_empty() && !line.starts_with(char::is_whitespace) {
+            // Unindented prose after frame-like lines means they weren't a trailing trace.
+            trace_start = None;
+            trace_lines = 0;
+        }
+        offset += line.len();
+    }
+
+    match trace_start {
+        Some(start) if start > 0 && trace_lines >= MIN_TRACE_LINES => {
+            let head = message[..start].trim_end();
+            if head.is_empty() {
+                (message, None)
+            } else {
+                (head, Some(message[start..].trim_end()))
+            }
+        }
+        _ => (message, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{Cancel, ConfirmCompletion},
+        editor_tests::{handle_completion_request, init_test},
+        hover_links::update_inlay_link_and_hover_points,
+        hover_status_item::HoverStatusItem,
+        inlay_hint_cache::tests::{cached_hint_labels, visible_hint_labels},
+        test::editor_lsp_test_context::EditorLspTestContext,
+        BufferOffset, ContextMenu, InlayId, MultiBufferOffset, PointForPosition, RunnableTasks,
+        CODE_ACTIONS_DEBOUNCE_TIMEOUT,
+    };
+    use collections::{BTreeSet, HashMap};
+    use gpui::TextRun;
+    use indoc::indoc;
+    use language::{
+        language_settings::{InlayHintSettings, LanguageSettingsContent},
+        Capability, Diagnostic, DiagnosticRelatedLocation, DiagnosticSet, FakeLspAdapter,
+        LanguageConfig, LanguageMatcher, Point,
+    };
+    use lsp::LanguageServerId;
+    use multi_buffer::{ExcerptRange, MultiBuffer, ToPoint};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use smol::stream::StreamExt;
+    use std::sync::atomic;
+    use std::sync::atomic::AtomicUsize;
+    use text::Bias;
+    use workspace::StatusItemView;
+
+    impl InfoPopover {
+        fn get_rendered_text(&self, cx: &gpui::AppContext) -> String {
+            let mut rendered_text = String::new();
+            if let Some(parsed_content) = self.parsed_content.clone() {
+                let markdown = parsed_content.read(cx);
+                let text = markdown.parsed_markdown().source().to_string();
+                let data = markdown.parsed_markdown().events();
+                let slice = data;
+
+                for (range, event) in slice.iter() {
+                    if [MarkdownEvent::Text, MarkdownEvent::Code].contains(event) {
+                        rendered_text.push_str(&text[range.clone()])
+                    }
+                }
+            }
+            rendered_text
+        }
+
+        /// The rendered lines with their styled runs marked by `«»`, followed by a line per
+        /// marked run describing how it differs from the base text style, naming syntax
+        /// highlights after the theme's highlight they're colored with.
+        fn get_rendered_snapshot(&self, cx: &gpui::AppContext) -> String {
+            let Some(parsed_content) = self.parsed_content.clone() else {
+                return String::new();
+            };
+            let markdown = parsed_content.read(cx);
+            let style = markdown.style();
+            let base = &style.base_text_style;
+            let describe = |run: &TextRun| {
+                let mut labels = Vec::new();
+                if run.font.weight != base.font_weight {
+                    labels.push("bold".to_string());
+                }
+                if run.font.style != base.font_style {
+                    labels.push("italic".to_string());
+                }
+                if run.underline.is_some() {
+                    labels.push("underline".to_string());
+                }
+                if run.strikethrough.is_some() {
+                    labels.push("strikethrough".to_string());
+                }
+                if run.background_color.is_some() {
+                    labels.push("background".to_string());
+                }
+                if let Some((name, _)) = style
+                    .syntax
+                    .highlights
+                    .iter()
+                    .find(|(_, highlight)| highlight.color == Some(run.color))
+                {
+                    labels.push(name.clone());
+                } else if run.color != base.color {
+                    labels.push("color".to_string());
+                }
+                labels.join(", ")
+            };
+
+            let mut lines = Vec::new();
+            let mut marked_runs = Vec::new();
+            for (text, runs) in markdown.rendered_lines() {
+                let mut line = String::new();
+                let mut offset = 0;
+                for (label, runs) in &runs.iter().group_by(|run| describe(run)) {
+                    let len = runs.map(|run| run.len).sum::<usize>();
+                    let run_text = &text[offset..offset + len];
+                    offset += len;
+                    if label.is_empty() {
+                        line.push_str(run_text);
+                    } else {
+                        line.push_str(&format!("«{run_text}»"));
+                        marked_runs.push(format!("«{}»: {label}", run_text.trim_end()));
+                    }
+                }
+                line.push_str(&text[offset..]);
+                lines.push(line.trim_end().to_string());
+            }
+            lines.extend(marked_runs);
+            lines.join("\n")
+        }
+    }
+
+    #[gpui::test]
+    async fn test_mouse_hover_info_popover_with_autocomplete_popover(
+        cx: &mut gpui::TestAppContext,
+    ) {
+        init_test(cx, |_| {});
+        const HOVER_DELAY_MILLIS: u64 = 350;
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+                    resolve_provider: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let counter = Arc::new(AtomicUsize::new(0));
+        // Basic hover delays and then pops without moving the mouse
+        cx.set_state(indoc! {"
+                oneˇ
+                two
+                three
+                fn test() { println!(); }
+            "});
+
+        //prompt autocompletion menu
+        cx.simulate_keystroke(".");
+        handle_completion_request(
+            &mut cx,
+            indoc! {"
+                        one.|<>
+                        two
+                        three
+                    "},
+            vec!["first_completion", "second_completion"],
+            counter.clone(),
+        )
+        .await;
+        cx.condition(|editor, _| editor.context_menu_visible()) // wait until completion menu is visible
+            .await;
+        assert_eq!(counter.load(atomic::Ordering::Acquire), 1); // 1 completion request
+
+        let hover_point = cx.display_point(indoc! {"
+                one.
+                two
+                three
+                fn test() { printˇln!(); }
+            "});
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // After delay, hover should be visible.
+        let symbol_range = cx.lsp_range(indoc! {"
+                one.
+                two
+                three
+                fn test() { «println!»(); }
+            "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        requests.next().await;
+
+        cx.editor(|editor, cx| {
+            assert!(editor.hover_state.visible());
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                1,
+                "Expected exactly one hover but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+            assert_eq!(rendered_text, "some basic docs".to_string())
+        });
+
+        // check that the completion menu is still visible and that there still has only been 1 completion request
+        cx.editor(|editor, _| assert!(editor.context_menu_visible()));
+        assert_eq!(counter.load(atomic::Ordering::Acquire), 1);
+
+        //apply a completion and check it was successfully applied
+        let _apply_additional_edits = cx.update_editor(|editor, cx| {
+            editor.context_menu_next(&Default::default(), cx);
+            editor
+                .confirm_completion(&ConfirmCompletion::default(), cx)
+                .unwrap()
+        });
+        cx.assert_editor_state(indoc! {"
+            one.second_completionˇ
+            two
+            three
+            fn test() { println!(); }
+        "});
+
+        // check that the completion menu is no longer visible and that there still has only been 1 completion request
+        cx.editor(|editor, _| assert!(!editor.context_menu_visible()));
+        assert_eq!(counter.load(atomic::Ordering::Acquire), 1);
+
+        //verify the information popover is still visible and unchanged
+        cx.editor(|editor, cx| {
+            assert!(editor.hover_state.visible());
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                1,
+                "Expected exactly one hover but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+
+            assert_eq!(rendered_text, "some basic docs".to_string())
+        });
+
+        // Mouse moved with no hover response dismisses
+        let hover_point = cx.display_point(indoc! {"
+                one.second_completionˇ
+                two
+                three
+                fn teˇst() { println!(); }
+            "});
+        let mut request = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        request.next().await;
+
+        // verify that the information popover is no longer visible
+        cx.editor(|editor, _| {
+            assert!(!editor.hover_state.visible());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_mouse_hover_info_popover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        // Basic hover delays and then pops without moving the mouse
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let hover_point = cx.display_point(indoc! {"
+            fn test() { printˇln!(); }
+        "});
+
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // After delay, hover should be visible.
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn test() { «println!»(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        requests.next().await;
+
+        cx.editor(|editor, cx| {
+            assert!(editor.hover_state.visible());
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                1,
+                "Expected exactly one hover but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+
+            assert_eq!(rendered_text, "some basic docs".to_string())
+        });
+
+        // Mouse moved with no hover response dismisses
+        let hover_point = cx.display_point(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+        let mut request = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        request.next().await;
+        cx.editor(|editor, _| {
+            assert!(!editor.hover_state.visible());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hovered_symbol_range(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.editor(|editor, _| assert_eq!(editor.hovered_symbol_range(), None));
+
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let range = editor.hovered_symbol_range().unwrap();
+            assert_eq!(
+                range.start.to_point(&snapshot)..range.end.to_point(&snapshot),
+                Point::new(0, 3)..Point::new(0, 7)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_enclosing_modifier(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                experimental: Some(json!({ "hoverRange": true })),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_enclosing_modifier = Some(HoverEnclosingModifier::Alt);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn test() { let x = foo.bar.baˇz; }
+        "});
+        let hover_point = cx.display_point(indoc! {"
+            fn test() { let x = foo.bar.baˇz; }
+        "});
+        let foo_range = cx.lsp_range(indoc! {"
+            fn test() { let x = «foo».bar.baz; }
+        "});
+        let baz_range = cx.lsp_range(indoc! {"
+            fn test() { let x = foo.bar.«baz»; }
+        "});
+        let enclosing_range = cx.lsp_range(indoc! {"
+            fn test() { let x = «foo.bar.baz»; }
+        "});
+
+        // Hovers are sent as positions, except for rust-analyzer's range hovers.
+        enum HoverPositionOrRange {}
+        impl lsp::request::Request for HoverPositionOrRange {
+            type Params = serde_json::Value;
+            type Result = Option<lsp::Hover>;
+            const METHOD: &'static str = "textDocument/hover";
+        }
+        let mut requests =
+            cx.handle_request::<HoverPositionOrRange, _, _>(move |_, params, _| async move {
+                let position = &params["position"];
+                let (value, range) = if position.get("start").is_some() {
+                    let range: lsp::Range = serde_json::from_value(position.clone()).unwrap();
+                    assert_eq!(range, enclosing_range);
+                    ("expression docs", enclosing_range)
+                } else {
+                    let position: lsp::Position = serde_json::from_value(position.clone()).unwrap();
+                    if position == lsp::Position::new(0, 30) {
+                        ("baz docs", baz_range)
+                    } else if position == lsp::Position::new(0, 20) {
+                        ("foo docs", foo_range)
+                    } else {
+                        return Ok(None);
+                    }
+                };
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: value.to_string(),
+                    }),
+                    range: Some(range),
+                }))
+            });
+        let hover_with = |modifiers: Modifiers, cx: &mut EditorLspTestContext| {
+            cx.update_editor(|editor, cx| {
+                let snapshot = editor.snapshot(cx);
+                let anchor = snapshot
+                    .buffer_snapshot
+                    .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+                hover_at_with_modifiers(editor, anchor, &modifiers, cx)
+            });
+            cx.background_executor
+                .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        };
+        let rendered_text = |cx: &mut EditorLspTestContext| {
+            cx.editor(|editor, cx| {
+                editor
+                    .hover_state
+                    .info_popovers
+                    .first()
+                    .unwrap()
+                    .get_rendered_text(cx)
+            })
+        };
+
+        // Without the modifier, the hovered symbol itself is requested.
+        hover_with(Modifiers::none(), &mut cx);
+        requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(rendered_text(&mut cx), "baz docs");
+
+        // Holding the modifier requests the range of the enclosing field expression, rather
+        // than its start, which would describe `foo`.
+        hover_with(
+            Modifiers {
+                alt: true,
+                ..Default::default()
+            },
+            &mut cx,
+        );
+        requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(rendered_text(&mut cx), "expression docs");
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            fn test() { let x = «foo.bar.baz»; }
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_hover_file_url_opens_in_workspace(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() {
+                let x = fˇoo();
+            }
+            fn foo() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn main() {
+                let x = «foo»();
+            }
+            fn foo() {}
+        "});
+        let url = "file:///root/dir/file.rs#L4C4";
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: format!("Calls [`foo`]({url})"),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        let markdown = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap()
+        });
+        cx.update(|cx| markdown.update(cx, |markdown, cx| markdown.open_url(url.into(), cx)));
+        cx.run_until_parked();
+
+        cx.assert_editor_state(indoc! {"
+            fn main() {
+                let x = foo();
+            }
+            fn ˇfoo() {}
+        "});
+        assert_eq!(
+            file_url_location("file:///root/dir/file.rs#12:5"),
+            Some((PathBuf::from("/root/dir/file.rs"), Some(Point::new(11, 4))))
+        );
+        assert_eq!(file_url_location("https://docs.rs/foo#L12"), None);
+    }
+
+    #[gpui::test]
+    async fn test_hover_relative_link_opens_in_workspace(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() {
+                let x = fˇoo();
+            }
+            fn foo() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn main() {
+                let x = «foo»();
+            }
+            fn foo() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Calls [`foo`](dir/file.rs#L4C4)".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        let markdown = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap()
+        });
+        let open_url = |url: &'static str, cx: &mut EditorLspTestContext| {
+            cx.update(|cx| markdown.update(cx, |markdown, cx| markdown.open_url(url.into(), cx)));
+            cx.run_until_parked();
+        };
+        let item_count = |cx: &mut EditorLspTestContext| {
+            cx.update_workspace(|workspace, cx| workspace.items(cx).count())
+        };
+
+        // The link opens the file in the worktree rather than in the browser.
+        open_url("dir/file.rs#L4C4", &mut cx);
+        assert_eq!(cx.opened_url(), None);
+        cx.assert_editor_state(indoc! {"
+            fn main() {
+                let x = foo();
+            }
+            fn ˇfoo() {}
+        "});
+
+        // Links to a fragment of the docs and to directories aren't opened in the worktree.
+        let items = item_count(&mut cx);
+        open_url("#examples", &mut cx);
+        open_url("dir", &mut cx);
+        assert_eq!(item_count(&mut cx), items);
+        cx.assert_editor_state(indoc! {"
+            fn main() {
+                let x = foo();
+            }
+            fn ˇfoo() {}
+        "});
+
+        // Web links are opened in the browser.
+        open_url("https://zed.dev/docs", &mut cx);
+        assert_eq!(cx.opened_url(), Some("https://zed.dev/docs".into()));
+        assert_eq!(item_count(&mut cx), items);
+    }
+
+    #[gpui::test]
+    async fn test_hover_link_click_dispatches_action(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let clicked_urls = Rc::new(RefCell::new(Vec::new()));
+        let subscription = cx.update_editor(|editor, _| {
+            let clicked_urls = clicked_urls.clone();
+            editor.register_action(move |action: &OpenHoverLink, _| {
+                clicked_urls.borrow_mut().push(action.url.clone());
+            })
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "See the [docs](https://zed.dev/docs)".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        let markdown = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap()
+        });
+        let url = SharedString::from("https://zed.dev/docs");
+        cx.update(|cx| markdown.update(cx, |markdown, cx| markdown.open_url(url.clone(), cx)));
+        assert_eq!(*clicked_urls.borrow(), ["https://zed.dev/docs"]);
+        assert_eq!(cx.opened_url(), None);
+
+        // Without a listener intercepting it, the editor opens the link.
+        drop(subscription);
+        cx.update_editor(|_, cx| cx.notify());
+        cx.run_until_parked();
+        cx.update(|cx| markdown.update(cx, |markdown, cx| markdown.open_url(url, cx)));
+        assert_eq!(clicked_urls.borrow().len(), 1);
+        assert_eq!(cx.opened_url(), Some("https://zed.dev/docs".into()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_link_clicked_event(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let clicked_urls = Rc::new(RefCell::new(Vec::new()));
+        let editor = cx.editor.clone();
+        cx.update(|cx| {
+            let clicked_urls = clicked_urls.clone();
+            cx.subscribe(&editor, move |_, event: &EditorEvent, _| {
+                if let EditorEvent::HoverLinkClicked(url) = event {
+                    clicked_urls.borrow_mut().push(url.clone());
+                }
+            })
+            .detach();
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "See the [docs](https://zed.dev/docs)".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        let markdown = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap()
+        });
+        let url = SharedString::from("https://zed.dev/docs");
+        cx.update(|cx| markdown.update(cx, |markdown, cx| markdown.open_url(url, cx)));
+        cx.run_until_parked();
+        assert_eq!(*clicked_urls.borrow(), ["https://zed.dev/docs"]);
+        assert_eq!(cx.opened_url(), Some("https://zed.dev/docs".into()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_content_selection_copy(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Returns the answer".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        let markdown = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap()
+        });
+        let (start, end) = cx.update(|cx| {
+            let markdown = markdown.read(cx);
+            let offset = gpui::point(px(0.), px(2.));
+            (
+                markdown.position_for_source_index(8).unwrap() + offset,
+                markdown.position_for_source_index(11).unwrap() + offset,
+            )
+        });
+        cx.simulate_mouse_down(start, MouseButton::Left, Modifiers::none());
+        cx.simulate_mouse_move(end, MouseButton::Left, Modifiers::none());
+        cx.simulate_mouse_up(end, MouseButton::Left, Modifiers::none());
+        cx.dispatch_action(markdown::Copy);
+
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().to_owned()),
+            Some("the".to_string())
+        );
+        cx.editor(|editor, _| assert!(editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_highlight_kept_for_same_range(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        let hover_highlights = |cx: &mut EditorLspTestContext| {
+            cx.editor(|editor, _| {
+                editor
+                    .background_highlights
+                    .get(&TypeId::of::<HoverState>())
+                    .map(|(_, ranges)| ranges.clone())
+                    .unwrap()
+            })
+        };
+
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        let first_highlights = hover_highlights(&mut cx);
+
+        // Hovering the same symbol again leaves the existing highlight in place.
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(Arc::ptr_eq(&first_highlights, &hover_highlights(&mut cx)));
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            fn «test»() { println!(); }
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_hover_injected_expression(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let html_language = Language::new(
+            LanguageConfig {
+                name: "HTML".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["html".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_html::language()),
+        )
+        .with_injection_query(
+            r#"
+            (script_element
+                (raw_text) @content
+                (#set! "language" "javascript"))
+            "#,
+        )
+        .unwrap();
+        let javascript_language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "JavaScript".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_typescript::language_tsx()),
+        ));
+
+        let mut cx = EditorLspTestContext::new(
+            html_language,
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.language_registry().add(javascript_language);
+
+        cx.set_state(indoc! {"
+            <script>
+                const label = `${toˇtal} items`;
+            </script>
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            <script>
+                const label = `${«total»} items`;
+            </script>
+        "});
+        // The server only knows about the interpolated expression from its start.
+        let request_count = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, params, _| {
+                request_count.fetch_add(1, atomic::Ordering::Release);
+                async move {
+                    if params.text_document_position_params.position != symbol_range.start {
+                        return Ok(None);
+                    }
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "const total: number".to_string(),
+                        }),
+                        range: Some(symbol_range),
+                    }))
+                }
+            }
+        });
+
+        // The buffer's server only serves HTML, so the injected JavaScript isn't asked about.
+        cx.dispatch_action(Hover);
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 1);
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+
+        // Once it serves JavaScript too, it's asked about the embedded expression.
+        cx.language_registry()
+            .register_fake_lsp_adapter("JavaScript", FakeLspAdapter::default());
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 3);
+
+        cx.editor(|editor, cx| {
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+            assert_eq!(rendered_text, "const total: number".to_string());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_fold_generics(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_fold_generics = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let signature = indoc! {"
+            pub fn convert<T: Into<String> + Clone, U: Default>(value: T) -> U
+            where
+                U: From<T>,
+        "};
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: format!("```rust\ncrate::convert\n```\n\n```rust\n{signature}```"),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        let (markdown, generics_fold) = cx.editor(|editor, cx| {
+            let info_popover = &editor.hover_state.info_popovers[0];
+            assert_eq!(
+                info_popover.get_rendered_text(cx),
+                "crate::convert\npub fn convert<...>(value: T) -> U\nwhere ...\n"
+            );
+            (
+                info_popover.parsed_content.clone().unwrap(),
+                info_popover.generics_fold.clone().unwrap(),
+            )
+        });
+
+        cx.update(|cx| generics_fold.toggle(&markdown, cx));
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                format!("crate::convert\n{signature}")
+            );
+        });
+
+        let rust = cx
+            .language_registry()
+            .language_for_name("Rust")
+            .await
+            .unwrap();
+        assert_eq!(
+            fold_generics("fn call<F: Fn() -> T, T>(f: F) -> T", &rust),
+            Some("fn call<...>(f: F) -> T".to_string())
+        );
+        assert_eq!(fold_generics("fn plain(value: Vec<u8>)", &rust), None);
+        assert_eq!(
+            fold_generics("fn first(values: Vec<Option<u8>>) -> Option<u8>", &rust),
+            None
+        );
+        assert_eq!(
+            fold_generics("impl<T: Clone> From<Vec<T>> for Wrapper<T>", &rust),
+            Some("impl<...> From<Vec<T>> for Wrapper<T>".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover_preload_code_languages(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_preload_code_languages = Some(true);
+                });
+            });
+        });
+        let language_registry = cx.language_registry();
+        language_registry.register_test_language(LanguageConfig {
+            name: "Lazy".into(),
+            ..Default::default()
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "```rust\nfn test()\n```\n\n```lazy\nlazy code\n```".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        // The popover is rendered again once the language that wasn't loaded has loaded.
+        let pending_languages = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .pending_languages
+                .clone()
+                .unwrap()
+        });
+        cx.run_until_parked();
+        assert_eq!(pending_languages.now_or_never(), Some(()));
+        assert!(language_registry
+            .language_for_name("Lazy")
+            .now_or_never()
+            .is_some_and(|language| language.is_ok()));
+
+        // Languages that are already loaded aren't waited for.
+        assert!(
+            loading_code_block_languages("```rust\nfn test()\n```", &language_registry).is_empty()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_keyboard_hover_info_popover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        // Hover with keyboard has no delay
+        cx.set_state(indoc! {"
+            fˇn test() { println!(); }
+        "});
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() { println!(); }
+        "});
+
+        cx.editor(|editor, _cx| {
+            assert!(!editor.hover_state.visible());
+
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                0,
+                "Expected no hovers but got but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+        });
+
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some other basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+
+        requests.next().await;
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                1,
+                "Expected exactly one hover but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+
+            assert_eq!(rendered_text, "some other basic docs".to_string())
+        });
+    }
+
+    #[gpui::test]
+    async fn test_keyboard_hover_while_typing(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_allow_while_typing = Some(false);
+                    settings.hover_popover_delay = Some(1000);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "some basic docs".to_string(),
+                }),
+                range: None,
+            }))
+        });
+        cx.update_buffer(|buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+
+        // Typing lasts for the user's hover delay rather than the default one.
+        cx.executor()
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.dispatch_action(Hover);
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+
+        cx.executor().advance_clock(Duration::from_millis(1000));
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+    }
+
+    #[gpui::test]
+    async fn test_hover_scroll_to_top_and_bottom(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fˇn test() { println!(); }
+        "});
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() { println!(); }
+        "});
+
+        let long_docs = (0..100).map(|ix| format!("line {ix}")).join("\n\n");
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| {
+            let long_docs = long_docs.clone();
+            async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: long_docs,
+                    }),
+                    range: Some(symbol_range),
+                }))
+            }
+        })
+        .next()
+        .await;
+
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            assert!(editor.key_context(cx).contains("showing_keyboard_hover"));
+        });
+
+        // Scrolling to the bottom scrolls by the whole overflow of the content, which is the
+        // extent of the popover's children and its vertical padding beyond its height.
+        let padding = cx.update(|cx| rems(0.5).to_pixels(cx.rem_size()));
+        cx.dispatch_action(HoverScrollToBottom);
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let scroll_handle = &editor.hover_state.info_popovers[0].scroll_handle;
+            let child_bounds = (0..)
+                .map_while(|ix| scroll_handle.bounds_for_item(ix))
+                .collect::<Vec<_>>();
+            let content_top = child_bounds
+                .iter()
+                .map(|bounds| bounds.top())
+                .min()
+                .unwrap();
+            let content_bottom = child_bounds
+                .iter()
+                .map(|bounds| bounds.bottom())
+                .max()
+                .unwrap();
+            let overflow =
+                content_bottom - content_top + padding * 2. - scroll_handle.bounds().size.height;
+            assert!(overflow > Pixels::ZERO);
+            assert_eq!(scroll_handle.offset().y, -overflow);
+        });
+
+        cx.dispatch_action(HoverScrollToTop);
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let offset = editor.hover_state.info_popovers[0].scroll_handle.offset();
+            assert_eq!(offset.y, Pixels::ZERO);
+        });
+
+        // Popovers shown by the mouse leave the keys to the editor.
+        cx.update_editor(|editor, cx| {
+            hide_hover(editor, cx);
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot.buffer_snapshot.anchor_before(4);
+            hover_at(editor, Some(anchor), cx);
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            assert!(editor.hover_state.visible());
+            let key_context = editor.key_context(cx);
+            assert!(key_context.contains("showing_hover"));
+            assert!(!key_context.contains("showing_keyboard_hover"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_toggle_hover_popover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let hover_point = cx.display_point(indoc! {"
+            fn test() { printˇln!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn test() { «println!»(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+
+        // Mouse hovers are ignored once toggled off
+        cx.dispatch_action(ToggleHoverPopover);
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.run_until_parked();
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // The keyboard hover action still shows the popover
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // Toggling back on restores mouse hovers and clears the override
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        cx.dispatch_action(ToggleHoverPopover);
+        cx.update(|cx| assert!(cx.global::<HoverPopoverOverride>().0.is_none()));
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_mouse_disabled(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_mouse_enabled = Some(false);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let hover_point = cx.display_point(indoc! {"
+            fn test() { printˇln!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn test() { «println!»(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.run_until_parked();
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // Moving the mouse away doesn't hide the popover the action showed either
+        cx.update_editor(|editor, cx| hover_at(editor, None, cx));
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_settings_per_worktree(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/a",
+            json!({
+                ".zed": { "settings.json": r#"{ "hover_popover_delay": 100 }"# },
+                "a.rs": "fn a() {}",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/b",
+            json!({
+                ".zed": { "settings.json": r#"{ "hover_popover_delay": 900 }"# },
+                "b.rs": "fn b() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/a".as_ref(), "/b".as_ref()], cx).await;
+        cx.executor().run_until_parked();
+
+        let buffer_a = project
+            .update(cx, |project, cx| project.open_local_buffer("/a/a.rs", cx))
+            .await
+            .unwrap();
+        let buffer_b = project
+            .update(cx, |project, cx| project.open_local_buffer("/b/b.rs", cx))
+            .await
+            .unwrap();
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(0, Capability::ReadWrite);
+            for buffer in [buffer_a, buffer_b] {
+                multibuffer.push_excerpts(
+                    buffer,
+                    [ExcerptRange {
+                        context: Point::new(0, 0)..Point::new(0, 9),
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+            multibuffer
+        });
+        let editor = cx.add_window(|cx| {
+            Editor::for_multibuffer(multibuffer, Some(project.clone()), false, cx)
+        });
+
+        _ = editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let anchor_a = snapshot.anchor_before(Point::new(0, 3));
+            let anchor_b = snapshot.anchor_before(Point::new(1, 3));
+            assert_eq!(
+                hover_settings(editor, anchor_a, cx).hover_popover_delay,
+                100
+            );
+            assert_eq!(
+                hover_settings(editor, anchor_b, cx).hover_popover_delay,
+                900
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_follows_worktree_settings(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let fs = cx.update_workspace(|workspace, _| workspace.app_state().fs.clone());
+        fs.as_fake()
+            .insert_tree(
+                "/root/.zed",
+                json!({ "settings.json": r#"{ "hover_code_line_numbers": true }"# }),
+            )
+            .await;
+        cx.run_until_parked();
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "```rust\nfn test()\n```".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        // The popover is styled by the worktree's settings rather than the user's.
+        cx.editor(|editor, cx| {
+            assert!(!EditorSettings::get_global(cx).hover_code_line_numbers);
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            assert!(markdown.style().code_block_line_numbers);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_test_status(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_test_status = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest_a() {}
+        "});
+        cx.run_until_parked();
+
+        let template = TaskTemplate {
+            label: "cargo test".to_string(),
+            command: "cargo".to_string(),
+            ..Default::default()
+        };
+        let tasks = RunnableTasks {
+            templates: vec![(TaskSourceKind::UserInput, template.clone())],
+            offset: MultiBufferOffset(0),
+            column: 0,
+            extra_variables: HashMap::default(),
+            context_range: BufferOffset(0)..BufferOffset(14),
+        };
+        let (project, buffer) = cx.update_editor(|editor, cx| {
+            (
+                editor.project.clone().unwrap(),
+                editor.buffer().read(cx).as_singleton().unwrap(),
+            )
+        });
+        let task_context = cx
+            .update(|cx| tasks.task_context(&project, &buffer, 0, cx))
+            .await
+            .unwrap();
+        let resolved_task = template
+            .resolve_task(&TaskSourceKind::UserInput.to_id_base(), &task_context)
+            .unwrap();
+        cx.update(|cx| {
+            project
+                .read(cx)
+                .task_inventory()
+                .update(cx, |inventory, _| {
+                    inventory.task_finished(
+                        resolved_task.id.clone(),
+                        TaskRunResult {
+                            success: false,
+                            duration: Duration::from_millis(1500),
+                        },
+                    );
+                });
+        });
+        cx.update_editor(|editor, cx| {
+            let buffer_id = buffer.read(cx).remote_id();
+            editor.insert_tasks((buffer_id, 0), tasks);
+        });
+
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Checks that A works".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+
+        // The last run is shown above the language server's hover, not instead of it.
+        cx.editor(|editor, cx| {
+            let info_popovers = &editor.hover_state.info_popovers;
+            assert_eq!(info_popovers.len(), 2);
+            assert_eq!(
+                info_popovers[0]
+                    .test_task
+                    .as_ref()
+                    .map(|(_, task)| task.id.clone()),
+                Some(resolved_task.id.clone())
+            );
+            assert_eq!(
+                info_popovers[0].get_rendered_text(cx),
+                "✗ Test failed in 1.50s"
+            );
+            assert!(info_popovers[1].test_task.is_none());
+            assert_eq!(
+                info_popovers[1].get_rendered_text(cx),
+                "Checks that A works"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_run_doctests(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        // Stands in for the Rust language's context provider, which names the package of the
+        // file according to cargo.
+        struct PackageContextProvider;
+        impl language::ContextProvider for PackageContextProvider {
+            fn build_context(
+                &self,
+                _: &TaskVariables,
+                _: &Location,
+                _: &mut AppContext,
+            ) -> anyhow::Result<TaskVariables> {
+                Ok(TaskVariables::from_iter([(
+                    VariableName::Custom("RUST_PACKAGE".into()),
+                    "my_crate".to_string(),
+                )]))
+            }
+        }
+        let language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::language()),
+        )
+        .with_context_provider(Some(Arc::new(PackageContextProvider)));
+        let mut cx = EditorLspTestContext::new(
+            language,
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_run_doctests = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: indoc! {"
+                            ```rust
+                            fn test()
+                            ```
+
+                            ---
+
+                            Does nothing.
+
+                            ```
+                            assert_eq!(test(), ());
+                            ```
+                        "}
+                        .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        let doctest = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .doctest
+                .clone()
+                .expect("documentation with an example should offer to run it")
+        });
+        assert_eq!(
+            doctest.resolved_task.resolved_label,
+            "cargo test -p my_crate --doc test"
+        );
+
+        cx.update_editor(|editor, cx| doctest.run(editor, cx));
+        cx.editor(|_, cx| assert!(doctest.running(cx)));
+
+        cx.update(|cx| {
+            doctest.inventory.update(cx, |inventory, cx| {
+                inventory.task_finished(
+                    doctest.resolved_task.id.clone(),
+                    TaskRunResult {
+                        success: true,
+                        duration: Duration::from_millis(250),
+                    },
+                );
+                cx.notify();
+            });
+        });
+        cx.run_until_parked();
+        cx.editor(|_, cx| {
+            assert!(!doctest.running(cx));
+            assert_eq!(
+                doctest.last_result(cx).map(doctest_result_text),
+                Some("✓ Doctest passed in 0.25s".to_string())
+            );
+        });
+
+        // Items of dependencies, which live in invisible worktrees, don't offer to run their
+        // examples.
+        let fs = cx.update_workspace(|workspace, _| workspace.app_state().fs.clone());
+        fs.as_fake()
+            .insert_tree("/registry", json!({ "lib.rs": "pub fn test() {}" }))
+            .await;
+        let project = cx.update_workspace(|workspace, _| workspace.project().clone());
+        let (worktree, _) = cx
+            .update(|cx| {
+                project.update(cx, |project, cx| {
+                    project.find_or_create_local_worktree("/registry", false, cx)
+                })
+            })
+            .await
+            .unwrap();
+        let dependency_buffer = cx
+            .update(|cx| {
+                let worktree_id = worktree.read(cx).id();
+                project.update(cx, |project, cx| {
+                    project.open_buffer((worktree_id, "lib.rs"), cx)
+                })
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        cx.update_editor(|_, cx| {
+            assert_eq!(
+                dependency_buffer
+                    .read(cx)
+                    .language()
+                    .map(|language| language.name()),
+                Some("Rust".into())
+            );
+            let position = dependency_buffer.read(cx).anchor_before(Point::new(0, 8));
+            assert!(doctest_context(&project, &dependency_buffer, position, cx).is_none());
+        });
+
+        assert!(has_runnable_doctest(
+            "```rust\nfn test()\n```\n\n---\n\n```rust,should_panic\npanic!()\n```"
+        ));
+        assert!(!has_runnable_doctest(
+            "```rust\nfn test()\n```\n\n---\n\n```rust,no_run\nloop {}\n```"
+        ));
+        assert!(!has_runnable_doctest(
+            "```rust\nfn test()\n```\n\n---\n\n```text\noutput\n```"
+        ));
+        assert!(!has_runnable_doctest("```rust\nfn test()\n```"));
+    }
+
+    #[gpui::test]
+    async fn test_empty_hovers_filtered(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        // Hover with keyboard has no delay
+        cx.set_state(indoc! {"
+            fˇn test() { println!(); }
+        "});
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Array(vec![
+                    lsp::MarkedString::String("regular text for hover to show".to_string()),
+                    lsp::MarkedString::String("".to_string()),
+                    lsp::MarkedString::LanguageString(lsp::LanguageString {
+                        language: "Rust".to_string(),
+                        value: "".to_string(),
+                    }),
+                ]),
+                range: Some(symbol_range),
+            }))
+        })
+        .next()
+        .await;
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                1,
+                "Expected exactly one hover but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+
+            assert_eq!(
+                rendered_text,
+                "regular text for hover to show".to_string(),
+                "No empty string hovers should be shown"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_highlight_empty_range(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_highlight_empty_range = Some(true);
+                });
+            });
+        });
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fˇn test() { println!(); }
+        "});
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Array(Vec::new()),
+                range: Some(symbol_range),
+            }))
+        })
+        .next()
+        .await;
+        cx.dispatch_action(Hover);
+        cx.run_until_parked();
+
+        cx.editor(|editor, _| {
+            assert!(
+                !editor.hover_state.visible(),
+                "No popover should be shown for a hover without contents"
+            );
+        });
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            «fn» test() { println!(); }
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_hover_keyword_docs(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_keyword_docs = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            asˇync fn test() {}
+        "});
+        let mut requests = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+            assert_eq!(
+                rendered_text,
+                "Returns a Future instead of blocking the current thread."
+            );
+        });
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            «async» fn test() {}
+        "});
+
+        // The keyword's name in a comment or a string isn't documented as the keyword.
+        for state in [
+            indoc! {"
+                // asˇync
+                fn test() {}
+            "},
+            indoc! {"
+                fn test() { let s = "asˇync"; }
+            "},
+        ] {
+            cx.update_editor(|editor, cx| hide_hover(editor, cx));
+            cx.set_state(state);
+            cx.dispatch_action(Hover);
+            requests.next().await;
+            cx.run_until_parked();
+            cx.editor(|editor, _| assert!(editor.hover_state.info_popovers.is_empty()));
+        }
+    }
+
+    #[gpui::test]
+    async fn test_hover_regex_explain(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_typescript(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_regex_explain = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            const id = /^\\d+-[a-ˇz]{2,3}$/;
+        "});
+        let mut requests = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(
+                markdown.read(cx).source(),
+                indoc! {"
+                    Regular expression:
+
+                    - `^` Start of the line
+                    - `\\d+` A digit, one or more times
+                    - `-` The literal character
+                    - `[a-z]{2,3}` One of these characters, between 2 and 3 times
+                    - `$` End of the line"}
+            );
+        });
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            const id = /«^\\d+-[a-z]{2,3}$»/;
+        "});
+    }
+
+    #[test]
+    fn test_explain_regex_named_groups() {
+        assert_eq!(
+            explain_regex("(?<éé>x)").unwrap(),
+            indoc! {"
+                Regular expression:
+
+                - `(?<éé>` Start of the capture group `éé`
+                - `x` The literal character
+                - `)` End of the group"}
+        );
+        assert_eq!(
+            explain_regex("(?P<año>\\d)").unwrap(),
+            indoc! {"
+                Regular expression:
+
+                - `(?P<año>` Start of the capture group `año`
+                - `\\d` A digit
+                - `)` End of the group"}
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover_attribute_docs(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_attribute_docs = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            #[derive(Clone, Debˇug)]
+            struct Point;
+        "});
+        let mut requests = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+            assert_eq!(rendered_text, "Formats the value with {:?} for debugging.");
+        });
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            #[derive(Clone, «Debug»)]
+            struct Point;
+        "});
+
+        // Symbols outside of attributes keep the language server's hover.
+        cx.set_state(indoc! {"
+            #[derive(Clone, Debug)]
+            struct Poˇint;
+        "});
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_backdrop(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn first() {}
+
+
+
+
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn first() {}
+
+
+
+
+            fn «test»() {}
+        "});
+        let click_point = cx.pixel_position(indoc! {"
+            fn fiˇrst() {}
+
+
+
+
+            fn test() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.update_editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            assert!(editor.hover_state.render_backdrop(&buffer, cx).is_none());
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_backdrop = Some(true);
+                });
+            });
+        });
+        cx.update_editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            assert!(editor.hover_state.render_backdrop(&buffer, cx).is_some());
+            cx.notify();
+        });
+        cx.run_until_parked();
+
+        // Clicking the backdrop dismisses the popover, and the click reaches the editor too.
+        cx.simulate_click(click_point, Modifiers::none());
+        cx.update_editor(|editor, cx| {
+            assert!(!editor.hover_state.visible());
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            assert!(editor.hover_state.render_backdrop(&buffer, cx).is_none());
+        });
+        cx.assert_editor_state(indoc! {"
+            fn fiˇrst() {}
+
+
+
+
+            fn test() {}
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_hover_fade_out(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_fade_out_millis = Some(200);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fˇn test() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.update_editor(|editor, cx| {
+            assert!(hide_hover(editor, cx));
+            assert!(!editor.hover_state.visible());
+            let dismissing = editor.hover_state.dismissing.as_ref().unwrap();
+            assert_eq!(dismissing.info_popovers.len(), 1);
+        });
+
+        cx.background_executor
+            .advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.hover_state.dismissing.is_some()));
+
+        cx.background_executor
+            .advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.hover_state.dismissing.is_none()));
+
+        // Popovers are removed immediately when the system asks to reduce motion.
+        cx.simulate_reduce_motion(true);
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.update_editor(|editor, cx| {
+            assert!(hide_hover(editor, cx));
+            assert!(editor.hover_state.dismissing.is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_retrigger_cooldown(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_retrigger_cooldown_millis = Some(1000);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let hover_point = cx.display_point(indoc! {"
+            fn test() { printˇln!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn test() { «println!»(); }
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                request_count.fetch_add(1, atomic::Ordering::Release);
+                async move {
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "some basic docs".to_string(),
+                        }),
+                        range: Some(symbol_range),
+                    }))
+                }
+            }
+        });
+        let hover_println = |cx: &mut EditorLspTestContext| {
+            cx.update_editor(|editor, cx| {
+                let snapshot = editor.snapshot(cx);
+                let anchor = snapshot
+                    .buffer_snapshot
+                    .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+                hover_at(editor, Some(anchor), cx)
+            });
+            cx.background_executor
+                .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+            cx.run_until_parked();
+        };
+
+        hover_println(&mut cx);
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 1);
+
+        // Hovering the symbol again right after dismissing its popover doesn't query it.
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        hover_println(&mut cx);
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 1);
+
+        cx.background_executor
+            .advance_clock(Duration::from_millis(1000));
+        hover_println(&mut cx);
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 2);
+    }
+
+    #[gpui::test]
+    async fn test_line_ends_trimmed(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        // Hover with keyboard has no delay
+        cx.set_state(indoc! {"
+            fˇn test() { println!(); }
+        "});
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() { println!(); }
+        "});
+
+        let code_str = "\nlet hovered_point: Vector2F // size = 8, align = 0x4\n";
+        let markdown_string = format!("\n```rust\n{code_str}```");
+
+        let closure_markdown_string = markdown_string.clone();
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| {
+            let future_markdown_string = closure_markdown_string.clone();
+            async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: future_markdown_string,
+                    }),
+                    range: Some(symbol_range),
+                }))
+            }
+        })
+        .next()
+        .await;
+
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers.len(),
+                1,
+                "Expected exactly one hover but got: {:?}",
+                editor.hover_state.info_popovers
+            );
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+
+            assert_eq!(
+                rendered_text, code_str,
+                "Should not have extra line breaks at end of rendered hover"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_untagged_code_block_uses_buffer_language(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Scalar(lsp::MarkedString::LanguageString(
+                    lsp::LanguageString {
+                        language: String::new(),
+                        value: "fn test()".to_string(),
+                    },
+                )),
+                range: Some(symbol_range),
+            }))
+        });
+
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            let parsed_content = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(parsed_content.read(cx).source(), "```Rust\nfn test()\n```");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_truncation_hint(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_truncation_hint = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "Runs the test suite and reports [truncated]".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, _| {
+            assert!(editor.hover_state.info_popovers[0].content_truncated);
+        });
+
+        assert!(hover_content_truncated("Returns the value (truncated)"));
+        assert!(hover_content_truncated(
+            "```rust\nfn test(\n    value: u32,"
+        ));
+        assert!(!hover_content_truncated("Returns the value…"));
+        assert!(!hover_content_truncated("Returns the value of the first,"));
+        assert!(!hover_content_truncated("Returns the value."));
+        assert!(!hover_content_truncated("```rust\nfn test()\n```"));
+        assert!(!hover_content_truncated("Wrap code in ``` fences."));
+    }
+
+    #[test]
+    fn test_hover_popover_max_width() {
+        let em_width = px(10.);
+        // By default, popovers are 120 characters wide, up to half of the editor width.
+        assert_eq!(
+            hover_popover_max_width(em_width, px(3000.), px(3000.), 0.),
+            px(1200.)
+        );
+        assert_eq!(
+            hover_popover_max_width(em_width, px(1000.), px(3000.), 0.),
+            px(500.)
+        );
+        // A percentage of the window width takes precedence over both.
+        assert_eq!(
+            hover_popover_max_width(em_width, px(3000.), px(3000.), 60.),
+            px(1800.)
+        );
+        assert_eq!(
+            hover_popover_max_width(em_width, px(1000.), px(3000.), 25.),
+            px(750.)
+        );
+        // The minimum width of 20 characters still applies.
+        assert_eq!(
+            hover_popover_max_width(em_width, px(300.), px(300.), 50.),
+            px(200.)
+        );
+    }
+
+    #[gpui::test]
+    fn test_hover_inline_code_style(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let cx = cx.add_empty_window();
+        cx.update(|cx| {
+            // Inline code takes its colors from the theme, keeping the text color of the prose.
+            let style = hover_markdown_style(EditorSettings::get_global(cx), cx);
+            assert_eq!(
+                style.inline_code.background_color,
+                Some(cx.theme().colors().background)
+            );
+            assert_eq!(style.inline_code.color, None);
+            assert_eq!(
+                style.base_text_style.color,
+                cx.theme().colors().editor_foreground
+            );
+        });
+    }
+
+    #[test]
+    fn test_hover_popover_heights() {
+        let min_info_height = px(80.);
+        // Popovers that fit are rendered at their natural heights.
+        assert_eq!(
+            hover_popover_heights(
+                HoverOverflowBehavior::ScrollInfo,
+                px(500.),
+                px(100.),
+                &[px(300.)],
+                min_info_height,
+            ),
+            None
+        );
+        // By default, the diagnostic keeps its height and the info popovers scroll.
+        assert_eq!(
+            hover_popover_heights(
+                HoverOverflowBehavior::ScrollInfo,
+                px(500.),
+                px(300.),
+                &[px(400.)],
+                min_info_height,
+            ),
+            Some(HoverPopoverHeights {
+                diagnostic: px(300.),
+                info: px(200.),
+            })
+        );
+        // Both scroll, with the diagnostic limited to its share of the space.
+        assert_eq!(
+            hover_popover_heights(
+                HoverOverflowBehavior::ScrollBoth,
+                px(500.),
+                px(400.),
+                &[px(400.)],
+                min_info_height,
+            ),
+            Some(HoverPopoverHeights {
+                diagnostic: px(250.),
+                info: px(250.),
+            })
+        );
+        // A short info popover leaves the rest of the space to the diagnostic.
+        assert_eq!(
+            hover_popover_heights(
+                HoverOverflowBehavior::ScrollBoth,
+                px(500.),
+                px(600.),
+                &[px(100.)],
+                min_info_height,
+            ),
+            Some(HoverPopoverHeights {
+                diagnostic: px(400.),
+                info: px(100.),
+            })
+        );
+        // Info popovers never shrink below the minimum height.
+        assert_eq!(
+            hover_popover_heights(
+                HoverOverflowBehavior::ScrollInfo,
+                px(500.),
+                px(480.),
+                &[px(200.), px(200.)],
+                min_info_height,
+            ),
+            Some(HoverPopoverHeights {
+                diagnostic: px(480.),
+                info: px(80.),
+            })
+        );
+    }
+
+    #[gpui::test]
+    fn test_language_hover_popover_max_width(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.languages.insert(
+                "Rust".into(),
+                LanguageSettingsContent {
+                    hover_popover_max_width_percent: Some(80.),
+                    ..Default::default()
+                },
+            );
+            settings.languages.insert(
+                "Python".into(),
+                LanguageSettingsContent {
+                    hover_popover_max_width_percent: Some(30.),
+                    ..Default::default()
+                },
+            );
+        });
+
+        let rust_buffer = cx.new_model(|cx| {
+            Buffer::local("fn test() {}", cx)
+                .with_language(Arc::new(crate::editor_tests::rust_lang()), cx)
+        });
+        let python_language = Arc::new(Language::new(
+            LanguageConfig {
+                name: "Python".into(),
+                ..Default::default()
+            },
+            None,
+        ));
+        let python_buffer = cx.new_model(|cx| {
+            Buffer::local("def test(): pass", cx).with_language(python_language, cx)
+        });
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(0, Capability::ReadWrite);
+            for buffer in [rust_buffer, python_buffer] {
+                multibuffer.push_excerpts(
+                    buffer,
+                    [ExcerptRange {
+                        context: Point::new(0, 0)..Point::new(0, 12),
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+            multibuffer
+        });
+
+        let bounds = HoverPopoverWidthBounds {
+            em_width: px(10.),
+            editor_width: px(1000.),
+            window_width: px(2000.),
+        };
+        cx.update(|cx| {
+            let snapshot = multibuffer.read(cx).snapshot(cx);
+            let rust_anchor = snapshot.anchor_after(Point::new(0, 4));
+            let python_anchor = snapshot.anchor_after(Point::new(1, 4));
+            assert_eq!(
+                language_hover_popover_max_width(&snapshot, rust_anchor, bounds, cx),
+                px(1600.)
+            );
+            assert_eq!(
+                language_hover_popover_max_width(&snapshot, python_anchor, bounds, cx),
+                px(600.)
+            );
+        });
+    }
+
+    #[test]
+    fn test_combine_blocks_strips_doc_comment_spaces() {
+        let markdown = |text: &str| HoverBlock {
+            text: text.to_string(),
+            kind: project::HoverBlockKind::Markdown,
+        };
+
+        // The space after each `///` is removed, keeping the code block's indentation.
+        assert_eq!(
+            combine_blocks(
+                &[markdown(
+                    " Adds one.\n\n ```\n fn add_one(x: u32) -> u32 {\n     x + 1\n }\n ```"
+                )],
+                None,
+                false,
+                false,
+            ),
+            "Adds one.\n\n```\nfn add_one(x: u32) -> u32 {\n    x + 1\n}\n```"
+        );
+        // Text whose lines don't all start with the space is left alone.
+        assert_eq!(
+            combine_blocks(&[markdown("Adds one.\n  - fast")], None, false, false),
+            "Adds one.\n  - fast"
+        );
+    }
+
+    #[test]
+    fn test_combine_blocks_separates_kinds() {
+        let signature = HoverBlock {
+            text: "fn test()".to_string(),
+            kind: project::HoverBlockKind::Code {
+                language: "rust".to_string(),
+            },
+        };
+        let docs = |text: &str| HoverBlock {
+            text: text.to_string(),
+            kind: project::HoverBlockKind::PlainText,
+        };
+
+        let combined = combine_blocks(&[signature, docs("Some docs")], None, false, false);
+        assert_eq!(combined, "```rust\nfn test()\n```\n\n---\n\nSome docs");
+        assert_eq!(combined.matches(HOVER_BLOCK_SEPARATOR).count(), 1);
+
+        // Blocks of the same kind are only spaced apart.
+        assert_eq!(
+            combine_blocks(&[docs("Some docs"), docs("More docs")], None, false, false),
+            "Some docs\n\nMore docs"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover_toc_headings(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_show_toc = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "# Overview\nRuns the tests.\n## Calling `test`\nNo arguments."
+                        .to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            let headings = editor.hover_state.info_popovers[0].toc_headings();
+            let source = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx)
+                .source()
+                .to_string();
+            assert_eq!(
+                headings
+                    .iter()
+                    .map(|heading| (heading.level, heading.title.as_str()))
+                    .collect::<Vec<_>>(),
+                vec![(1, "Overview"), (2, "Calling test")]
+            );
+            assert!(source[headings[0].source_index..].starts_with("Overview"));
+            assert!(source[headings[1].source_index..].starts_with("Calling"));
+        });
+
+        let single_heading = parse_markdown("# Overview\nRuns the tests.");
+        assert_eq!(
+            markdown_headings("# Overview\nRuns the tests.", &single_heading),
+            vec![HoverHeading {
+                level: 1,
+                title: "Overview".to_string(),
+                source_index: 2,
+            }]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover_restyled_on_font_change(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "some basic docs".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<ThemeSettings>(cx, |settings| {
+                    settings.buffer_font_family = Some("Hover Test Mono".to_string());
+                });
+            });
+        });
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            assert_eq!(
+                markdown.style().base_text_style.font_family,
+                SharedString::from("Hover Test Mono")
+            );
+        });
+    }
+
+    #[cfg(debug_assertions)]
+    #[gpui::test]
+    async fn test_show_raw_hover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(crate::ShowRawHover);
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let rendered_text = editor.hover_state.info_popovers[0].get_rendered_text(cx);
+            assert!(
+                rendered_text.contains(r#""value": "some basic docs""#),
+                "{rendered_text}"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_raw_doc_comment(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_raw_doc_comment = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            // Not documentation.
+            /// Does the **work**.
+            ///
+            /// - first
+            #[inline]
+            fn do_work() {}
+            fn test() { do_wˇork(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            // Not documentation.
+            /// Does the **work**.
+            ///
+            /// - first
+            #[inline]
+            fn do_work() {}
+            fn test() { «do_work»(); }
+        "});
+        let target_range = cx.lsp_range(indoc! {"
+            // Not documentation.
+            /// Does the **work**.
+            ///
+            /// - first
+            #[inline]
+            fn «do_work»() {}
+            fn test() { do_work(); }
+        "});
+        cx.handle_request::<lsp::request::GotoDefinition, _, _>(move |url, _, _| async move {
+            Ok(Some(lsp::GotoDefinitionResponse::Link(vec![
+                lsp::LocationLink {
+                    origin_selection_range: Some(symbol_range),
+                    target_uri: url.clone(),
+                    target_range,
+                    target_selection_range: target_range,
+                },
+            ])))
+        });
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "```rust\nfn do_work()\n```\n\n---\n\nDoes the work. - first"
+                        .to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            assert_eq!(editor.hover_state.info_popovers.len(), 1);
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            // The signature of the language server's hover is kept, its documentation is
+            // replaced with the doc comment.
+            assert_eq!(
+                markdown.source(),
+                "```rust\nfn do_work()\n```\n\nDoes the **work**.\n\n- first"
+            );
+        });
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            // Not documentation.
+            /// Does the **work**.
+            ///
+            /// - first
+            #[inline]
+            fn do_work() {}
+            fn test() { «do_work»(); }
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_hover_dependency_source(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_dependency_source = Some(true);
+                });
+            });
+        });
+        let fs = cx.update_workspace(|workspace, _| workspace.app_state().fs.clone());
+        fs.as_fake()
+            .insert_tree(
+                "/registry",
+                json!({ "dep": { "lib.rs": "pub fn dep() {}\n" } }),
+            )
+            .await;
+
+        cx.set_state(indoc! {"
+            fn test() { deˇp(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn test() { «dep»(); }
+        "});
+        cx.handle_request::<lsp::request::GotoDefinition, _, _>(move |_, _, _| async move {
+            let target_range = lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 10));
+            Ok(Some(lsp::GotoDefinitionResponse::Link(vec![
+                lsp::LocationLink {
+                    origin_selection_range: Some(symbol_range),
+                    target_uri: lsp::Url::from_file_path("/registry/dep/lib.rs").unwrap(),
+                    target_range,
+                    target_selection_range: target_range,
+                },
+            ])))
+        });
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "Does the work".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        let location = cx.editor(|editor, cx| {
+            let project = editor.project.as_ref().unwrap().read(cx);
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            assert!(!is_dependency_source(project, buffer.read(cx), cx));
+
+            editor.hover_state.info_popovers[0]
+                .dependency_source
+                .clone()
+                .expect("definition should resolve to the dependency")
+        });
+        cx.update_editor(|editor, cx| open_dependency_source(editor, location, cx));
+        cx.run_until_parked();
+
+        let source_editor =
+            cx.update_workspace(|workspace, cx| workspace.active_item_as::<Editor>(cx).unwrap());
+        cx.read(|cx| {
+            let source_editor = source_editor.read(cx);
+            assert!(source_editor.read_only(cx));
+            assert_eq!(source_editor.text(cx), "pub fn dep() {}\n");
+            assert_eq!(
+                source_editor.selections.newest::<Point>(cx).head(),
+                Point::new(0, 7)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_typographic_replacements(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_typographic_replacements = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "Decrements `i--` -- \"quickly\"\n\n```c\ni--;\n```".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            let rendered_text = editor
+                .hover_state
+                .info_popovers
+                .first()
+                .unwrap()
+                .get_rendered_text(cx);
+            assert_eq!(rendered_text, "Decrements i-- — “quickly”i--;\n");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_status_bar_placement(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_popover_placement = Some(HoverPopoverPlacement::StatusBar);
+                });
+            });
+        });
+        let editor = cx.editor.clone();
+        let status_item = cx.update(|cx| {
+            let status_item = cx.new_view(|_| HoverStatusItem::new());
+            status_item.update(cx, |status_item, cx| {
+                status_item.set_active_pane_item(Some(&editor), cx)
+            });
+            status_item
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "```rust\nfn test()\n```\n\nDoes nothing".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        cx.update(|cx| {
+            assert_eq!(
+                status_item.read(cx).text().map(|text| text.as_ref()),
+                Some("fn test()")
+            );
+        });
+        // The popover itself is not shown.
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let style = editor.style.clone().unwrap_or_default();
+            assert!(editor
+                .hover_state
+                .render(
+                    &snapshot,
+                    &style,
+                    DisplayRow(0)..DisplayRow(10),
+                    gpui::size(px(400.), px(200.)),
+                    None,
+                    cx,
+                )
+                .is_none());
+        });
+
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        cx.run_until_parked();
+        cx.update(|cx| assert_eq!(status_item.read(cx).text(), None));
+    }
+
+    #[gpui::test]
+    async fn test_hover_stale_warning(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Does nothing".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.update_editor(|editor, cx| {
+            assert!(editor.buffer().read(cx).is_dirty(cx));
+        });
+
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, _| assert!(!editor.hover_state.info_popovers[0].stale_warning));
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_stale_warning = Some(true);
+                });
+            });
+        });
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, _| assert!(editor.hover_state.info_popovers[0].stale_warning));
+
+        // Servers that are sent the edits of the buffer don't hover stale text.
+        assert!(!syncs_document_changes(&lsp::ServerCapabilities::default()));
+        assert!(syncs_document_changes(&lsp::ServerCapabilities {
+            text_document_sync: Some(lsp::TextDocumentSyncCapability::Kind(
+                lsp::TextDocumentSyncKind::INCREMENTAL
+            )),
+            ..Default::default()
+        }));
+        assert!(!syncs_document_changes(&lsp::ServerCapabilities {
+            text_document_sync: Some(lsp::TextDocumentSyncCapability::Options(
+                lsp::TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(lsp::TextDocumentSyncKind::NONE),
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }));
+    }
+
+    #[gpui::test]
+    async fn test_hover_bracket_match(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_bracket_match = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn main() ˇ{
+                let x = 1;
+            } // end of main
+        "});
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            let popover = &editor.hover_state.info_popovers[0];
+            let rendered_text = popover.get_rendered_text(cx);
+            assert!(rendered_text.contains("on line 3"), "{rendered_text:?}");
+            assert!(rendered_text.contains("} // end of main"));
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            assert_eq!(
+                popover.bracket_match.unwrap().to_point(&snapshot),
+                Point::new(2, 0)
+            );
+        });
+
+        // A bracket with a diagnostic shows the diagnostic rather than its match.
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        let range = cx.text_anchor_range(indoc! {"
+            fn main() «{»
+                let x = 1;
+            } // end of main
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "this delimiter might not be properly closed".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, _| {
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert_eq!(
+                diagnostic_popover.local_diagnostic.diagnostic.message,
+                "this delimiter might not be properly closed"
+            );
+            assert!(editor
+                .hover_state
+                .info_popovers
+                .iter()
+                .all(|popover| popover.bracket_match.is_none()));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_show_last_hover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_remember_last = Some(true);
+                });
+            });
+        });
+        let fs = cx.update_workspace(|workspace, _| workspace.app_state().fs.clone());
+        fs.as_fake()
+            .insert_file("/root/dir/other.rs", "fn other() {}\n".into())
+            .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                request_count.fetch_add(1, atomic::Ordering::Release);
+                async move {
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "Remembered docs".to_string(),
+                        }),
+                        range: Some(symbol_range),
+                    }))
+                }
+            }
+        });
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        // Switching to another editor hides the hover.
+        cx.update_workspace(|workspace, cx| {
+            workspace.open_abs_path(PathBuf::from("/root/dir/other.rs"), true, cx)
+        })
+        .await
+        .unwrap();
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+
+        let editor = cx.editor.clone();
+        cx.update_workspace(|workspace, cx| workspace.activate_item(&editor, cx));
+        cx.run_until_parked();
+        cx.dispatch_action(ShowLastHover);
+        cx.editor(|editor, cx| {
+            assert!(editor.hover_state.visible());
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "Remembered docs"
+            );
+        });
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 1);
+    }
+
+    #[gpui::test]
+    async fn test_hover_code_theme(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            ThemeRegistry::global(cx).insert_user_themes([serde_json::from_value(json!({
+                "name": "High Contrast",
+                "appearance": "dark",
+                "style": { "syntax": { "keyword": { "color": "#ff0000" } } }
+            }))
+            .unwrap()]);
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_code_theme = Some("High Contrast".to_string());
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "```rust\nfn test()\n```".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            let override_keyword = ThemeRegistry::global(cx)
+                .get("High Contrast")
+                .unwrap()
+                .syntax()
+                .color("keyword");
+            assert_ne!(cx.theme().syntax().color("keyword"), override_keyword);
+
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(
+                markdown.read(cx).style().syntax.color("keyword"),
+                override_keyword
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_symbol_kind(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                document_symbol_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_show_symbol_kind = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let definition_range = cx.lsp_range(indoc! {"
+            «fn test() {}»
+        "});
+        cx.handle_request::<lsp::request::DocumentSymbolRequest, _, _>(move |_, _, _| async move {
+            #[allow(deprecated)]
+            let symbol = lsp::DocumentSymbol {
+                name: "test".to_string(),
+                detail: None,
+                kind: lsp::SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                range: definition_range,
+                selection_range: symbol_range,
+                children: None,
+            };
+            Ok(Some(lsp::DocumentSymbolResponse::Nested(vec![symbol])))
+        });
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "Does nothing".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, _| {
+            let symbol_kind = editor.hover_state.info_popovers[0].symbol_kind;
+            assert_eq!(symbol_kind, Some(lsp::SymbolKind::FUNCTION));
+            assert_eq!(
+                symbol_kind.and_then(symbol_kind_icon),
+                Some((IconName::Code, "function"))
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_symbol_kind_without_document_symbols(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_show_symbol_kind = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let symbol_requests = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::DocumentSymbolRequest, _, _>({
+            let symbol_requests = symbol_requests.clone();
+            move |_, _, _| {
+                symbol_requests.fetch_add(1, atomic::Ordering::Release);
+                async move { Ok(None) }
+            }
+        });
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Does nothing".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        // Servers that don't provide document symbols aren't asked for them.
+        assert_eq!(symbol_requests.load(atomic::Ordering::Acquire), 0);
+        cx.editor(|editor, _| assert_eq!(editor.hover_state.info_popovers[0].symbol_kind, None));
+    }
+
+    #[gpui::test]
+    async fn test_build_hover_elements(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Does nothing".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+
+        let elements = cx.update_editor(|editor, cx| {
+            editor.build_hover_elements_at(4, gpui::size(px(400.), px(200.)), cx)
+        });
+        requests.next().await;
+        let elements = elements.await.unwrap();
+        assert_eq!(elements.len(), 1);
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_preserve_server_spacing(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::PlainText,
+                        value: "usage: test [options]\n  -v  verbose\n  -q  quiet".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        let line_breaks = |cx: &mut EditorLspTestContext| {
+            cx.editor(|editor, cx| {
+                let markdown = editor.hover_state.info_popovers[0]
+                    .parsed_content
+                    .clone()
+                    .unwrap();
+                let events = markdown.read(cx).parsed_markdown().events().clone();
+                let count =
+                    |kind: MarkdownEvent| events.iter().filter(|(_, event)| *event == kind).count();
+                (
+                    count(MarkdownEvent::SoftBreak),
+                    count(MarkdownEvent::HardBreak),
+                )
+            })
+        };
+
+        // By default, the lines of plain text are joined like a markdown paragraph.
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        assert_eq!(line_breaks(&mut cx), (2, 0));
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_preserve_server_spacing = Some(true);
+                });
+            });
+        });
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        assert_eq!(line_breaks(&mut cx), (0, 2));
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "usage: test [options]\u{a0}\u{a0}-v  verbose\u{a0}\u{a0}-q  quiet"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_preserved_spacing_keeps_table_rows(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_preserve_server_spacing = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::PlainText,
+                        value: indoc! {"
+                            | Name | Docs |
+                            | --- | --- |
+                            | first | one |
+                            | second | two |
+
+                            Trailing note
+                            on two lines
+                        "}
+                        .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            let popover = editor.hover_state.info_popovers.first().unwrap();
+            assert_eq!(
+                popover.get_rendered_text(cx),
+                "NameDocsfirstonesecondtwoTrailing noteon two lines"
+            );
+
+            // The rows stay intact and the breaks are kept outside of the table only.
+            let markdown = popover.parsed_content.as_ref().unwrap().read(cx);
+            let mut within_cell = false;
+            let mut rows = 0;
+            let mut breaks_in_cells = 0;
+            let mut breaks_outside_table = 0;
+            for (_, event) in markdown.parsed_markdown().events().iter() {
+                match event {
+                    MarkdownEvent::Start(MarkdownTag::TableCell) => within_cell = true,
+                    MarkdownEvent::End(MarkdownTagEnd::TableCell) => within_cell = false,
+                    MarkdownEvent::Start(MarkdownTag::TableRow) => rows += 1,
+                    MarkdownEvent::SoftBreak | MarkdownEvent::HardBreak if within_cell => {
+                        breaks_in_cells += 1
+                    }
+                    MarkdownEvent::HardBreak => breaks_outside_table += 1,
+                    _ => {}
+                }
+            }
+            assert_eq!(rows, 2);
+            assert_eq!(breaks_in_cells, 0);
+            assert_eq!(breaks_outside_table, 1);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_table_cells_with_inline_markdown(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fˇn test() { println!(); }
+        "});
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() { println!(); }
+        "});
+
+        let markdown_string = indoc! {"
+            | Name | Docs |
+            | --- | --- |
+            | **bold** | [link](https://zed.dev) |
+        "};
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: markdown_string.to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        })
+        .next()
+        .await;
+
+        cx.dispatch_action(Hover);
+
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            let popover = editor.hover_state.info_popovers.first().unwrap();
+            let markdown = popover.parsed_content.as_ref().unwrap().read(cx);
+
+            let lines = markdown
+                .rendered_lines()
+                .map(|(text, runs)| (text.to_string(), runs.to_vec()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                lines
+                    .iter()
+                    .map(|(text, _)| text.as_str())
+                    .collect::<Vec<_>>(),
+                ["Name", "Docs", "bold", "link"],
+                "Expected each table cell to be laid out as its own line"
+            );
+
+            let (_, bold_runs) = &lines[2];
+            assert!(
+                bold_runs
+                    .iter()
+                    .all(|run| run.font.weight == FontWeight::BOLD),
+                "Expected the bold cell to be drawn with a bold font"
+            );
+            let (_, link_runs) = &lines[3];
+            assert!(
+                link_runs.iter().all(|run| run.underline.is_some()),
+                "Expected the link cell to be drawn with the link style"
+            );
+
+            let source = markdown.parsed_markdown().source();
+            let links = markdown.rendered_links().collect::<Vec<_>>();
+            assert_eq!(links.len(), 1);
+            let (link_range, link_url) = &links[0];
+            assert_eq!(*link_url, "https://zed.dev");
+            assert_eq!(&source[link_range.clone()], "[link](https://zed.dev)");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_list_item_indentation(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fˇn test() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: indoc! {"
+                            9. Ninth item
+
+                               Ninth paragraph
+                            10. Tenth item
+                                - Nested item
+
+                                  Nested paragraph
+                        "}
+                        .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            let lines = markdown
+                .rendered_line_bounds()
+                .map(|(text, bounds)| (text.trim().to_string(), bounds.origin.x))
+                .collect::<Vec<_>>();
+            let left = |line: &str| {
+                lines
+                    .iter()
+                    .find(|(text, _)| text == line)
+                    .map(|(_, left)| *left)
+                    .unwrap_or_else(|| panic!("{line:?} wasn't rendered in {lines:?}"))
+            };
+
+            // The items of a list and their later paragraphs start at the same offset,
+            // whatever the width of their bullets.
+            assert_eq!(left("Ninth paragraph"), left("Ninth item"));
+            assert_eq!(left("Tenth item"), left("Ninth item"));
+            // Nested lists are indented further than the text of the item containing them.
+            assert!(left("Nested item") > left("Tenth item"));
+            assert_eq!(left("Nested paragraph"), left("Nested item"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_paragraph_indent(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_paragraph_indent = Some(2.);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fˇn test() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: indoc! {"
+                            # Title
+
+                            Prose
+
+                            - Item
+
+                              Item paragraph
+                        "}
+                        .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            let lines = markdown
+                .rendered_line_bounds()
+                .map(|(text, bounds)| (text.to_string(), bounds.origin.x))
+                .collect::<Vec<_>>();
+            let left = |line: &str| {
+                lines
+                    .iter()
+                    .find(|(text, _)| text == line)
+                    .map(|(_, left)| *left)
+                    .unwrap_or_else(|| panic!("{line:?} wasn't rendered in {lines:?}"))
+            };
+
+            // The indentation is layout padding, so the rendered text is left as is.
+            assert!(left("Prose") > left("Title"));
+            assert_eq!(left("Item paragraph"), left("Item"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_adjacent_code_blocks_in_different_languages(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let syntax = Arc::new(SyntaxTheme::new_test([
+            ("string", gpui::red()),
+            ("tag", gpui::blue()),
+        ]));
+        cx.update(|cx| {
+            let mut theme_settings = ThemeSettings::get_global(cx).clone();
+            let mut theme = (*theme_settings.active_theme).clone();
+            theme.styles.syntax = syntax.clone();
+            theme_settings.active_theme = Arc::new(theme);
+            ThemeSettings::override_global(theme_settings, cx);
+        });
+        let typescript = Language::new(
+            LanguageConfig {
+                name: "TypeScript".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_typescript::language_typescript()),
+        )
+        .with_highlights_query("(string) @string")
+        .unwrap();
+        typescript.set_theme(&syntax);
+        cx.language_registry().add(Arc::new(typescript));
+        let html = Language::new(
+            LanguageConfig {
+                name: "HTML".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_html::language()),
+        )
+        .with_highlights_query("(tag_name) @tag")
+        .unwrap();
+        html.set_theme(&syntax);
+        cx.language_registry().add(Arc::new(html));
+
+        cx.set_state(indoc! {"
+            fˇn test() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            «fn» test() {}
+        "});
+        // The second fence directly follows the first one, and lists an attribute after its
+        // language.
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "```typescript\nlet s = \"ts\";\n```\n```html,ignore\n<p></p>\n```"
+                            .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            let lines = markdown
+                .rendered_lines()
+                .map(|(text, runs)| {
+                    let colors = runs.iter().map(|run| run.color).collect::<Vec<_>>();
+                    (text.trim().to_string(), colors)
+                })
+                .collect::<Vec<_>>();
+            let colors_of = |line: &str| {
+                lines
+                    .iter()
+                    .find(|(text, _)| text == line)
+                    .map(|(_, colors)| colors.clone())
+                    .unwrap_or_else(|| panic!("expected {line:?} on its own line in {lines:?}"))
+            };
+            assert!(colors_of("let s = \"ts\";").contains(&gpui::red()));
+            assert!(colors_of("<p></p>").contains(&gpui::blue()));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_diagnostic_and_info_popovers(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        // Hover with just diagnostic, pops DiagnosticPopover immediately and then
+        // info popover once request completes
+        cx.set_state(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+
+        // Send diagnostic to client
+        let range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "A test diagnostic message.".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        // Hover pops diagnostic immediately
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        cx.editor(|Editor { hover_state, .. }, _| {
+            assert!(
+                hover_state.diagnostic_popover.is_some() && hover_state.info_popovers.is_empty()
+            )
+        });
+
+        // Info Popover shows after request responded to
+        let range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "some new docs".to_string(),
+                }),
+                range: Some(range),
+            }))
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+
+        cx.background_executor.run_until_parked();
+        cx.editor(|Editor { hover_state, .. }, _| {
+            hover_state.diagnostic_popover.is_some() && hover_state.info_task.is_some()
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_diagnostic_anchor(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn test() {
+                let a = 1;
+                let ˇb = 2;
+            }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn test() «{
+                let a = 1;
+                let b = 2;
+            }»
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "A multi-line diagnostic.".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        // By default the popovers are positioned at the start of the diagnostic
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let anchor = editor.hover_state.popover_anchor(&snapshot, cx).unwrap();
+            assert_eq!(anchor.to_point(&snapshot), Point::new(0, 10));
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_diagnostic_anchor = Some(HoverDiagnosticAnchor::MousePoint);
+                });
+            });
+        });
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let anchor = editor.hover_state.popover_anchor(&snapshot, cx).unwrap();
+            assert_eq!(anchor.to_point(&snapshot), Point::new(2, 8));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_diagnostic_hover_max_lines(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.diagnostic_hover_max_lines = Some(2);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: indoc! {"
+                            mismatched types
+                            expected struct `Vec<String>`
+                            found struct `Vec<&str>`
+                            in this macro invocation
+                        "}
+                        .to_string(),
+                        source: Some("rustc".to_string()),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            let (message, _) = diagnostic_popover.message_and_trace(&buffer, cx);
+            assert_eq!(
+                diagnostic_popover.truncated_message(&message, &buffer, cx),
+                Some("rustc: mismatched types\nexpected struct `Vec<String>`")
+            );
+            assert_eq!(
+                diagnostic_popover.truncated_message("one line\ntwo lines\n", &buffer, cx),
+                None
+            );
+            assert!(!*diagnostic_popover.message_expanded.borrow());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_import_suggestions(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_import_suggestions = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn test() -> HashˇMap<u32, u32> {}
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn test() -> «HashMap»<u32, u32> {}
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "cannot find type `HashMap` in this scope".to_string(),
+                        code: Some("E0412".to_string()),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        let buffer_uri = cx.buffer_lsp_url.clone();
+        let mut code_action_requests =
+            cx.handle_request::<lsp::request::CodeActionRequest, _, _>(move |_, _, _| {
+                let buffer_uri = buffer_uri.clone();
+                async move {
+                    Ok(Some(vec![
+                        lsp::CodeActionOrCommand::CodeAction(lsp::CodeAction {
+                            title: "Import `std::collections::HashMap`".to_string(),
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            edit: Some(lsp::WorkspaceEdit {
+                                changes: Some(
+                                    [(
+                                        buffer_uri,
+                                        vec![lsp::TextEdit::new(
+                                            lsp::Range::new(
+                                                lsp::Position::new(0, 0),
+                                                lsp::Position::new(0, 0),
+                                            ),
+                                            "use std::collections::HashMap;\n\n".to_string(),
+                                        )],
+                                    )]
+                                    .into_iter()
+                                    .collect(),
+                                ),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                        lsp::CodeActionOrCommand::CodeAction(lsp::CodeAction {
+                            title: "Create struct `HashMap`".to_string(),
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            ..Default::default()
+                        }),
+                    ]))
+                }
+            });
+        let mut hover_requests = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        code_action_requests.next().await;
+        hover_requests.next().await;
+        cx.run_until_parked();
+
+        let import_suggestions = cx.editor(|editor, _| {
+            editor
+                .hover_state
+                .diagnostic_popover
+                .as_ref()
+                .and_then(|popover| popover.import_suggestions.clone())
+                .unwrap()
+        });
+        assert_eq!(
+            import_suggestions
+                .actions
+                .iter()
+                .map(|action| action.lsp_action.title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Import `std::collections::HashMap`"]
+        );
+
+        cx.update_editor(|editor, cx| {
+            apply_hover_code_action(
+                editor,
+                import_suggestions.buffer.clone(),
+                import_suggestions.actions[0].clone(),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            use std::collections::HashMap;
+
+            fn test() -> HashˇMap<u32, u32> {}
+        "});
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_prominent_quick_fix(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_prominent_quickfix = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn test() -> u32 { ˇ1u64 }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn test() -> u32 { «1u64» }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "mismatched types".to_string(),
+                        code: Some("E0308".to_string()),
+                        severity: DiagnosticSeverity::ERROR,
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        let buffer_uri = cx.buffer_lsp_url.clone();
+        let mut code_action_requests =
+            cx.handle_request::<lsp::request::CodeActionRequest, _, _>(move |_, _, _| {
+                let buffer_uri = buffer_uri.clone();
+                async move {
+                    Ok(Some(vec![
+                        lsp::CodeActionOrCommand::CodeAction(lsp::CodeAction {
+                            title: "Convert to u32 with `try_into`".to_string(),
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            ..Default::default()
+                        }),
+                        lsp::CodeActionOrCommand::CodeAction(lsp::CodeAction {
+                            title: "Change return type to u64".to_string(),
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            is_preferred: Some(true),
+                            edit: Some(lsp::WorkspaceEdit {
+                                changes: Some(
+                                    [(
+                                        buffer_uri,
+                                        vec![lsp::TextEdit::new(
+                                            lsp::Range::new(
+                                                lsp::Position::new(0, 13),
+                                                lsp::Position::new(0, 16),
+                                            ),
+                                            "u64".to_string(),
+                                        )],
+                                    )]
+                                    .into_iter()
+                                    .collect(),
+                                ),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                    ]))
+                }
+            });
+        let mut hover_requests = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        code_action_requests.next().await;
+        hover_requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, _| {
+            let (_, quick_fix) = editor
+                .hover_state
+                .diagnostic_popover
+                .as_ref()
+                .and_then(|popover| popover.quick_fix.clone())
+                .unwrap();
+            assert_eq!(quick_fix.lsp_action.title, "Change return type to u64");
+        });
+
+        cx.dispatch_action(ApplyHoverQuickFix);
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            fn test() -> u64 { ˇ1u64 }
+        "});
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_local_images(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_render_local_images = Some(true);
+                });
+            });
+        });
+        let fs = cx.update_workspace(|workspace, _| workspace.app_state().fs.clone());
+        fs.as_fake()
+            .insert_file("/root/dir/diagram.png", b"\x89PNG\r\n\x1a\n".to_vec())
+            .await;
+        fs.as_fake()
+            .insert_file("/root/dir/notes.txt", b"not an image".to_vec())
+            .await;
+        fs.as_fake()
+            .insert_tree("/root/dir/folder.png", json!({}))
+            .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: indoc! {"
+                        ![diagram](diagram.png)
+                        ![missing](missing.png)
+                        ![notes](notes.txt)
+                        ![folder](folder.png)
+                        ![remote](https://example.com/remote.png)
+                    "}
+                    .to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        // The image is rendered in place of its alt text, while the others fall back to links.
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .as_ref()
+                .unwrap()
+                .read(cx);
+            assert_eq!(
+                markdown.style().local_images,
+                HashMap::from_iter([(
+                    SharedString::from("diagram.png"),
+                    PathBuf::from("/root/dir/diagram.png")
+                )])
+            );
+            let rendered_text = markdown
+                .rendered_lines()
+                .map(|(text, _)| text)
+                .collect::<String>();
+            assert!(!rendered_text.contains("diagram"), "{rendered_text:?}");
+            assert_eq!(
+                markdown
+                    .rendered_links()
+                    .map(|(_, destination)| destination)
+                    .collect::<Vec<_>>(),
+                vec![
+                    "missing.png",
+                    "notes.txt",
+                    "folder.png",
+                    "https://example.com/remote.png"
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_passes_through_moves(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_block_underlying_moves = Some(false);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Returns the answer".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.update_editor(|editor, cx| {
+            let anchor = editor.selections.newest_anchor().head();
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        // The move over the popover reaches the editor, which isn't hovering a symbol there.
+        let markdown = cx.editor(|editor, _| {
+            editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap()
+        });
+        let position = cx.update(|cx| {
+            markdown.read(cx).position_for_source_index(8).unwrap() + gpui::point(px(0.), px(2.))
+        });
+        cx.simulate_mouse_move(position, None, Modifiers::none());
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    async fn test_hover_chain_debounce(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_chain_debounce = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn test() { ˇa.b().c(); }
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, params, _| {
+                request_count.fetch_add(1, atomic::Ordering::Release);
+                async move {
+                    let docs = match params.text_document_position_params.position.character {
+                        14 => "Docs for b",
+                        18 => "Docs for c",
+                        _ => "Docs for a",
+                    };
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: docs.to_string(),
+                        }),
+                        range: None,
+                    }))
+                }
+            }
+        });
+
+        // Sweep over `b` without settling on it long enough for its hover.
+        let hover_point = |cx: &mut EditorLspTestContext, marked_text: &str| {
+            let point = cx.display_point(marked_text);
+            cx.update_editor(|editor, cx| {
+                let snapshot = editor.snapshot(cx);
+                let anchor = snapshot
+                    .buffer_snapshot
+                    .anchor_before(point.to_offset(&snapshot, Bias::Left));
+                hover_at(editor, Some(anchor), cx)
+            });
+        };
+        hover_point(
+            &mut cx,
+            indoc! {"
+                fn test() { a.ˇb().c(); }
+            "},
+        );
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_REQUEST_DELAY_MILLIS + 50));
+        cx.run_until_parked();
+        hover_point(
+            &mut cx,
+            indoc! {"
+                fn test() { a.b().ˇc(); }
+            "},
+        );
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.run_until_parked();
+
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 1);
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "Docs for c"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_min_symbol_length(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_min_symbol_length = Some(2);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn test(ˇi: u32) {}
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                request_count.fetch_add(1, atomic::Ordering::Release);
+                async move {
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "i: u32".to_string(),
+                        }),
+                        range: None,
+                    }))
+                }
+            }
+        });
+
+        cx.update_editor(|editor, cx| {
+            let anchor = editor.selections.newest_anchor().head();
+            hover_at(editor, Some(anchor), cx)
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 0);
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+
+        // The keyboard action shows the hover regardless of the symbol's length.
+        cx.dispatch_action(Hover);
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::Acquire), 1);
+        cx.editor(|editor, _| assert!(editor.hover_state.visible()));
+    }
+
+    #[gpui::test]
+    fn test_run_indicator_info(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let tasks = RunnableTasks {
+            templates: vec![
+                (
+                    TaskSourceKind::UserInput,
+                    TaskTemplate {
+                        label: "test it_works".to_string(),
+                        command: "cargo".to_string(),
+                        args: vec!["test".to_string(), "it_works".to_string()],
+                        ..Default::default()
+                    },
+                ),
+                (
+                    TaskSourceKind::UserInput,
+                    TaskTemplate {
+                        label: "run".to_string(),
+                        command: "cargo run".to_string(),
+                        ..Default::default()
+                    },
+                ),
+            ],
+            offset: crate::MultiBufferOffset(0),
+            column: 0,
+            extra_variables: Default::default(),
+            context_range: crate::BufferOffset(0)..crate::BufferOffset(0),
+        };
+        let buffer = cx.update(|cx| MultiBuffer::build_simple("fn it_works() {}", cx));
+        let buffer = cx.update(|cx| buffer.read(cx).snapshot(cx));
+        cx.update(|cx| assert_eq!(run_indicator_info(&tasks, &buffer, cx), None));
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_code_lens_info = Some(true);
+                });
+            });
+        });
+        cx.update(|cx| {
+            assert_eq!(
+                run_indicator_info(&tasks, &buffer, cx).as_deref(),
+                Some("test it_works: cargo test it_works\nrun: cargo run")
+            )
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_adjacent_symbols(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇfirst() { second(); third(); }
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, params, _| async move {
+            let docs = match params.text_document_position_params.position.character {
+                13 => "Docs for second",
+                23 => "Docs for third",
+                _ => "Docs for first",
+            };
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: docs.to_string(),
+                }),
+                range: None,
+            }))
+        });
+
+        cx.dispatch_action(HoverNextSymbol);
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            fn first() { ˇsecond(); third(); }
+        "});
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "Docs for second"
+            );
+        });
+
+        cx.dispatch_action(HoverNextSymbol);
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            fn first() { second(); ˇthird(); }
+        "});
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "Docs for third"
+            );
+        });
+
+        cx.dispatch_action(HoverPrevSymbol);
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            fn first() { ˇsecond(); third(); }
+        "});
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "Docs for second"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_adjacent_identifier_across_nodes(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let text = "fn outer() {\n    let a = b(c);\n}\nfn next() {}\n";
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(text, cx).with_language(Arc::new(crate::editor_tests::rust_lang()), cx)
+        });
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let offset_of = |needle: &str| text.find(needle).unwrap();
+
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("c)"), Direction::Next),
+            Some(offset_of("next"))
+        );
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("c)"), Direction::Prev),
+            Some(offset_of("b("))
+        );
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("next"), Direction::Prev),
+            Some(offset_of("c)"))
+        );
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("    let"), Direction::Prev),
+            Some(offset_of("outer"))
+        );
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("    let"), Direction::Next),
+            Some(offset_of("a ="))
+        );
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("outer"), Direction::Prev),
+            None
+        );
+        assert_eq!(
+            adjacent_identifier(&snapshot, offset_of("next"), Direction::Next),
+            None
+        );
+    }
+
+    #[gpui::test]
+    async fn test_diagnostic_stack_trace_collapsed(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_collapse_stack_traces = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: indoc! {"
+                            TypeError: value is undefined
+                                at parse (src/parse.js:10:5)
+                                at main (src/main.js:3:1)
+                        "}
+                        .to_string(),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            let (message, trace) = diagnostic_popover.message_and_trace(&buffer, cx);
+            assert_eq!(message, "TypeError: value is undefined");
+            assert_eq!(
+                trace.as_deref(),
+                Some("    at parse (src/parse.js:10:5)\n    at main (src/main.js:3:1)")
+            );
+            assert!(!*diagnostic_popover.trace_expanded.borrow());
+        });
+
+        assert_eq!(
+            split_stack_trace("expected `u32`, found `i32`\nat least one value needed"),
+            (
+                "expected `u32`, found `i32`\nat least one value needed",
+                None
+            )
+        );
+    }
+
+    #[gpui::test]
+    async fn test_diagnostic_empty_source(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "unused function".to_string(),
+                        source: Some(String::new()),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            let (message, _) = diagnostic_popover.message_and_trace(&buffer, cx);
+            assert_eq!(message, "unused function");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_diagnostic_hover_show_code(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "cannot borrow `v` as mutable".to_string(),
+                        source: Some("rustc".to_string()),
+                        code: Some("E0502".to_string()),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+        cx.editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert_eq!(diagnostic_popover.code(&buffer, cx), None);
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.diagnostic_hover_show_code = Some(true);
+                });
+            });
+        });
+        cx.editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            let (message, _) = diagnostic_popover.message_and_trace(&buffer, cx);
+            assert_eq!(message, "rustc: cannot borrow `v` as mutable");
+            assert_eq!(diagnostic_popover.code(&buffer, cx), Some("E0502"));
+            assert_eq!(diagnostic_popover.code_url(), None);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_diagnostic_code_url(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn teˇst() { println!(); }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "mismatched types".to_string(),
+                        source: Some("rustc".to_string()),
+                        code: Some("E0308".to_string()),
+                        code_description: Some(
+                            "https://doc.rust-lang.org/error_codes/E0308.html".to_string(),
+                        ),
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        // The code links to its documentation even when `diagnostic_hover_show_code` is off.
+        cx.editor(|editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert_eq!(diagnostic_popover.code(&buffer, cx), Some("E0308"));
+            assert_eq!(
+                diagnostic_popover.code_url(),
+                Some("https://doc.rust-lang.org/error_codes/E0308.html")
+            );
+        });
+
+        // Clicking it opens the documentation.
+        cx.update_editor(|_, cx| cx.notify());
+        cx.run_until_parked();
+        let code_bounds = cx.debug_bounds("diagnostic-code").unwrap();
+        cx.simulate_click(code_bounds.center(), Modifiers::none());
+        assert_eq!(
+            cx.opened_url(),
+            Some("https://doc.rust-lang.org/error_codes/E0308.html".into())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover_inlay_label_parts(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.inlay_hints = Some(InlayHintSettings {
+                enabled: true,
+                edit_debounce_ms: 0,
+                scroll_debounce_ms: 0,
+                show_type_hints: true,
+                show_parameter_hints: true,
+                show_other_hints: true,
+            })
+        });
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                inlay_hint_provider: Some(lsp::OneOf::Right(
+                    lsp::InlayHintServerCapabilities::Options(lsp::InlayHintOptions {
+                        resolve_provider: Some(true),
+                        ..Default::default()
+                    }),
+                )),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            struct TestStruct;
+
+            // ==================
+
+            struct TestNewType<T>(T);
+
+            fn main() {
+                let variableˇ = TestNewType(TestStruct);
+            }
+        "});
+
+        let hint_start_offset = cx.ranges(indoc! {"
+            struct TestStruct;
+
+            // ==================
+
+            struct TestNewType<T>(T);
+
+            fn main() {
+                let variableˇ = TestNewType(TestStruct);
+            }
+        "})[0]
+            .start;
+        let hint_position = cx.to_lsp(hint_start_offset);
+        let new_type_target_range = cx.lsp_range(indoc! {"
+            struct TestStruct;
+
+            // ==================
+
+            struct «TestNewType»<T>(T);
+
+            fn main() {
+                let variable = TestNewType(TestStruct);
+            }
+        "});
+        let struct_target_range = cx.lsp_range(indoc! {"
+            struct «TestStruct»;
+
+            // ==================
+
+            struct TestNewType<T>(T);
+
+            fn main() {
+                let variable = TestNewType(TestStruct);
+            }
+        "});
+
+        let uri = cx.buffer_lsp_url.clone();
+        let new_type_label = "TestNewType";
+        let struct_label = "TestStruct";
+        let entire_hint_label = ": TestNewType<TestStruct>";
+        let closure_uri = uri.clone();
+        cx.lsp
+            .handle_request::<lsp::request::InlayHintRequest, _, _>(move |params, _| {
+                let task_uri = closure_uri.clone();
+                async move {
+                    assert_eq!(params.text_document.uri, task_uri);
+                    Ok(Some(vec![lsp::InlayHint {
+                        position: hint_position,
+                        label: lsp::InlayHintLabel::LabelParts(vec![lsp::InlayHintLabelPart {
+                            value: entire_hint_label.to_string(),
+                            ..Default::default()
+                        }]),
+                        kind: Some(lsp::InlayHintKind::TYPE),
+                        text_edits: None,
+                        tooltip: None,
+                        padding_left: Some(false),
+                        padding_right: Some(false),
+                        data: None,
+                    }]))
+                }
+            })
+            .next()
+            .await;
+        cx.background_executor.run_until_parked();
+        cx.update_editor(|editor, cx| {
+            let expected_layers = vec![entire_hint_label.to_string()];
+            assert_eq!(expected_layers, cached_hint_labels(editor));
+            assert_eq!(expected_layers, visible_hint_labels(editor, cx));
+        });
+
+        let inlay_range = cx
+            .ranges(indoc! {"
+                struct TestStruct;
+
+                // ==================
+
+                struct TestNewType<T>(T);
+
+                fn main() {
+                    let variable« »= TestNewType(TestStruct);
+                }
+        "})
+            .get(0)
+            .cloned()
+            .unwrap();
+        let new_type_hint_part_hover_position = cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let previous_valid = inlay_range.start.to_display_point(&snapshot);
+            let next_valid = inlay_range.end.to_display_point(&snapshot);
+            assert_eq!(previous_valid.row(), next_valid.row());
+            assert!(previous_valid.column() < next_valid.column());
+            let exact_unclipped = DisplayPoint::new(
+                previous_valid.row(),
+                previous_valid.column()
+                    + (entire_hint_label.find(new_type_label).unwrap() + new_type_label.len() / 2)
+                        as u32,
+            );
+            PointForPosition {
+                previous_valid,
+                next_valid,
+                exact_unclipped,
+                column_overshoot_after_line_end: 0,
+            }
+        });
+        cx.update_editor(|editor, cx| {
+            update_inlay_link_and_hover_points(
+                &editor.snapshot(cx),
+                new_type_hint_part_hover_position,
+                editor,
+                true,
+                false,
+                cx,
+            );
+        });
+
+        let resolve_closure_uri = uri.clone();
+        cx.lsp
+            .handle_request::<lsp::request::InlayHintResolveRequest, _, _>(
+                move |mut hint_to_resolve, _| {
+                    let mut resolved_hint_positions = BTreeSet::new();
+                    let task_uri = resolve_closure_uri.clone();
+                    async move {
+                        let inserted = resolved_hint_positions.insert(hint_to_resolve.position);
+                        assert!(inserted, "Hint {hint_to_resolve:?} was resolved twice");
+
+                        // `: TestNewType<TestStruct>`
+                        hint_to_resolve.label = lsp::InlayHintLabel::LabelParts(vec![
+                            lsp::InlayHintLabelPart {
+                                value: ": ".to_string(),
+                                ..Default::default()
+                            },
+                            lsp::InlayHintLabelPart {
+                                value: new_type_label.to_string(),
+                                location: Some(lsp::Location {
+                                    uri: task_uri.clone(),
+                                    range: new_type_target_range,
+                                }),
+                                tooltip: Some(lsp::InlayHintLabelPartTooltip::String(format!(
+                                    "A tooltip for `{new_type_label}`"
+                                ))),
+                                ..Default::default()
+                            },
+                            lsp::InlayHintLabelPart {
+                                value: "<".to_string(),
+                                ..Default::default()
+                            },
+                            lsp::InlayHintLabelPart {
+                                value: struct_label.to_string(),
+                                location: Some(lsp::Location {
+                                    uri: task_uri,
+                                    range: struct_target_range,
+                                }),
+                                tooltip: Some(lsp::InlayHintLabelPartTooltip::MarkupContent(
+                                    lsp::MarkupContent {
+                                        kind: lsp::MarkupKind::Markdown,
+                                        value: format!("A tooltip for `{struct_label}`"),
+                                    },
+                                )),
+                                ..Default::default()
+                            },
+                            lsp::InlayHintLabelPart {
+                                value: ">".to_string(),
+                                ..Default::default()
+                            },
+                        ]);
+
+                        Ok(hint_to_resolve)
+                    }
+                },
+            )
+            .next()
+            .await;
+        cx.background_executor.run_until_parked();
+
+        cx.update_editor(|editor, cx| {
+            update_inlay_link_and_hover_points(
+                &editor.snapshot(cx),
+                new_type_hint_part_hover_position,
+                editor,
+                true,
+                false,
+                cx,
+            );
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.background_executor.run_until_parked();
+        cx.update_editor(|editor, cx| {
+            let hover_state = &editor.hover_state;
+            assert!(
+                hover_state.diagnostic_popover.is_none() && hover_state.info_popovers.len() == 1
+            );
+            let popover = hover_state.info_popovers.first().cloned().unwrap();
+            let buffer_snapshot = editor.buffer().update(cx, |buffer, cx| buffer.snapshot(cx));
+            assert_eq!(
+                popover.symbol_range,
+                RangeInEditor::Inlay(InlayHighlight {
+                    inlay: InlayId::Hint(0),
+                    inlay_position: buffer_snapshot.anchor_at(inlay_range.start, Bias::Right),
+                    range: ": ".len()..": ".len() + new_type_label.len(),
+                }),
+                "Popover range should match the new type label part"
+            );
+            assert_eq!(
+                popover.get_rendered_text(cx),
+                format!("A tooltip for {new_type_label}"),
+            );
+        });
+
+        let struct_hint_part_hover_position = cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let previous_valid = inlay_range.start.to_display_point(&snapshot);
+            let next_valid = inlay_range.end.to_display_point(&snapshot);
+            assert_eq!(previous_valid.row(), next_valid.row());
+            assert!(previous_valid.column() < next_valid.column());
+            let exact_unclipped = DisplayPoint::new(
+                previous_valid.row(),
+                previous_valid.column()
+                    + (entire_hint_label.find(struct_label).unwrap() + struct_label.len() / 2)
+                        as u32,
+            );
+            PointForPosition {
+                previous_valid,
+                next_valid,
+                exact_unclipped,
+                column_overshoot_after_line_end: 0,
+            }
+        });
+        cx.update_editor(|editor, cx| {
+            update_inlay_link_and_hover_points(
+                &editor.snapshot(cx),
+                struct_hint_part_hover_position,
+                editor,
+                true,
+                false,
+                cx,
+            );
+        });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        cx.background_executor.run_until_parked();
+        cx.update_editor(|editor, cx| {
+            let hover_state = &editor.hover_state;
+            assert!(
+                hover_state.diagnostic_popover.is_none() && hover_state.info_popovers.len() == 1
+            );
+            let popover = hover_state.info_popovers.first().cloned().unwrap();
+            let buffer_snapshot = editor.buffer().update(cx, |buffer, cx| buffer.snapshot(cx));
+            assert_eq!(
+                popover.symbol_range,
+                RangeInEditor::Inlay(InlayHighlight {
+                    inlay: InlayId::Hint(0),
+                    inlay_position: buffer_snapshot.anchor_at(inlay_range.start, Bias::Right),
+                    range: ": ".len() + new_type_label.len() + "<".len()
+                        ..": ".len() + new_type_label.len() + "<".len() + struct_label.len(),
+                }),
+                "Popover range should match the struct label part"
+            );
+            assert_eq!(
+                popover.get_rendered_text(cx),
+                format!("A tooltip for {struct_label}"),
+                "Rendered markdown element should remove backticks from text"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_rendered_snapshot(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let syntax = Arc::new(SyntaxTheme::new_test([("string", gpui::red())]));
+        cx.update(|cx| {
+            let mut theme_settings = ThemeSettings::get_global(cx).clone();
+            let mut theme = (*theme_settings.active_theme).clone();
+            theme.styles.syntax = syntax.clone();
+            theme_settings.active_theme = Arc::new(theme);
+            ThemeSettings::override_global(theme_settings, cx);
+        });
+        let typescript = Language::new(
+            LanguageConfig {
+                name: "TypeScript".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_typescript::language_typescript()),
+        )
+        .with_highlights_query("(string) @string")
+        .unwrap();
+        typescript.set_theme(&syntax);
+        cx.language_registry().add(Arc::new(typescript));
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: indoc! {"
+                            Adds **one** to [a number](https://example.com/number).
+
+                            ```typescript
+                            let n = \"one\";
+                            ```
+                        "}
+                        .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_snapshot(cx),
+                indoc! {"
+                    Adds «one» to «a number».
+                    let n = «\"one\"»;
+                    «one»: bold
+                    «a number»: underline
+                    «\"one\"»: string"}
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_text_align(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn tesˇt() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "Adds one to a number.\n\n```rust\nfn test()\n```".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
+        let text_align = |cx: &mut EditorLspTestContext| {
+            cx.editor(|editor, cx| {
+                let markdown = editor.hover_state.info_popovers[0]
+                    .parsed_content
+                    .clone()
+                    .unwrap();
+                markdown.read(cx).style().text_align
+            })
+        };
+
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        assert_eq!(text_align(&mut cx), TextAlign::Left);
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_text_align = Some(HoverTextAlign::Justify);
+                });
+            });
+        });
+        cx.editor(|editor, _| assert!(editor.hover_state.visible()));
+        assert_eq!(text_align(&mut cx), TextAlign::Justify);
+    }
+
+    #[gpui::test]
+    async fn test_hover_cache_across_excerpts(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_cache = Some(true);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/a",
+            json!({ "main.rs": "fn test() {}\nfn main() {\n    test();\n}\n" }),
+        )
+        .await;
+        let project = Project::test(fs, ["/a".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(crate::editor_tests::rust_lang());
+        let mut fake_servers = language_registry.register_fake_lsp_adapter(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/a/main.rs", cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+        let request_count = Arc::new(AtomicUsize::new(0));
+        fake_server.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _| {
+                request_count.fetch_add(1, atomic::Ordering::SeqCst);
+                async move {
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "some basic docs".to_string(),
+                        }),
+                        range: None,
+                    }))
+                }
+            }
+        });
+
+        // Two excerpts of the same buffer, both containing the definition of `test`.
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+        let excerpt_ids = multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts(
+                buffer.clone(),
+                [
+                    ExcerptRange {
+                        context: Point::new(0, 0)..Point::new(0, 12),
+                        primary: None,
+                    },
+                    ExcerptRange {
+                        context: Point::new(0, 0)..Point::new(2, 11),
+                        primary: None,
+                    },
+                ],
+                cx,
+            )
+        });
+        let editor = cx
+            .add_window(|cx| Editor::for_multibuffer(multibuffer, Some(project.clone()), true, cx));
+        cx.executor().run_until_parked();
+
+        for excerpt_id in excerpt_ids {
+            editor
+                .update(cx, |editor, cx| {
+                    let text_anchor = buffer.read(cx).anchor_before(Point::new(0, 4));
+                    let anchor = editor
+                        .buffer()
+                        .read(cx)
+                        .snapshot(cx)
+                        .anchor_in_excerpt(excerpt_id, text_anchor)
+                        .unwrap();
+                    hide_hover(editor, cx);
+                    show_hover(editor, anchor, None, true, cx);
+                })
+                .unwrap();
+            cx.executor().run_until_parked();
+
+            editor
+                .update(cx, |editor, cx| {
+                    assert!(editor.hover_state.visible());
+                    let markdown = editor.hover_state.info_popovers[0]
+                        .parsed_content
+                        .clone()
+                        .unwrap();
+                    assert_eq!(markdown.read(cx).source(), "some basic docs");
+                })
+                .unwrap();
+            assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+        }
+    }
+
+    #[gpui::test]
+    async fn test_hover_on_modifier_click(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_on_modifier_click = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let click_point = cx.pixel_position(indoc! {"
+            fn teˇst() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        cx.handle_request::<lsp::request::GotoDefinition, _, _>(
+            move |_, _, _| async move { Ok(None) },
+        );
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+
+        // Without a definition to go to, the click shows the hover without waiting for the delay
+        cx.simulate_click(click_point, Modifiers::secondary_key());
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            assert!(editor.hover_state.visible());
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(markdown.read(cx).source(), "some basic docs");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_empty_fallback(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_empty_fallback = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn tesˇt() { test(); testing(); }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(None)
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(
+                markdown.read(cx).source(),
+                "`test` appears 2 times in this buffer"
+            );
+        });
+        cx.assert_editor_background_highlights::<HoverState>(indoc! {"
+            fn «test»() { test(); testing(); }
+        "});
+
+        // Hovers with documentation don't count the word's occurrences.
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "Docs for test".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            assert_eq!(editor.hover_state.info_popovers.len(), 1);
+            assert_eq!(
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "Docs for test"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_hover_const_value(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_const_value = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            const MAX: u32 = 2 * 5;
+            fn test() -> u32 { MAˇX }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "```rust\nconst MAX: u32 = 10 (0xA)\n```\n\n---\n\nThe maximum."
+                            .to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(
+                markdown.read(cx).source(),
+                "**`MAX = 10 (0xA)`**\n\n```rust\nconst MAX: u32 = 10 (0xA)\n```\n\n---\n\nThe maximum."
+            );
+        });
+    }
+
+    #[test]
+    fn test_const_value_from_hover_signature() {
+        let blocks = [HoverBlock {
+            text: "```rust\nmy_crate\n```\n\n```rust\npub const MAX: u32 = 10\n```\n\n---\n\n```rust\nconst MIN: u32 = 0;\n```".to_string(),
+            kind: project::HoverBlockKind::Markdown,
+        }];
+        assert_eq!(
+            const_value_from_hover(&blocks, "MAX"),
+            Some(("MAX".to_string(), "10".to_string()))
+        );
+        // Only the signature the hover starts with declares the hovered constant.
+        assert_eq!(const_value_from_hover(&blocks, "MIN"), None);
+        assert_eq!(const_value_from_hover(&blocks, "my_crate"), None);
+
+        let blocks = [HoverBlock {
+            text: "const MIN: u32 = 0;".to_string(),
+            kind: project::HoverBlockKind::Code {
+                language: "rust".to_string(),
+            },
+        }];
+        assert_eq!(
+            const_value_from_hover(&blocks, "MIN"),
+            Some(("MIN".to_string(), "0".to_string()))
+        );
+    }
+
+    #[gpui::test]
+    fn test_const_initializer_in_enclosing_scopes(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let text = indoc! {"
+            fn first() { const LIMIT: u32 = 1; }
+            fn second() -> u32 { LIMIT }
+            const OTHER: u32 = 2;
+            fn third() -> u32 { const INNER: u32 = 3; OTHER + INNER }
+        "};
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(text, cx).with_language(Arc::new(crate::editor_tests::rust_lang()), cx)
+        });
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let initializer_at = |needle: &str| {
+            let offset = text.rfind(needle).unwrap();
+            const_initializer_at(&snapshot, snapshot.anchor_before(offset))
+        };
+
+        // Constants declared in other functions aren't in scope.
+        assert_eq!(initializer_at("LIMIT"), None);
+        assert_eq!(
+            initializer_at("OTHER"),
+            Some(("OTHER".to_string(), "2".to_string()))
+        );
+        assert_eq!(
+            initializer_at("INNER"),
+            Some(("INNER".to_string(), "3".to_string()))
+        );
+    }
+
+    #[gpui::test]
+    async fn test_hover_pin_hint(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.set_global(HoverPinCount(Some(0)));
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_show_hints = Some(true);
+                });
+            });
+        });
+        let pin_hint = |cx: &mut ViewContext<Editor>| {
+            let show_hints = EditorSettings::get_global(cx).hover_show_hints;
+            render_pin_hint(show_hints, cx).is_some()
+        };
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        // Without a keybinding for pinning, there is nothing to hint at.
+        cx.update_editor(|_, cx| assert!(!pin_hint(cx)));
+        cx.update(|cx| cx.bind_keys([gpui::KeyBinding::new("ctrl-k ctrl-p", PinHover, None)]));
+        cx.update_editor(|_, cx| assert!(pin_hint(cx)));
+
+        // The hint hides once popovers have been pinned a few times.
+        cx.update(|cx| cx.set_global(HoverPinCount(Some(HOVER_PIN_HINT_LIMIT))));
+        cx.update_editor(|_, cx| assert!(!pin_hint(cx)));
+
+        // Hints are opt-in, and not shown while the pin count is loading.
+        cx.update(|cx| cx.set_global(HoverPinCount(None)));
+        cx.update_editor(|_, cx| assert!(!pin_hint(cx)));
+        cx.update(|cx| {
+            cx.set_global(HoverPinCount(Some(0)));
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_show_hints = None;
+                });
+            });
+        });
+        cx.update_editor(|_, cx| assert!(!pin_hint(cx)));
+    }
+
+    #[gpui::test]
+    async fn test_configured_hover_delay(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+            fn other() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, params, _| async move {
+                let docs = match params.text_document_position_params.position.line {
+                    0 => "some basic docs",
+                    _ => "other docs",
+                };
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: docs.to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        let hover_point = cx.display_point(indoc! {"
+            fn tˇest() {}
+            fn other() {}
+        "});
+        let hover_with_delay = |cx: &mut EditorLspTestContext, delay: Option<u64>| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|settings, cx| {
+                    settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                        settings.hover_popover_delay = delay;
+                    });
+                });
+            });
+            cx.update_editor(|editor, cx| {
+                hide_hover(editor, cx);
+                let snapshot = editor.snapshot(cx);
+                let anchor = snapshot
+                    .buffer_snapshot
+                    .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+                hover_at(editor, Some(anchor), cx)
+            });
+        };
+
+        // A small delay shows the popover before the default one would.
+        hover_with_delay(&mut cx, Some(50));
+        cx.background_executor
+            .advance_clock(Duration::from_millis(100));
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+
+        hover_with_delay(&mut cx, None);
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_REQUEST_DELAY_MILLIS + 50));
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+        cx.background_executor.advance_clock(Duration::from_millis(
+            HOVER_DELAY_MILLIS - HOVER_REQUEST_DELAY_MILLIS,
+        ));
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // No delay shows the popover without waiting.
+        hover_with_delay(&mut cx, Some(0));
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+
+        // Moving the mouse to another symbol hides the popover right away, and the new one
+        // still hides once the mouse leaves it, unlike popovers shown from the keyboard.
+        let other_point = cx.display_point(indoc! {"
+            fn test() {}
+            fn oˇther() {}
+        "});
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(other_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx);
+            assert!(!editor.hover_state.visible());
+        });
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            assert_eq!(editor.hover_state.info_popovers.len(), 1);
+            let info_popover = &editor.hover_state.info_popovers[0];
+            assert_eq!(info_popover.get_rendered_text(cx), "other docs");
+            assert!(!*info_popover.keyboard_grace.borrow());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_cancel_hides_hover_before_selections(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn «testˇ»() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        // Escape first hides the popover, keeping the selection.
+        cx.dispatch_action(Cancel);
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+        cx.assert_editor_state(indoc! {"
+            fn «testˇ»() {}
+        "});
+
+        // Without a popover, it behaves as usual.
+        cx.dispatch_action(Cancel);
+        cx.assert_editor_state(indoc! {"
+            fn testˇ() {}
+        "});
+    }
+
+    #[gpui::test]
+    async fn test_pin_hover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.set_global(HoverPinCount(Some(0)));
+            cx.bind_keys([gpui::KeyBinding::new("ctrl-k ctrl-p", PinHover, None)]);
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_show_hints = Some(true);
+                });
+            });
+        });
+        let pin_hint = |cx: &mut ViewContext<Editor>| {
+            let show_hints = EditorSettings::get_global(cx).hover_show_hints;
+            render_pin_hint(show_hints, cx).is_some()
+        };
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+
+        // A pinned popover stays when the mouse moves away.
+        cx.dispatch_action(PinHover);
+        cx.update_editor(|editor, cx| {
+            assert!(editor.hover_state.pinned);
+            hover_at(editor, None, cx);
+            assert!(editor.hover_state.visible());
+        });
 
-        let hover_point = cx.display_point(indoc! {"
-                one.
-                two
-                three
-                fn test() { printˇln!(); }
-            "});
+        // It also stays when the cursor moves or the editor scrolls.
         cx.update_editor(|editor, cx| {
-            let snapshot = editor.snapshot(cx);
-            let anchor = snapshot
-                .buffer_snapshot
-                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
-            hover_at(editor, Some(anchor), cx)
+            editor.change_selections(None, cx, |s| s.select_ranges([0..0]));
+            assert!(editor.hover_state.visible());
+            editor.set_scroll_position(gpui::Point::new(0., 1.), cx);
+            assert!(editor.hover_state.visible());
+            assert!(editor.hover_state.pinned);
         });
-        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
 
-        // After delay, hover should be visible.
+        // Pinning again unpins it.
+        cx.dispatch_action(PinHover);
+        cx.editor(|editor, _| assert!(!editor.hover_state.pinned));
+
+        // The hint hides once popovers have been pinned a few times.
+        for _ in 1..HOVER_PIN_HINT_LIMIT {
+            cx.update_editor(|_, cx| assert!(pin_hint(cx)));
+            cx.dispatch_action(PinHover);
+            cx.dispatch_action(PinHover);
+        }
+        cx.update_editor(|_, cx| assert!(!pin_hint(cx)));
+    }
+
+    #[gpui::test]
+    async fn test_pinned_hover_hides_on_symbol_edit(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| cx.set_global(HoverPinCount(Some(0))));
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+            fn other() {}
+        "});
         let symbol_range = cx.lsp_range(indoc! {"
-                one.
-                two
-                three
-                fn test() { «println!»(); }
-            "});
+            fn «test»() {}
+            fn other() {}
+        "});
         let mut requests =
             cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
                 Ok(Some(lsp::Hover {
@@ -791,130 +11807,441 @@ mod tests {
                     range: Some(symbol_range),
                 }))
             });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.dispatch_action(PinHover);
+
+        // Edits elsewhere keep the pinned popover.
+        cx.update_buffer(|buffer, cx| buffer.edit([(17..17, "_")], None, cx));
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            assert!(editor.hover_state.pinned);
+            assert!(editor.hover_state.visible());
+        });
+
+        // Editing the hovered symbol hides it.
+        cx.update_buffer(|buffer, cx| buffer.edit([(5..5, "_")], None, cx));
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            assert!(!editor.hover_state.pinned);
+            assert!(!editor.hover_state.visible());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_mouse_moving_into_hover_popover(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "some basic docs".to_string(),
+                    }),
+                    range: None,
+                }))
+            });
+        let hover_point = cx.display_point(indoc! {"
+            fn tˇest() {}
+        "});
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
+        });
         cx.background_executor
             .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
         requests.next().await;
+        cx.run_until_parked();
+        assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+
+        cx.update_editor(|editor, cx| {
+            editor.hover_state.popover_bounds = vec![Bounds::new(
+                gpui::point(px(100.), px(100.)),
+                gpui::size(px(200.), px(50.)),
+            )];
+
+            // Moving through the gap below the popover and into it keeps it visible.
+            for y in [px(155.), px(125.)] {
+                editor
+                    .hover_state
+                    .update_mouse_over_popover(gpui::point(px(150.), y));
+                assert!(editor.hover_state.mouse_over_popover);
+                hover_at(editor, None, cx);
+                assert!(editor.hover_state.visible());
+            }
+
+            // Moving away hides it.
+            editor
+                .hover_state
+                .update_mouse_over_popover(gpui::point(px(150.), px(300.)));
+            assert!(!editor.hover_state.mouse_over_popover);
+            hover_at(editor, None, cx);
+            assert!(!editor.hover_state.visible());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_stacked_diagnostics(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn tˇest() { println!(); }
+        "});
+        let error_range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        let warning_range = cx.text_anchor_range(indoc! {"
+            fn «te»st() { println!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![
+                    DiagnosticEntry {
+                        range: error_range,
+                        diagnostic: Diagnostic {
+                            message: "mismatched types".to_string(),
+                            severity: DiagnosticSeverity::ERROR,
+                            group_id: 0,
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: warning_range,
+                        diagnostic: Diagnostic {
+                            message: "unused variable".to_string(),
+                            severity: DiagnosticSeverity::WARNING,
+                            group_id: 1,
+                            ..Default::default()
+                        },
+                    },
+                ],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
 
+        // The error comes first even though the warning's range is more specific.
         cx.editor(|editor, cx| {
-            assert!(editor.hover_state.visible());
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            let (message, _) = diagnostic_popover.message_and_trace(&buffer, cx);
+            assert_eq!(message, "mismatched types");
             assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                1,
-                "Expected exactly one hover but got: {:?}",
-                editor.hover_state.info_popovers
+                diagnostic_popover.stacked_messages(),
+                vec!["unused variable"]
             );
-            let rendered_text = editor
-                .hover_state
-                .info_popovers
-                .first()
-                .unwrap()
-                .get_rendered_text(cx);
-            assert_eq!(rendered_text, "some basic docs".to_string())
+            assert_eq!(diagnostic_popover.activation_info().0, 0);
         });
+    }
 
-        // check that the completion menu is still visible and that there still has only been 1 completion request
-        cx.editor(|editor, _| assert!(editor.context_menu_visible()));
-        assert_eq!(counter.load(atomic::Ordering::Acquire), 1);
+    #[gpui::test]
+    async fn test_diagnostic_related_information(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
 
-        //apply a completion and check it was successfully applied
-        let _apply_additional_edits = cx.update_editor(|editor, cx| {
-            editor.context_menu_next(&Default::default(), cx);
-            editor
-                .confirm_completion(&ConfirmCompletion::default(), cx)
-                .unwrap()
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let fs = cx.update_workspace(|workspace, _| workspace.app_state().fs.clone());
+        fs.as_fake()
+            .insert_file("/root/dir/other.rs", "fn one() {}\nfn other() {}\n".into())
+            .await;
+
+        cx.set_state(indoc! {"
+            fn tˇest() { println!(); }
+        "});
+        let stacked_range = cx.text_anchor_range(indoc! {"
+            «fn test»() { println!(); }
+        "});
+        let primary_range = cx.text_anchor_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
+        let related_range = cx.text_anchor_range(indoc! {"
+            fn test() { «println»!(); }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![
+                    DiagnosticEntry {
+                        range: stacked_range,
+                        diagnostic: Diagnostic {
+                            message: "consider renaming".to_string(),
+                            severity: DiagnosticSeverity::HINT,
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: primary_range,
+                        diagnostic: Diagnostic {
+                            message: "mismatched types".to_string(),
+                            severity: DiagnosticSeverity::ERROR,
+                            is_primary: true,
+                            related_locations: vec![DiagnosticRelatedLocation {
+                                abs_path: PathBuf::from("/root/dir/other.rs"),
+                                position: Unclipped(PointUtf16::new(1, 3)),
+                                message: "other defined here".to_string(),
+                            }],
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: related_range,
+                        diagnostic: Diagnostic {
+                            message: "expected due to this".to_string(),
+                            severity: DiagnosticSeverity::INFORMATION,
+                            ..Default::default()
+                        },
+                    },
+                ],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        cx.background_executor.run_until_parked();
+
+        // The stacked diagnostic of the group isn't repeated as related information.
+        let targets = cx.editor(|editor, _| {
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert_eq!(
+                diagnostic_popover.stacked_messages(),
+                vec!["consider renaming"]
+            );
+            let related_information = &diagnostic_popover.related_information;
+            assert_eq!(
+                related_information
+                    .iter()
+                    .map(|info| (info.message.as_str(), info.location.as_str()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    ("expected due to this", "line 1"),
+                    ("other defined here", "other.rs:2")
+                ]
+            );
+            related_information
+                .iter()
+                .map(|info| info.target.clone())
+                .collect::<Vec<_>>()
         });
+
+        cx.update_editor(|editor, cx| jump_to_related_information(editor, targets[0].clone(), cx));
         cx.assert_editor_state(indoc! {"
-            one.second_completionˇ
-            two
-            three
-            fn test() { println!(); }
+            fn test() { ˇprintln!(); }
         "});
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
 
-        // check that the completion menu is no longer visible and that there still has only been 1 completion request
-        cx.editor(|editor, _| assert!(!editor.context_menu_visible()));
-        assert_eq!(counter.load(atomic::Ordering::Acquire), 1);
-
-        //verify the information popover is still visible and unchanged
-        cx.editor(|editor, cx| {
-            assert!(editor.hover_state.visible());
+        // Locations in other files are opened in the workspace.
+        cx.update_editor(|editor, cx| jump_to_related_information(editor, targets[1].clone(), cx));
+        cx.run_until_parked();
+        cx.update_workspace(|workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let editor = editor.read(cx);
+            assert_eq!(editor.text(cx), "fn one() {}\nfn other() {}\n");
             assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                1,
-                "Expected exactly one hover but got: {:?}",
-                editor.hover_state.info_popovers
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(1, 3)
             );
-            let rendered_text = editor
-                .hover_state
-                .info_popovers
-                .first()
-                .unwrap()
-                .get_rendered_text(cx);
+        });
+    }
 
-            assert_eq!(rendered_text, "some basic docs".to_string())
+    #[gpui::test]
+    async fn test_hover_diagnostic_code_actions(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn test() -> u32 { 1uˇ64 }
+        "});
+        let range = cx.text_anchor_range(indoc! {"
+            fn test() -> u32 { «1u64» }
+        "});
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "mismatched types".to_string(),
+                        severity: DiagnosticSeverity::ERROR,
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
         });
 
-        // Mouse moved with no hover response dismisses
-        let hover_point = cx.display_point(indoc! {"
-                one.second_completionˇ
-                two
-                three
-                fn teˇst() { println!(); }
-            "});
-        let mut request = cx
+        let has_actions = Arc::new(atomic::AtomicBool::new(false));
+        let mut code_action_requests =
+            cx.handle_request::<lsp::request::CodeActionRequest, _, _>({
+                let has_actions = has_actions.clone();
+                move |_, _, _| {
+                    let has_actions = has_actions.load(atomic::Ordering::SeqCst);
+                    async move {
+                        Ok(Some(if has_actions {
+                            vec![lsp::CodeActionOrCommand::CodeAction(lsp::CodeAction {
+                                title: "Change return type to u64".to_string(),
+                                kind: Some(lsp::CodeActionKind::QUICKFIX),
+                                ..Default::default()
+                            })]
+                        } else {
+                            Vec::new()
+                        }))
+                    }
+                }
+            });
+        let mut hover_requests = cx
             .lsp
             .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        // Without code actions, there is no button.
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        code_action_requests.next().await;
+        hover_requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert!(!diagnostic_popover.has_code_actions);
+        });
+
+        has_actions.store(true, atomic::Ordering::SeqCst);
         cx.update_editor(|editor, cx| {
-            let snapshot = editor.snapshot(cx);
-            let anchor = snapshot
-                .buffer_snapshot
-                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
-            hover_at(editor, Some(anchor), cx)
+            hide_hover(editor, cx);
+            hover(editor, &Hover, cx);
+        });
+        code_action_requests.next().await;
+        hover_requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert!(diagnostic_popover.has_code_actions);
         });
-        cx.background_executor
-            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
-        request.next().await;
 
-        // verify that the information popover is no longer visible
+        // The button opens the code actions at the start of the diagnostic.
+        cx.update_editor(|editor, cx| {
+            let target = editor
+                .hover_state
+                .diagnostic_popover
+                .as_ref()
+                .unwrap()
+                .local_diagnostic
+                .range
+                .start;
+            show_diagnostic_code_actions(editor, target, cx);
+        });
+        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
+        cx.assert_editor_state(indoc! {"
+            fn test() -> u32 { ˇ1u64 }
+        "});
+        cx.executor().advance_clock(CODE_ACTIONS_DEBOUNCE_TIMEOUT);
+        code_action_requests.next().await;
+        cx.run_until_parked();
         cx.editor(|editor, _| {
-            assert!(!editor.hover_state.visible());
+            assert!(matches!(
+                editor.context_menu.read().as_ref(),
+                Some(ContextMenu::CodeActions(_))
+            ));
         });
     }
 
     #[gpui::test]
-    async fn test_mouse_hover_info_popover(cx: &mut gpui::TestAppContext) {
+    async fn test_hover_diagnostic_code_actions_dont_delay_info(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
 
         let mut cx = EditorLspTestContext::new_rust(
             lsp::ServerCapabilities {
                 hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
                 ..Default::default()
             },
             cx,
         )
         .await;
 
-        // Basic hover delays and then pops without moving the mouse
         cx.set_state(indoc! {"
-            fn ˇtest() { println!(); }
+            fn test() -> u32 { 1uˇ64 }
         "});
-        let hover_point = cx.display_point(indoc! {"
-            fn test() { printˇln!(); }
+        let range = cx.text_anchor_range(indoc! {"
+            fn test() -> u32 { «1u64» }
         "});
-
-        cx.update_editor(|editor, cx| {
-            let snapshot = editor.snapshot(cx);
-            let anchor = snapshot
-                .buffer_snapshot
-                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
-            hover_at(editor, Some(anchor), cx)
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        message: "mismatched types".to_string(),
+                        severity: DiagnosticSeverity::ERROR,
+                        ..Default::default()
+                    },
+                }],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
         });
-        assert!(!cx.editor(|editor, _| editor.hover_state.visible()));
 
-        // After delay, hover should be visible.
+        // The language server is slow to answer with code actions.
+        let mut code_action_requests = cx.handle_request::<lsp::request::CodeActionRequest, _, _>(
+            move |_, _, cx| async move {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+                Ok(Some(vec![lsp::CodeActionOrCommand::CodeAction(
+                    lsp::CodeAction {
+                        title: "Change return type to u64".to_string(),
+                        kind: Some(lsp::CodeActionKind::QUICKFIX),
+                        ..Default::default()
+                    },
+                )]))
+            },
+        );
         let symbol_range = cx.lsp_range(indoc! {"
-            fn test() { «println!»(); }
+            fn test() -> u32 { «1u64» }
         "});
-        let mut requests =
+        let mut hover_requests =
             cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
                 Ok(Some(lsp::Hover {
                     contents: lsp::HoverContents::Markup(lsp::MarkupContent {
@@ -924,52 +12251,116 @@ mod tests {
                     range: Some(symbol_range),
                 }))
             });
-        cx.background_executor
-            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
-        requests.next().await;
 
+        // The info popover is shown without waiting for the code actions.
+        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
+        code_action_requests.next().await;
+        hover_requests.next().await;
+        cx.run_until_parked();
         cx.editor(|editor, cx| {
-            assert!(editor.hover_state.visible());
             assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                1,
-                "Expected exactly one hover but got: {:?}",
-                editor.hover_state.info_popovers
+                editor.hover_state.info_popovers[0].get_rendered_text(cx),
+                "some basic docs"
             );
-            let rendered_text = editor
-                .hover_state
-                .info_popovers
-                .first()
-                .unwrap()
-                .get_rendered_text(cx);
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert!(!diagnostic_popover.has_code_actions);
+        });
 
-            assert_eq!(rendered_text, "some basic docs".to_string())
+        // The code actions are filled in once the language server answers.
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+            assert!(diagnostic_popover.has_code_actions);
         });
+    }
 
-        // Mouse moved with no hover response dismisses
-        let hover_point = cx.display_point(indoc! {"
-            fn teˇst() { println!(); }
-        "});
-        let mut request = cx
-            .lsp
-            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
-        cx.update_editor(|editor, cx| {
-            let snapshot = editor.snapshot(cx);
-            let anchor = snapshot
-                .buffer_snapshot
-                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
-            hover_at(editor, Some(anchor), cx)
+    #[gpui::test]
+    async fn test_mouse_hover_diagnostic_without_symbol(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_min_symbol_length = Some(2);
+                });
+            });
         });
-        cx.background_executor
-            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
-        request.next().await;
-        cx.editor(|editor, _| {
-            assert!(!editor.hover_state.visible());
+
+        cx.set_state("fn test(ˇi: u32) {}  \n");
+        let unused_range = cx.text_anchor_range("fn test(«i»: u32) {}  \n");
+        let whitespace_range = cx.text_anchor_range("fn test(i: u32) {}«  »\n");
+        cx.update_buffer(|buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let set = DiagnosticSet::from_sorted_entries(
+                vec![
+                    DiagnosticEntry {
+                        range: unused_range,
+                        diagnostic: Diagnostic {
+                            message: "unused variable".to_string(),
+                            severity: DiagnosticSeverity::WARNING,
+                            group_id: 0,
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: whitespace_range,
+                        diagnostic: Diagnostic {
+                            message: "trailing whitespace".to_string(),
+                            severity: DiagnosticSeverity::WARNING,
+                            group_id: 1,
+                            ..Default::default()
+                        },
+                    },
+                ],
+                &snapshot,
+            );
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
         });
+        let mut requests = cx
+            .lsp
+            .handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move { Ok(None) });
+
+        // Hovering an underline shows its diagnostic, whether it underlines something
+        // other than a symbol or a symbol too short to be hovered otherwise.
+        for (marked_text, expected_message) in [
+            ("fn test(i: u32) {} ˇ \n", "trailing whitespace"),
+            ("fn test(ˇi: u32) {}  \n", "unused variable"),
+        ] {
+            let hover_point = cx.display_point(marked_text);
+            cx.update_editor(|editor, cx| {
+                hide_hover(editor, cx);
+                let snapshot = editor.snapshot(cx);
+                let anchor = snapshot
+                    .buffer_snapshot
+                    .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+                hover_at(editor, Some(anchor), cx)
+            });
+            cx.background_executor
+                .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+            requests.next().await;
+            cx.run_until_parked();
+
+            cx.editor(|editor, cx| {
+                let buffer = editor.buffer().read(cx).snapshot(cx);
+                assert!(editor.hover_state.info_popovers.is_empty());
+                let diagnostic_popover = editor.hover_state.diagnostic_popover.as_ref().unwrap();
+                let (message, _) = diagnostic_popover.message_and_trace(&buffer, cx);
+                assert_eq!(message, expected_message);
+            });
+        }
     }
 
     #[gpui::test]
-    async fn test_keyboard_hover_info_popover(cx: &mut gpui::TestAppContext) {
+    async fn test_superseded_hover_request_is_cancelled(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
 
         let mut cx = EditorLspTestContext::new_rust(
@@ -981,62 +12372,232 @@ mod tests {
         )
         .await;
 
-        // Hover with keyboard has no delay
         cx.set_state(indoc! {"
-            fˇn test() { println!(); }
-        "});
-        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
-        let symbol_range = cx.lsp_range(indoc! {"
-            «fn» test() { println!(); }
+            fn ˇfirst() {}
+            fn second() {}
         "});
+        // The server is slow to answer for the first symbol.
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, params, cx| {
+                let executor = cx.background_executor().clone();
+                async move {
+                    let first = params.text_document_position_params.position.line == 0;
+                    if first {
+                        executor.timer(Duration::from_secs(1)).await;
+                    }
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: if first { "first docs" } else { "second docs" }.to_string(),
+                        }),
+                        range: None,
+                    }))
+                }
+            });
+        let mut cancellations = cx
+            .lsp
+            .handle_notification::<lsp::notification::Cancel, _>(|_, _| {});
 
-        cx.editor(|editor, _cx| {
-            assert!(!editor.hover_state.visible());
+        cx.dispatch_action(Hover);
+        cx.run_until_parked();
+        cx.set_selections_state(indoc! {"
+            fn first() {}
+            fn ˇsecond() {}
+        "});
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.run_until_parked();
 
-            assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                0,
-                "Expected no hovers but got but got: {:?}",
-                editor.hover_state.info_popovers
-            );
+        // Hovering the second symbol cancels the request for the first one, which
+        // isn't shown even once the server would have answered it.
+        cancellations.next().await;
+        cx.background_executor.advance_clock(Duration::from_secs(2));
+        cx.run_until_parked();
+        cx.editor(|editor, cx| {
+            assert_eq!(editor.hover_state.info_popovers.len(), 1);
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(markdown.read(cx).source(), "second docs");
         });
+    }
+
+    #[gpui::test]
+    async fn test_dismissed_hover_request_is_cancelled(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
 
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        // The server is slow to answer.
         let mut requests =
-            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
-                Ok(Some(lsp::Hover {
-                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
-                        kind: lsp::MarkupKind::Markdown,
-                        value: "some other basic docs".to_string(),
-                    }),
-                    range: Some(symbol_range),
-                }))
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, cx| {
+                let executor = cx.background_executor().clone();
+                async move {
+                    executor.timer(Duration::from_secs(1)).await;
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "some basic docs".to_string(),
+                        }),
+                        range: None,
+                    }))
+                }
             });
+        let mut cancellations = cx
+            .lsp
+            .handle_notification::<lsp::notification::Cancel, _>(|_, _| {});
 
+        cx.dispatch_action(Hover);
         requests.next().await;
+        cx.run_until_parked();
+        assert!(cancellations.try_next().is_err());
+
+        // Moving the mouse away before the server answers cancels the request, whose
+        // response is never shown.
+        cx.update_editor(|editor, cx| hover_at(editor, None, cx));
+        cancellations.next().await;
+        cx.background_executor.advance_clock(Duration::from_secs(2));
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(!editor.hover_state.visible()));
+        assert!(cancellations.try_next().is_err());
+    }
+
+    #[gpui::test]
+    async fn test_hover_cache_within_symbol(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_cache = Some(true);
+                });
+            });
+        });
+
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                request_count.fetch_add(1, atomic::Ordering::SeqCst);
+                async move {
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: "some basic docs".to_string(),
+                        }),
+                        range: Some(symbol_range),
+                    }))
+                }
+            }
+        });
+
+        // Hovering the same symbol again, anywhere in it, reuses its hover.
+        for marked_text in ["fn ˇtest() {}\n", "fn teˇst() {}\n", "fn ˇtest() {}\n"] {
+            cx.set_selections_state(marked_text);
+            cx.dispatch_action(Hover);
+            cx.run_until_parked();
+            assert!(cx.editor(|editor, _| editor.hover_state.visible()));
+        }
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 1);
+
+        // Editing the buffer invalidates the cache.
+        cx.update_buffer(|buffer, cx| buffer.edit([(0..0, " ")], None, cx));
+        cx.set_selections_state(" fn teˇst() {}\n");
         cx.dispatch_action(Hover);
+        cx.run_until_parked();
+        assert_eq!(request_count.load(atomic::Ordering::SeqCst), 2);
+    }
+
+    #[gpui::test]
+    async fn test_hover_marked_string(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn ˇtest() { println!(); }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() { println!(); }
+        "});
 
+        // A plain marked string is markdown.
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Scalar(lsp::MarkedString::String(
+                    "Some *basic* docs".to_string(),
+                )),
+                range: Some(symbol_range),
+            }))
+        });
+        cx.dispatch_action(Hover);
         cx.condition(|editor, _| editor.hover_state.visible()).await;
         cx.editor(|editor, cx| {
-            assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                1,
-                "Expected exactly one hover but got: {:?}",
-                editor.hover_state.info_popovers
-            );
-
-            let rendered_text = editor
-                .hover_state
-                .info_popovers
-                .first()
-                .unwrap()
-                .get_rendered_text(cx);
+            let parsed_content = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(parsed_content.read(cx).source(), "Some *basic* docs");
+        });
 
-            assert_eq!(rendered_text, "some other basic docs".to_string())
+        // A language string is a code block in its language, which highlights it.
+        cx.update_editor(|editor, cx| hide_hover(editor, cx));
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Scalar(lsp::MarkedString::LanguageString(
+                    lsp::LanguageString {
+                        language: "rust".to_string(),
+                        value: "fn test()".to_string(),
+                    },
+                )),
+                range: Some(symbol_range),
+            }))
+        });
+        cx.dispatch_action(Hover);
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.editor(|editor, cx| {
+            let parsed_content = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            assert_eq!(parsed_content.read(cx).source(), "```rust\nfn test()\n```");
         });
     }
 
     #[gpui::test]
-    async fn test_empty_hovers_filtered(cx: &mut gpui::TestAppContext) {
+    async fn test_hover_marked_string_array(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
 
         let mut cx = EditorLspTestContext::new_rust(
@@ -1048,56 +12609,40 @@ mod tests {
         )
         .await;
 
-        // Hover with keyboard has no delay
         cx.set_state(indoc! {"
-            fˇn test() { println!(); }
+            fn ˇtest() { println!(); }
         "});
-        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
         let symbol_range = cx.lsp_range(indoc! {"
-            «fn» test() { println!(); }
+            fn «test»() { println!(); }
         "});
         cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
             Ok(Some(lsp::Hover {
                 contents: lsp::HoverContents::Array(vec![
-                    lsp::MarkedString::String("regular text for hover to show".to_string()),
-                    lsp::MarkedString::String("".to_string()),
                     lsp::MarkedString::LanguageString(lsp::LanguageString {
-                        language: "Rust".to_string(),
-                        value: "".to_string(),
+                        language: "rust".to_string(),
+                        value: "fn test()".to_string(),
                     }),
+                    lsp::MarkedString::String("Some *basic* docs".to_string()),
                 ]),
                 range: Some(symbol_range),
             }))
-        })
-        .next()
-        .await;
+        });
         cx.dispatch_action(Hover);
-
         cx.condition(|editor, _| editor.hover_state.visible()).await;
         cx.editor(|editor, cx| {
+            let parsed_content = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
             assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                1,
-                "Expected exactly one hover but got: {:?}",
-                editor.hover_state.info_popovers
-            );
-            let rendered_text = editor
-                .hover_state
-                .info_popovers
-                .first()
-                .unwrap()
-                .get_rendered_text(cx);
-
-            assert_eq!(
-                rendered_text,
-                "regular text for hover to show".to_string(),
-                "No empty string hovers should be shown"
+                parsed_content.read(cx).source(),
+                "```rust\nfn test()\n```\n\n---\n\nSome *basic* docs"
             );
         });
     }
 
     #[gpui::test]
-    async fn test_line_ends_trimmed(cx: &mut gpui::TestAppContext) {
+    async fn test_hover_markup_kind_plaintext(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
 
         let mut cx = EditorLspTestContext::new_rust(
@@ -1108,61 +12653,59 @@ mod tests {
             cx,
         )
         .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_markup_kind = Some(HoverMarkupKind::PlainText);
+                });
+            });
+        });
+
+        // Language servers that start are asked for plain text.
+        cx.update(|cx| {
+            assert_eq!(
+                cx.global::<HoverContentFormat>().0,
+                vec![lsp::MarkupKind::PlainText, lsp::MarkupKind::Markdown]
+            );
+        });
 
-        // Hover with keyboard has no delay
         cx.set_state(indoc! {"
-            fˇn test() { println!(); }
+            fn ˇtest() { println!(); }
         "});
-        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
         let symbol_range = cx.lsp_range(indoc! {"
-            «fn» test() { println!(); }
+            fn «test»() { println!(); }
         "});
-
-        let code_str = "\nlet hovered_point: Vector2F // size = 8, align = 0x4\n";
-        let markdown_string = format!("\n```rust\n{code_str}```");
-
-        let closure_markdown_string = markdown_string.clone();
-        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| {
-            let future_markdown_string = closure_markdown_string.clone();
-            async move {
-                Ok(Some(lsp::Hover {
-                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
-                        kind: lsp::MarkupKind::Markdown,
-                        value: future_markdown_string,
-                    }),
-                    range: Some(symbol_range),
-                }))
-            }
-        })
-        .next()
-        .await;
-
+        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+            Ok(Some(lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "Some *basic* docs\n# not a heading".to_string(),
+                }),
+                range: Some(symbol_range),
+            }))
+        });
         cx.dispatch_action(Hover);
-
         cx.condition(|editor, _| editor.hover_state.visible()).await;
-        cx.editor(|editor, cx| {
-            assert_eq!(
-                editor.hover_state.info_popovers.len(),
-                1,
-                "Expected exactly one hover but got: {:?}",
-                editor.hover_state.info_popovers
-            );
-            let rendered_text = editor
-                .hover_state
-                .info_popovers
-                .first()
-                .unwrap()
-                .get_rendered_text(cx);
+        cx.run_until_parked();
 
+        // The documentation is shown as is, without parsing it as markdown.
+        cx.editor(|editor, cx| {
+            let parsed_content = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            let markdown = parsed_content.read(cx);
+            let source = "Some *basic* docs\n# not a heading";
+            assert_eq!(markdown.source(), source);
             assert_eq!(
-                rendered_text, code_str,
-                "Should not have extra line breaks at end of rendered hover"
+                markdown.parsed_markdown().events().as_ref(),
+                &[(0..source.len(), MarkdownEvent::Text)]
             );
         });
     }
 
     #[gpui::test]
-    async fn test_hover_diagnostic_and_info_popovers(cx: &mut gpui::TestAppContext) {
+    async fn test_keyboard_hover_link_navigation(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
 
         let mut cx = EditorLspTestContext::new_rust(
@@ -1174,365 +12717,261 @@ mod tests {
         )
         .await;
 
-        // Hover with just diagnostic, pops DiagnosticPopover immediately and then
-        // info popover once request completes
         cx.set_state(indoc! {"
-            fn teˇst() { println!(); }
+            fn tesˇt() {}
         "});
-
-        // Send diagnostic to client
-        let range = cx.text_anchor_range(indoc! {"
-            fn «test»() { println!(); }
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn «test»() {}
         "});
-        cx.update_buffer(|buffer, cx| {
-            let snapshot = buffer.text_snapshot();
-            let set = DiagnosticSet::from_sorted_entries(
-                vec![DiagnosticEntry {
-                    range,
-                    diagnostic: Diagnostic {
-                        message: "A test diagnostic message.".to_string(),
-                        ..Default::default()
-                    },
-                }],
-                &snapshot,
-            );
-            buffer.update_diagnostics(LanguageServerId(0), set, cx);
-        });
-
-        // Hover pops diagnostic immediately
-        cx.update_editor(|editor, cx| hover(editor, &Hover, cx));
-        cx.background_executor.run_until_parked();
-
-        cx.editor(|Editor { hover_state, .. }, _| {
-            assert!(
-                hover_state.diagnostic_popover.is_some() && hover_state.info_popovers.is_empty()
-            )
-        });
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "See [one](https://zed.dev/one) and [two](https://zed.dev/two)"
+                            .to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
 
-        // Info Popover shows after request responded to
-        let range = cx.lsp_range(indoc! {"
-            fn «test»() { println!(); }
-        "});
-        cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
-            Ok(Some(lsp::Hover {
-                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
-                    kind: lsp::MarkupKind::Markdown,
-                    value: "some new docs".to_string(),
-                }),
-                range: Some(range),
-            }))
-        });
-        cx.background_executor
-            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        // Without a focused link, there is nothing to open.
+        cx.dispatch_action(OpenFocusedHoverLink);
+        assert_eq!(cx.opened_url(), None);
+
+        // Focusing cycles through the links, wrapping around in both directions.
+        for (direction, expected_ix) in [
+            (Direction::Next, 0),
+            (Direction::Next, 1),
+            (Direction::Next, 0),
+            (Direction::Prev, 1),
+        ] {
+            match direction {
+                Direction::Next => cx.dispatch_action(HoverNextLink),
+                Direction::Prev => cx.dispatch_action(HoverPrevLink),
+            }
+            cx.editor(|editor, _| assert_eq!(editor.hover_state.focused_link, Some(expected_ix)));
+        }
 
-        cx.background_executor.run_until_parked();
-        cx.editor(|Editor { hover_state, .. }, _| {
-            hover_state.diagnostic_popover.is_some() && hover_state.info_task.is_some()
-        });
+        cx.dispatch_action(OpenFocusedHoverLink);
+        cx.run_until_parked();
+        assert_eq!(cx.opened_url(), Some("https://zed.dev/two".into()));
     }
 
     #[gpui::test]
-    async fn test_hover_inlay_label_parts(cx: &mut gpui::TestAppContext) {
-        init_test(cx, |settings| {
-            settings.defaults.inlay_hints = Some(InlayHintSettings {
-                enabled: true,
-                edit_debounce_ms: 0,
-                scroll_debounce_ms: 0,
-                show_type_hints: true,
-                show_parameter_hints: true,
-                show_other_hints: true,
-            })
-        });
+    async fn test_mouse_hover_leaves_tab_to_editor(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
 
         let mut cx = EditorLspTestContext::new_rust(
             lsp::ServerCapabilities {
-                inlay_hint_provider: Some(lsp::OneOf::Right(
-                    lsp::InlayHintServerCapabilities::Options(lsp::InlayHintOptions {
-                        resolve_provider: Some(true),
-                        ..Default::default()
-                    }),
-                )),
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
                 ..Default::default()
             },
             cx,
         )
-        .await;
-
-        cx.set_state(indoc! {"
-            struct TestStruct;
-
-            // ==================
-
-            struct TestNewType<T>(T);
-
-            fn main() {
-                let variableˇ = TestNewType(TestStruct);
-            }
-        "});
-
-        let hint_start_offset = cx.ranges(indoc! {"
-            struct TestStruct;
-
-            // ==================
-
-            struct TestNewType<T>(T);
-
-            fn main() {
-                let variableˇ = TestNewType(TestStruct);
-            }
-        "})[0]
-            .start;
-        let hint_position = cx.to_lsp(hint_start_offset);
-        let new_type_target_range = cx.lsp_range(indoc! {"
-            struct TestStruct;
-
-            // ==================
-
-            struct «TestNewType»<T>(T);
-
-            fn main() {
-                let variable = TestNewType(TestStruct);
-            }
-        "});
-        let struct_target_range = cx.lsp_range(indoc! {"
-            struct «TestStruct»;
-
-            // ==================
-
-            struct TestNewType<T>(T);
-
-            fn main() {
-                let variable = TestNewType(TestStruct);
-            }
-        "});
-
-        let uri = cx.buffer_lsp_url.clone();
-        let new_type_label = "TestNewType";
-        let struct_label = "TestStruct";
-        let entire_hint_label = ": TestNewType<TestStruct>";
-        let closure_uri = uri.clone();
-        cx.lsp
-            .handle_request::<lsp::request::InlayHintRequest, _, _>(move |params, _| {
-                let task_uri = closure_uri.clone();
-                async move {
-                    assert_eq!(params.text_document.uri, task_uri);
-                    Ok(Some(vec![lsp::InlayHint {
-                        position: hint_position,
-                        label: lsp::InlayHintLabel::LabelParts(vec![lsp::InlayHintLabelPart {
-                            value: entire_hint_label.to_string(),
-                            ..Default::default()
-                        }]),
-                        kind: Some(lsp::InlayHintKind::TYPE),
-                        text_edits: None,
-                        tooltip: None,
-                        padding_left: Some(false),
-                        padding_right: Some(false),
-                        data: None,
-                    }]))
-                }
-            })
-            .next()
-            .await;
-        cx.background_executor.run_until_parked();
+        .await;
+        cx.update(|cx| {
+            cx.bind_keys([
+                gpui::KeyBinding::new("tab", crate::Tab, Some("Editor")),
+                gpui::KeyBinding::new("tab", HoverNextLink, Some("Editor && showing_hover")),
+            ])
+        });
+
+        cx.set_state(indoc! {"
+            fn test() {
+            ˇtest();
+            }
+        "});
+        let hover_point = cx.display_point(indoc! {"
+            fn test() {
+            teˇst();
+            }
+        "});
+        let symbol_range = cx.lsp_range(indoc! {"
+            fn test() {
+            «test»();
+            }
+        "});
+        let mut requests =
+            cx.handle_request::<lsp::request::HoverRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                        kind: lsp::MarkupKind::Markdown,
+                        value: "See [one](https://zed.dev/one)".to_string(),
+                    }),
+                    range: Some(symbol_range),
+                }))
+            });
         cx.update_editor(|editor, cx| {
-            let expected_layers = vec![entire_hint_label.to_string()];
-            assert_eq!(expected_layers, cached_hint_labels(editor));
-            assert_eq!(expected_layers, visible_hint_labels(editor, cx));
+            let snapshot = editor.snapshot(cx);
+            let anchor = snapshot
+                .buffer_snapshot
+                .anchor_before(hover_point.to_offset(&snapshot, Bias::Left));
+            hover_at(editor, Some(anchor), cx)
         });
+        cx.background_executor
+            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| assert!(editor.hover_state.visible()));
 
-        let inlay_range = cx
-            .ranges(indoc! {"
-                struct TestStruct;
+        // The mouse hover doesn't take the tab from the editor, which indents.
+        cx.simulate_keystroke("tab");
+        cx.assert_editor_state(indoc! {"
+            fn test() {
+                ˇtest();
+            }
+        "});
+        cx.editor(|editor, _| assert_eq!(editor.hover_state.focused_link, None));
+    }
 
-                // ==================
+    #[gpui::test]
+    async fn test_hover_renders_long_content_incrementally(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
 
-                struct TestNewType<T>(T);
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
 
-                fn main() {
-                    let variable« »= TestNewType(TestStruct);
+        let docs = (0..200)
+            .map(|ix| format!("Paragraph {ix}."))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        cx.set_state(indoc! {"
+            fn ˇtest() {}
+        "});
+        let mut requests = cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let docs = docs.clone();
+            move |_, _, _| {
+                let docs = docs.clone();
+                async move {
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: docs,
+                        }),
+                        range: None,
+                    }))
                 }
-        "})
-            .get(0)
-            .cloned()
-            .unwrap();
-        let new_type_hint_part_hover_position = cx.update_editor(|editor, cx| {
-            let snapshot = editor.snapshot(cx);
-            let previous_valid = inlay_range.start.to_display_point(&snapshot);
-            let next_valid = inlay_range.end.to_display_point(&snapshot);
-            assert_eq!(previous_valid.row(), next_valid.row());
-            assert!(previous_valid.column() < next_valid.column());
-            let exact_unclipped = DisplayPoint::new(
-                previous_valid.row(),
-                previous_valid.column()
-                    + (entire_hint_label.find(new_type_label).unwrap() + new_type_label.len() / 2)
-                        as u32,
-            );
-            PointForPosition {
-                previous_valid,
-                next_valid,
-                exact_unclipped,
-                column_overshoot_after_line_end: 0,
             }
         });
-        cx.update_editor(|editor, cx| {
-            update_inlay_link_and_hover_points(
-                &editor.snapshot(cx),
-                new_type_hint_part_hover_position,
-                editor,
-                true,
-                false,
-                cx,
-            );
-        });
-
-        let resolve_closure_uri = uri.clone();
-        cx.lsp
-            .handle_request::<lsp::request::InlayHintResolveRequest, _, _>(
-                move |mut hint_to_resolve, _| {
-                    let mut resolved_hint_positions = BTreeSet::new();
-                    let task_uri = resolve_closure_uri.clone();
-                    async move {
-                        let inserted = resolved_hint_positions.insert(hint_to_resolve.position);
-                        assert!(inserted, "Hint {hint_to_resolve:?} was resolved twice");
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
 
-                        // `: TestNewType<TestStruct>`
-                        hint_to_resolve.label = lsp::InlayHintLabel::LabelParts(vec![
-                            lsp::InlayHintLabelPart {
-                                value: ": ".to_string(),
-                                ..Default::default()
-                            },
-                            lsp::InlayHintLabelPart {
-                                value: new_type_label.to_string(),
-                                location: Some(lsp::Location {
-                                    uri: task_uri.clone(),
-                                    range: new_type_target_range,
-                                }),
-                                tooltip: Some(lsp::InlayHintLabelPartTooltip::String(format!(
-                                    "A tooltip for `{new_type_label}`"
-                                ))),
-                                ..Default::default()
-                            },
-                            lsp::InlayHintLabelPart {
-                                value: "<".to_string(),
-                                ..Default::default()
-                            },
-                            lsp::InlayHintLabelPart {
-                                value: struct_label.to_string(),
-                                location: Some(lsp::Location {
-                                    uri: task_uri,
-                                    range: struct_target_range,
-                                }),
-                                tooltip: Some(lsp::InlayHintLabelPartTooltip::MarkupContent(
-                                    lsp::MarkupContent {
-                                        kind: lsp::MarkupKind::Markdown,
-                                        value: format!("A tooltip for `{struct_label}`"),
-                                    },
-                                )),
-                                ..Default::default()
-                            },
-                            lsp::InlayHintLabelPart {
-                                value: ">".to_string(),
-                                ..Default::default()
-                            },
-                        ]);
+        // The paragraphs past the first frame's budget are laid out in the frame after it.
+        cx.editor(|editor, cx| {
+            let markdown = editor.hover_state.info_popovers[0]
+                .parsed_content
+                .clone()
+                .unwrap();
+            let lines = markdown
+                .read(cx)
+                .rendered_lines()
+                .map(|(text, _)| text.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(lines.len(), 200);
+            assert_eq!(lines.last().unwrap(), "Paragraph 199.");
+        });
+    }
 
-                        Ok(hint_to_resolve)
-                    }
-                },
-            )
-            .next()
-            .await;
-        cx.background_executor.run_until_parked();
+    #[gpui::test]
+    async fn test_hover_live_update(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
 
-        cx.update_editor(|editor, cx| {
-            update_inlay_link_and_hover_points(
-                &editor.snapshot(cx),
-                new_type_hint_part_hover_position,
-                editor,
-                true,
-                false,
-                cx,
-            );
-        });
-        cx.background_executor
-            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
-        cx.background_executor.run_until_parked();
-        cx.update_editor(|editor, cx| {
-            let hover_state = &editor.hover_state;
-            assert!(
-                hover_state.diagnostic_popover.is_none() && hover_state.info_popovers.len() == 1
-            );
-            let popover = hover_state.info_popovers.first().cloned().unwrap();
-            let buffer_snapshot = editor.buffer().update(cx, |buffer, cx| buffer.snapshot(cx));
-            assert_eq!(
-                popover.symbol_range,
-                RangeInEditor::Inlay(InlayHighlight {
-                    inlay: InlayId::Hint(0),
-                    inlay_position: buffer_snapshot.anchor_at(inlay_range.start, Bias::Right),
-                    range: ": ".len()..": ".len() + new_type_label.len(),
-                }),
-                "Popover range should match the new type label part"
-            );
-            assert_eq!(
-                popover.get_rendered_text(cx),
-                format!("A tooltip for {new_type_label}"),
-            );
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.hover_live_update = Some(true);
+                });
+            });
         });
 
-        let struct_hint_part_hover_position = cx.update_editor(|editor, cx| {
-            let snapshot = editor.snapshot(cx);
-            let previous_valid = inlay_range.start.to_display_point(&snapshot);
-            let next_valid = inlay_range.end.to_display_point(&snapshot);
-            assert_eq!(previous_valid.row(), next_valid.row());
-            assert!(previous_valid.column() < next_valid.column());
-            let exact_unclipped = DisplayPoint::new(
-                previous_valid.row(),
-                previous_valid.column()
-                    + (entire_hint_label.find(struct_label).unwrap() + struct_label.len() / 2)
-                        as u32,
-            );
-            PointForPosition {
-                previous_valid,
-                next_valid,
-                exact_unclipped,
-                column_overshoot_after_line_end: 0,
+        cx.set_state(indoc! {"
+            fn test() { printˇln!(); }
+        "});
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let mut requests = cx.handle_request::<lsp::request::HoverRequest, _, _>({
+            let request_count = request_count.clone();
+            move |_, _, _| {
+                let edited = request_count.fetch_add(1, atomic::Ordering::SeqCst) > 0;
+                async move {
+                    // After the edit, the server's range also covers the `!`.
+                    let (value, end) = if edited {
+                        ("Prints a line, edited", 21)
+                    } else {
+                        ("Prints a line", 19)
+                    };
+                    Ok(Some(lsp::Hover {
+                        contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                            kind: lsp::MarkupKind::Markdown,
+                            value: value.to_string(),
+                        }),
+                        range: Some(lsp::Range::new(
+                            lsp::Position::new(0, 12),
+                            lsp::Position::new(0, end),
+                        )),
+                    }))
+                }
             }
         });
+        cx.dispatch_action(Hover);
+        requests.next().await;
+        cx.condition(|editor, _| editor.hover_state.visible()).await;
+        cx.run_until_parked();
+
+        // Popovers which aren't the language server's, like a test's last run, come first.
         cx.update_editor(|editor, cx| {
-            update_inlay_link_and_hover_points(
-                &editor.snapshot(cx),
-                struct_hint_part_hover_position,
-                editor,
-                true,
-                false,
-                cx,
-            );
+            let mut test_run_popover = editor.hover_state.info_popovers[0].clone();
+            test_run_popover.buffer_version = None;
+            test_run_popover.parsed_content = Some(cx.new_view(|cx| {
+                Markdown::new(
+                    "Test passed".to_string(),
+                    MarkdownStyle::default(),
+                    None,
+                    cx,
+                    None,
+                )
+            }));
+            editor.hover_state.info_popovers.insert(0, test_run_popover);
         });
-        cx.background_executor
-            .advance_clock(Duration::from_millis(HOVER_DELAY_MILLIS + 100));
-        cx.background_executor.run_until_parked();
-        cx.update_editor(|editor, cx| {
-            let hover_state = &editor.hover_state;
-            assert!(
-                hover_state.diagnostic_popover.is_none() && hover_state.info_popovers.len() == 1
-            );
-            let popover = hover_state.info_popovers.first().cloned().unwrap();
-            let buffer_snapshot = editor.buffer().update(cx, |buffer, cx| buffer.snapshot(cx));
-            assert_eq!(
-                popover.symbol_range,
-                RangeInEditor::Inlay(InlayHighlight {
-                    inlay: InlayId::Hint(0),
-                    inlay_position: buffer_snapshot.anchor_at(inlay_range.start, Bias::Right),
-                    range: ": ".len() + new_type_label.len() + "<".len()
-                        ..": ".len() + new_type_label.len() + "<".len() + struct_label.len(),
-                }),
-                "Popover range should match the struct label part"
-            );
+        cx.run_until_parked();
+
+        // Editing the hovered symbol re-queries the server once the edits settle.
+        cx.update_buffer(|buffer, cx| buffer.edit([(17..17, "_")], None, cx));
+        cx.executor()
+            .advance_clock(Duration::from_millis(HOVER_LIVE_UPDATE_DEBOUNCE_MILLIS));
+        requests.next().await;
+        cx.run_until_parked();
+
+        cx.editor(|editor, cx| {
+            let info_popovers = &editor.hover_state.info_popovers;
+            assert_eq!(info_popovers.len(), 2);
+            assert_eq!(info_popovers[0].get_rendered_text(cx), "Test passed");
             assert_eq!(
-                popover.get_rendered_text(cx),
-                format!("A tooltip for {struct_label}"),
-                "Rendered markdown element should remove backticks from text"
+                info_popovers[1].get_rendered_text(cx),
+                "Prints a line, edited"
             );
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let symbol_range = info_popovers[1].symbol_range.as_text_range().unwrap();
+            assert_eq!(symbol_range.to_offset(&snapshot), 12..21);
         });
     }
 }
@@ -0,0 +1,62 @@
+use crate::{hover_popover::hover_status_text, Editor, EditorEvent};
+use gpui::{Subscription, View};
+use ui::prelude::*;
+use workspace::{item::ItemHandle, StatusItemView};
+
+/// Shows the first line of the active editor's hover documentation in the status bar,
+/// when `hover_popover_placement` is `status_bar`.
+#[derive(Default)]
+pub struct HoverStatusItem {
+    text: Option<SharedString>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl HoverStatusItem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> Option<&SharedString> {
+        self.text.as_ref()
+    }
+
+    fn update_text(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        self.text = hover_status_text(editor.read(cx), cx).map(SharedString::from);
+        cx.notify();
+    }
+}
+
+impl Render for HoverStatusItem {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.text.clone(), |el, text| {
+            el.child(
+                Label::new(text)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .single_line(),
+            )
+        })
+    }
+}
+
+impl StatusItemView for HoverStatusItem {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.subscribe(&editor, |this, editor, event, cx| {
+                if let EditorEvent::HoverChanged = event {
+                    this.update_text(editor, cx);
+                }
+            }));
+            self.update_text(editor, cx);
+        } else {
+            self.text = None;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
@@ -127,6 +127,8 @@ pub struct RemoveOptions {
 pub struct Metadata {
     pub inode: u64,
     pub mtime: SystemTime,
+    /// The size of the file in bytes.
+    pub len: u64,
     pub is_symlink: bool,
     pub is_dir: bool,
 }
@@ -404,6 +406,7 @@ impl Fs for RealFs {
         Ok(Some(Metadata {
             inode,
             mtime: metadata.modified().unwrap(),
+            len: metadata.len(),
             is_symlink,
             is_dir: metadata.file_type().is_dir(),
         }))
@@ -1508,15 +1511,21 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    content,
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: content.len() as u64,
                     is_dir: false,
                     is_symlink,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: 0,
                     is_dir: true,
                     is_symlink,
                 },
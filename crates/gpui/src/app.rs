@@ -637,6 +637,11 @@ impl AppContext {
         self.platform.should_auto_hide_scrollbars()
     }
 
+    /// Returns whether the user has asked to reduce motion, such as animations, at the platform level.
+    pub fn should_reduce_motion(&self) -> bool {
+        self.platform.should_reduce_motion()
+    }
+
     /// Restart the application.
     pub fn restart(&self, binary_path: Option<PathBuf>) {
         self.platform.restart(binary_path)
@@ -282,6 +282,11 @@ impl TestAppContext {
         self.test_platform.opened_url.borrow().clone()
     }
 
+    /// Simulates the user asking the platform to reduce motion.
+    pub fn simulate_reduce_motion(&self, reduce_motion: bool) {
+        self.test_platform.reduce_motion.set(reduce_motion);
+    }
+
     /// Simulates the user resizing the window to the new size.
     pub fn simulate_window_resize(&self, window_handle: AnyWindowHandle, size: Size<Pixels>) {
         self.test_window(window_handle).simulate_resize(size);
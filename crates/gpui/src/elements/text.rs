@@ -1,8 +1,8 @@
 use crate::{
     ActiveTooltip, AnyTooltip, AnyView, Bounds, DispatchPhase, Element, ElementId, GlobalElementId,
     HighlightStyle, Hitbox, IntoElement, LayoutId, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    Pixels, Point, SharedString, Size, TextRun, TextStyle, WhiteSpace, WindowContext, WrappedLine,
-    TOOLTIP_DELAY,
+    Pixels, Point, SharedString, Size, TextAlign, TextRun, TextStyle, WhiteSpace, WindowContext,
+    WrappedLine, TOOLTIP_DELAY,
 };
 use anyhow::anyhow;
 use parking_lot::{Mutex, MutexGuard};
@@ -30,7 +30,7 @@ impl Element for &'static str {
         cx: &mut WindowContext,
     ) -> (LayoutId, Self::RequestLayoutState) {
         let mut state = TextLayout::default();
-        let layout_id = state.layout(SharedString::from(*self), None, cx);
+        let layout_id = state.layout(SharedString::from(*self), None, TextAlign::Left, cx);
         (layout_id, state)
     }
 
@@ -88,7 +88,7 @@ impl Element for SharedString {
         cx: &mut WindowContext,
     ) -> (LayoutId, Self::RequestLayoutState) {
         let mut state = TextLayout::default();
-        let layout_id = state.layout(self.clone(), None, cx);
+        let layout_id = state.layout(self.clone(), None, TextAlign::Left, cx);
         (layout_id, state)
     }
 
@@ -130,6 +130,7 @@ impl IntoElement for SharedString {
 pub struct StyledText {
     text: SharedString,
     runs: Option<Vec<TextRun>>,
+    text_align: TextAlign,
     layout: TextLayout,
 }
 
@@ -139,6 +140,7 @@ impl StyledText {
         StyledText {
             text: text.into(),
             runs: None,
+            text_align: TextAlign::default(),
             layout: TextLayout::default(),
         }
     }
@@ -181,6 +183,12 @@ impl StyledText {
         self.runs = Some(runs);
         self
     }
+
+    /// Set how the wrapped lines of this text are aligned horizontally.
+    pub fn with_text_align(mut self, text_align: TextAlign) -> Self {
+        self.text_align = text_align;
+        self
+    }
 }
 
 impl Element for StyledText {
@@ -198,7 +206,9 @@ impl Element for StyledText {
 
         cx: &mut WindowContext,
     ) -> (LayoutId, Self::RequestLayoutState) {
-        let layout_id = self.layout.layout(self.text.clone(), self.runs.take(), cx);
+        let layout_id =
+            self.layout
+                .layout(self.text.clone(), self.runs.take(), self.text_align, cx);
         (layout_id, ())
     }
 
@@ -240,6 +250,8 @@ struct TextLayoutInner {
     lines: SmallVec<[WrappedLine; 1]>,
     line_height: Pixels,
     wrap_width: Option<Pixels>,
+    /// The [`WrappedLine::justify_offsets`] of each line, computed once per layout.
+    justify_offsets: SmallVec<[Vec<Pixels>; 1]>,
     size: Option<Size<Pixels>>,
     bounds: Option<Bounds<Pixels>>,
 }
@@ -253,6 +265,7 @@ impl TextLayout {
         &mut self,
         text: SharedString,
         runs: Option<Vec<TextRun>>,
+        text_align: TextAlign,
         cx: &mut WindowContext,
     ) -> LayoutId {
         let text_style = cx.text_style();
@@ -302,12 +315,17 @@ impl TextLayout {
                         lines: Default::default(),
                         line_height,
                         wrap_width,
+                        justify_offsets: Default::default(),
                         size: Some(Size::default()),
                         bounds: None,
                     });
                     return Size::default();
                 };
 
+                let justify_offsets = lines
+                    .iter()
+                    .map(|line| line.justify_offsets(text_align, wrap_width))
+                    .collect();
                 let mut size: Size<Pixels> = Size::default();
                 for line in &lines {
                     let line_size = line.size(line_height);
@@ -319,6 +337,7 @@ impl TextLayout {
                     lines,
                     line_height,
                     wrap_width,
+                    justify_offsets,
                     size: Some(size),
                     bounds: None,
                 });
@@ -352,8 +371,13 @@ impl TextLayout {
 
         let line_height = element_state.line_height;
         let mut line_origin = bounds.origin;
-        for line in &element_state.lines {
-            line.paint(line_origin, line_height, cx).log_err();
+        for (line, justify_offsets) in element_state
+            .lines
+            .iter()
+            .zip(&element_state.justify_offsets)
+        {
+            line.paint(line_origin, line_height, justify_offsets, cx)
+                .log_err();
             line_origin.y += line.size(line_height).height;
         }
     }
@@ -375,13 +399,21 @@ impl TextLayout {
         let line_height = element_state.line_height;
         let mut line_origin = bounds.origin;
         let mut line_start_ix = 0;
-        for line in &element_state.lines {
+        for (line, justify_offsets) in element_state
+            .lines
+            .iter()
+            .zip(&element_state.justify_offsets)
+        {
             let line_bottom = line_origin.y + line.size(line_height).height;
             if position.y > line_bottom {
                 line_origin.y = line_bottom;
                 line_start_ix += line.len() + 1;
             } else {
-                let position_within_line = position - line_origin;
+                let mut position_within_line = position - line_origin;
+                if !justify_offsets.is_empty() {
+                    position_within_line =
+                        line.unjustify_position(justify_offsets, position_within_line, line_height);
+                }
                 match line.index_for_position(position_within_line, line_height) {
                     Ok(index_within_line) => return Ok(line_start_ix + index_within_line),
                     Err(index_within_line) => return Err(line_start_ix + index_within_line),
@@ -406,7 +438,11 @@ impl TextLayout {
         let mut line_origin = bounds.origin;
         let mut line_start_ix = 0;
 
-        for line in &element_state.lines {
+        for (line, justify_offsets) in element_state
+            .lines
+            .iter()
+            .zip(&element_state.justify_offsets)
+        {
             let line_end_ix = line_start_ix + line.len();
             if index < line_start_ix {
                 break;
@@ -416,7 +452,9 @@ impl TextLayout {
                 continue;
             } else {
                 let ix_within_line = index - line_start_ix;
-                return Some(line_origin + line.position_for_index(ix_within_line, line_height)?);
+                let mut position = line.position_for_index(ix_within_line, line_height)?;
+                position.x += line.justify_offset_for_index(justify_offsets, ix_within_line);
+                return Some(line_origin + position);
             }
         }
 
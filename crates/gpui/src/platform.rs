@@ -164,6 +164,7 @@ pub(crate) trait Platform: 'static {
 
     fn set_cursor_style(&self, style: CursorStyle);
     fn should_auto_hide_scrollbars(&self) -> bool;
+    fn should_reduce_motion(&self) -> bool;
 
     #[cfg(target_os = "linux")]
     fn write_to_primary(&self, item: ClipboardItem);
@@ -484,6 +484,11 @@ impl WaylandClient {
                             client.cursor.set_size(size);
                         }
                     }
+                    XDPEvent::ReduceMotion(reduce_motion) => {
+                        if let Some(client) = client.0.upgrade() {
+                            client.borrow_mut().common.reduce_motion = reduce_motion;
+                        }
+                    }
                 }
             })
             .unwrap();
@@ -13,6 +13,7 @@ pub enum Event {
     WindowAppearance(WindowAppearance),
     CursorTheme(String),
     CursorSize(u32),
+    ReduceMotion(bool),
 }
 
 pub struct XDPEventSource {
@@ -46,6 +47,12 @@ impl XDPEventSource {
                 {
                     sender.send(Event::CursorSize(initial_size))?;
                 }
+                if let Ok(enable_animations) = settings
+                    .read::<bool>("org.gnome.desktop.interface", "enable-animations")
+                    .await
+                {
+                    sender.send(Event::ReduceMotion(!enable_animations))?;
+                }
 
                 if let Ok(mut cursor_theme_changed) = settings
                     .receive_setting_changed_with_args(
@@ -85,6 +92,27 @@ impl XDPEventSource {
                         .detach();
                 }
 
+                if let Ok(mut enable_animations_changed) = settings
+                    .receive_setting_changed_with_args::<bool>(
+                        "org.gnome.desktop.interface",
+                        "enable-animations",
+                    )
+                    .await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(enable_animations) =
+                                enable_animations_changed.next().await
+                            {
+                                let enable_animations = enable_animations?;
+                                sender.send(Event::ReduceMotion(!enable_animations))?;
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
                 let mut appearance_changed = settings.receive_color_scheme_changed().await?;
                 while let Some(scheme) = appearance_changed.next().await {
                     sender.send(Event::WindowAppearance(WindowAppearance::from_native(
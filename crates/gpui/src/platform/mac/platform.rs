@@ -842,6 +842,14 @@ impl Platform for MacPlatform {
         }
     }
 
+    fn should_reduce_motion(&self) -> bool {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let reduce_motion: BOOL = msg_send![workspace, accessibilityDisplayShouldReduceMotion];
+            reduce_motion == YES
+        }
+    }
+
     fn write_to_clipboard(&self, item: ClipboardItem) {
         let state = self.0.lock();
         unsafe {
@@ -8,7 +8,7 @@ use collections::VecDeque;
 use futures::channel::oneshot;
 use parking_lot::Mutex;
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     path::{Path, PathBuf},
     rc::{Rc, Weak},
     sync::Arc,
@@ -27,6 +27,7 @@ pub(crate) struct TestPlatform {
     current_primary_item: Mutex<Option<ClipboardItem>>,
     pub(crate) prompts: RefCell<TestPrompts>,
     pub opened_url: RefCell<Option<String>>,
+    pub(crate) reduce_motion: Cell<bool>,
     pub text_system: Arc<dyn PlatformTextSystem>,
     weak: Weak<Self>,
 }
@@ -66,6 +67,7 @@ impl TestPlatform {
             current_primary_item: Mutex::new(None),
             weak: weak.clone(),
             opened_url: Default::default(),
+            reduce_motion: Cell::new(false),
             text_system,
         })
     }
@@ -273,6 +275,10 @@ impl Platform for TestPlatform {
         false
     }
 
+    fn should_reduce_motion(&self) -> bool {
+        self.reduce_motion.get()
+    }
+
     #[cfg(target_os = "linux")]
     fn write_to_primary(&self, item: ClipboardItem) {
         *self.current_primary_item.lock() = Some(item);
@@ -497,6 +497,10 @@ impl Platform for WindowsPlatform {
         should_auto_hide_scrollbars().log_err().unwrap_or(false)
     }
 
+    fn should_reduce_motion(&self) -> bool {
+        should_reduce_motion().log_err().unwrap_or(false)
+    }
+
     fn write_to_clipboard(&self, item: ClipboardItem) {
         if item.text.len() > 0 {
             set_clipboard_string(item.text()).unwrap();
@@ -680,3 +684,9 @@ fn should_auto_hide_scrollbars() -> Result<bool> {
     let ui_settings = UISettings::new()?;
     Ok(ui_settings.AutoHideScrollBars()?)
 }
+
+#[inline]
+fn should_reduce_motion() -> Result<bool> {
+    let ui_settings = UISettings::new()?;
+    Ok(!ui_settings.AnimationsEnabled()?)
+}
@@ -119,6 +119,9 @@ pub struct Style {
     /// The mouse cursor style shown when the mouse pointer is over an element.
     pub mouse_cursor: Option<CursorStyle>,
 
+    /// The opacity of this element and its children, between 0 and 1
+    pub opacity: Option<f32>,
+
     /// Whether to draw a red debugging outline around this element
     #[cfg(debug_assertions)]
     pub debug: bool,
@@ -167,6 +170,16 @@ pub enum WhiteSpace {
     Nowrap,
 }
 
+/// How to align wrapped text horizontally
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    /// Align each line to the left edge of the element
+    #[default]
+    Left,
+    /// Stretch the spaces of every wrapped line, except the last, to fill the width of the element
+    Justify,
+}
+
 /// The properties that can be used to style text in GPUI
 #[derive(Refineable, Clone, Debug, PartialEq)]
 #[refineable(Debug)]
@@ -413,6 +426,17 @@ impl Style {
         bounds: Bounds<Pixels>,
         cx: &mut WindowContext,
         continuation: impl FnOnce(&mut WindowContext),
+    ) {
+        cx.with_element_opacity(self.opacity, |cx| {
+            self.paint_with_opacity(bounds, cx, continuation)
+        });
+    }
+
+    fn paint_with_opacity(
+        &self,
+        bounds: Bounds<Pixels>,
+        cx: &mut WindowContext,
+        continuation: impl FnOnce(&mut WindowContext),
     ) {
         #[cfg(debug_assertions)]
         if self.debug_below {
@@ -559,6 +583,7 @@ impl Default for Style {
             box_shadow: Default::default(),
             text: TextStyleRefinement::default(),
             mouse_cursor: None,
+            opacity: None,
 
             #[cfg(debug_assertions)]
             debug: false,
@@ -319,6 +319,12 @@ pub trait Styled: Sized {
         &mut style.text
     }
 
+    /// Set the opacity of this element and its children, between 0 and 1.
+    fn opacity(mut self, opacity: f32) -> Self {
+        self.style().opacity = Some(opacity);
+        self
+    }
+
     /// Set the text color of this element, this value cascades to its child elements.
     fn text_color(mut self, color: impl Into<Hsla>) -> Self {
         self.text_style().get_or_insert_with(Default::default).color = Some(color.into());
@@ -1,6 +1,7 @@
 use crate::{
-    black, fill, point, px, size, Bounds, Hsla, LineLayout, Pixels, Point, Result, SharedString,
-    StrikethroughStyle, UnderlineStyle, WindowContext, WrapBoundary, WrappedLineLayout,
+    black, fill, point, px, size, Bounds, Hsla, LineLayout, Pixels, Point, Result, ShapedGlyph,
+    SharedString, StrikethroughStyle, TextAlign, UnderlineStyle, WindowContext, WrapBoundary,
+    WrappedLineLayout,
 };
 use derive_more::{Deref, DerefMut};
 use smallvec::SmallVec;
@@ -56,6 +57,7 @@ impl ShapedLine {
             line_height,
             &self.decoration_runs,
             &[],
+            &[],
             cx,
         )?;
 
@@ -81,11 +83,13 @@ impl WrappedLine {
         self.layout.len()
     }
 
-    /// Paint this line of text to the window.
+    /// Paint this line of text to the window, offsetting each glyph horizontally by the
+    /// corresponding justify offset, if any.
     pub fn paint(
         &self,
         origin: Point<Pixels>,
         line_height: Pixels,
+        justify_offsets: &[Pixels],
         cx: &mut WindowContext,
     ) -> Result<()> {
         paint_line(
@@ -94,11 +98,131 @@ impl WrappedLine {
             line_height,
             &self.decoration_runs,
             &self.wrap_boundaries,
+            justify_offsets,
             cx,
         )?;
 
         Ok(())
     }
+
+    /// The horizontal offset to apply to each glyph, in layout order, so that every wrapped
+    /// line but the last fills `align_width`. Empty when the text isn't justified.
+    pub(crate) fn justify_offsets(
+        &self,
+        align: TextAlign,
+        align_width: Option<Pixels>,
+    ) -> Vec<Pixels> {
+        let (TextAlign::Justify, Some(align_width)) = (align, align_width) else {
+            return Vec::new();
+        };
+        if self.wrap_boundaries.is_empty() {
+            return Vec::new();
+        }
+
+        let glyphs = self.glyphs().collect::<Vec<_>>();
+        let is_space = |ix: usize| self.text[glyphs[ix].1.index..].starts_with(' ');
+
+        let mut offsets = vec![Pixels::ZERO; glyphs.len()];
+        let mut line_start = 0;
+        for boundary in self.wrap_boundaries.iter() {
+            let Some(line_end) = glyphs[line_start..]
+                .iter()
+                .position(|(glyph_boundary, _)| glyph_boundary == boundary)
+                .map(|ix| line_start + ix)
+            else {
+                break;
+            };
+
+            let mut content_end = line_end;
+            while content_end > line_start && is_space(content_end - 1) {
+                content_end -= 1;
+            }
+            let spaces = (line_start..content_end).filter(|ix| is_space(*ix)).count();
+            if spaces > 0 {
+                let content_width =
+                    glyphs[content_end].1.position.x - glyphs[line_start].1.position.x;
+                let extra = px((align_width - content_width).max(Pixels::ZERO).0 / spaces as f32);
+                let mut offset = Pixels::ZERO;
+                for (ix, glyph_offset) in (line_start..).zip(&mut offsets[line_start..line_end]) {
+                    *glyph_offset = offset;
+                    if ix < content_end && is_space(ix) {
+                        offset += extra;
+                    }
+                }
+            }
+            line_start = line_end;
+        }
+        offsets
+    }
+
+    /// Maps a position within this line, as painted with the given justify offsets, back to
+    /// the position it corresponds to in the unjustified layout.
+    pub(crate) fn unjustify_position(
+        &self,
+        justify_offsets: &[Pixels],
+        mut position: Point<Pixels>,
+        line_height: Pixels,
+    ) -> Point<Pixels> {
+        let row = (position.y / line_height).max(0.) as usize;
+        let row_start_x = row
+            .checked_sub(1)
+            .and_then(|ix| self.wrap_boundaries.get(ix))
+            .map_or(Pixels::ZERO, |boundary| {
+                self.unwrapped_layout.runs[boundary.run_ix].glyphs[boundary.glyph_ix]
+                    .position
+                    .x
+            });
+
+        let glyphs = self.glyphs();
+        let mut wraps = self.wrap_boundaries.iter().peekable();
+        let mut current_row = 0;
+        let mut offset = Pixels::ZERO;
+        for ((boundary, glyph), glyph_offset) in glyphs.zip(justify_offsets) {
+            if wraps.peek() == Some(&&boundary) {
+                wraps.next();
+                current_row += 1;
+            }
+            if current_row > row
+                || (current_row == row
+                    && glyph.position.x - row_start_x + *glyph_offset > position.x)
+            {
+                break;
+            }
+            if current_row == row {
+                offset = *glyph_offset;
+            }
+        }
+
+        position.x -= offset;
+        position
+    }
+
+    /// The glyphs of this line in layout order, with the boundary that would wrap before each.
+    fn glyphs(&self) -> impl Iterator<Item = (WrapBoundary, &ShapedGlyph)> {
+        self.unwrapped_layout
+            .runs
+            .iter()
+            .enumerate()
+            .flat_map(|(run_ix, run)| {
+                run.glyphs
+                    .iter()
+                    .enumerate()
+                    .map(move |(glyph_ix, glyph)| (WrapBoundary { run_ix, glyph_ix }, glyph))
+            })
+    }
+
+    /// The horizontal offset [`Self::paint`] applies to the glyph at the given byte index.
+    pub(crate) fn justify_offset_for_index(
+        &self,
+        justify_offsets: &[Pixels],
+        index: usize,
+    ) -> Pixels {
+        self.glyphs()
+            .zip(justify_offsets)
+            .take_while(|((_, glyph), _)| glyph.index <= index)
+            .last()
+            .map_or(Pixels::ZERO, |(_, offset)| *offset)
+    }
 }
 
 fn paint_line(
@@ -107,6 +231,7 @@ fn paint_line(
     line_height: Pixels,
     decoration_runs: &[DecorationRun],
     wrap_boundaries: &[WrapBoundary],
+    justify_offsets: &[Pixels],
     cx: &mut WindowContext,
 ) -> Result<()> {
     let line_bounds = Bounds::new(
@@ -129,6 +254,8 @@ fn paint_line(
         let text_system = cx.text_system().clone();
         let mut glyph_origin = origin;
         let mut prev_glyph_position = Point::default();
+        let mut justify_offsets = justify_offsets.iter();
+        let mut prev_justify_offset = Pixels::ZERO;
         for (run_ix, run) in layout.runs.iter().enumerate() {
             let max_glyph_size = text_system.bounding_box(run.font_id, layout.font_size).size;
 
@@ -172,9 +299,14 @@ fn paint_line(
 
                     glyph_origin.x = origin.x;
                     glyph_origin.y += line_height;
+                    prev_justify_offset = Pixels::ZERO;
                 }
                 prev_glyph_position = glyph.position;
 
+                let justify_offset = justify_offsets.next().copied().unwrap_or_default();
+                glyph_origin.x += justify_offset - prev_justify_offset;
+                prev_justify_offset = justify_offset;
+
                 let mut finished_background: Option<(Point<Pixels>, Hsla)> = None;
                 let mut finished_underline: Option<(Point<Pixels>, UnderlineStyle)> = None;
                 let mut finished_strikethrough: Option<(Point<Pixels>, StrikethroughStyle)> = None;
@@ -328,3 +460,91 @@ fn paint_line(
         Ok(())
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FontId, GlyphId, ShapedRun};
+
+    const GLYPH_WIDTH: Pixels = px(10.);
+    const LINE_HEIGHT: Pixels = px(20.);
+
+    /// "aa bb cc dd" laid out in a monospace font and wrapped before "cc".
+    fn wrapped_line() -> WrappedLine {
+        let text = SharedString::from("aa bb cc dd");
+        let glyphs = (0..text.len())
+            .map(|index| ShapedGlyph {
+                id: GlyphId(0),
+                position: point(GLYPH_WIDTH * index as f32, px(0.)),
+                index,
+                is_emoji: false,
+            })
+            .collect();
+        WrappedLine {
+            layout: Arc::new(WrappedLineLayout {
+                unwrapped_layout: Arc::new(LineLayout {
+                    font_size: px(16.),
+                    width: GLYPH_WIDTH * text.len() as f32,
+                    ascent: px(12.),
+                    descent: px(4.),
+                    runs: vec![ShapedRun {
+                        font_id: FontId(0),
+                        glyphs,
+                    }],
+                    len: text.len(),
+                }),
+                wrap_boundaries: SmallVec::from_iter([WrapBoundary {
+                    run_ix: 0,
+                    glyph_ix: 6,
+                }]),
+                wrap_width: Some(px(70.)),
+            }),
+            text,
+            decoration_runs: SmallVec::new(),
+        }
+    }
+
+    #[test]
+    fn test_justify_offsets() {
+        let line = wrapped_line();
+        assert!(line
+            .justify_offsets(TextAlign::Left, Some(px(70.)))
+            .is_empty());
+        assert!(line.justify_offsets(TextAlign::Justify, None).is_empty());
+
+        // The only space within "aa bb" takes up the 20px it's short of the width, and the
+        // last line isn't stretched.
+        let offsets = line.justify_offsets(TextAlign::Justify, Some(px(70.)));
+        assert_eq!(
+            offsets,
+            [0., 0., 0., 20., 20., 20., 0., 0., 0., 0., 0.].map(px)
+        );
+    }
+
+    #[test]
+    fn test_justified_position_and_index_round_trip() {
+        let line = wrapped_line();
+        let offsets = line.justify_offsets(TextAlign::Justify, Some(px(70.)));
+
+        let justified_position = |index: usize| {
+            let mut position = line.position_for_index(index, LINE_HEIGHT).unwrap();
+            position.x += line.justify_offset_for_index(&offsets, index);
+            position
+        };
+        assert_eq!(justified_position(1), point(px(10.), px(0.)));
+        assert_eq!(justified_position(3), point(px(50.), px(0.)));
+        assert_eq!(justified_position(7), point(px(10.), LINE_HEIGHT));
+
+        // The start of the second line is also the end of the first one, so it isn't mapped
+        // back to itself.
+        for index in (0..line.len()).filter(|index| *index != 6) {
+            let position =
+                line.unjustify_position(&offsets, justified_position(index), LINE_HEIGHT);
+            assert_eq!(
+                line.index_for_position(position, LINE_HEIGHT),
+                Ok(index),
+                "index {index}"
+            );
+        }
+    }
+}
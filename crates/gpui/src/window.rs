@@ -521,6 +521,7 @@ pub struct Window {
     pub(crate) text_style_stack: Vec<TextStyleRefinement>,
     pub(crate) element_offset_stack: Vec<Point<Pixels>>,
     pub(crate) content_mask_stack: Vec<ContentMask<Pixels>>,
+    pub(crate) element_opacity: Option<f32>,
     pub(crate) requested_autoscroll: Option<Bounds<Pixels>>,
     pub(crate) rendered_frame: Frame,
     pub(crate) next_frame: Frame,
@@ -821,6 +822,7 @@ impl Window {
             text_style_stack: Vec::new(),
             element_offset_stack: Vec::new(),
             content_mask_stack: Vec::new(),
+            element_opacity: None,
             requested_autoscroll: None,
             rendered_frame: Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
             next_frame: Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
@@ -1854,6 +1856,42 @@ impl<'a> WindowContext<'a> {
         }
     }
 
+    /// Invoke the given function with the given opacity applied on top of the current one.
+    /// This method should only be called during element drawing.
+    pub fn with_element_opacity<R>(
+        &mut self,
+        opacity: Option<f32>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        debug_assert!(
+            matches!(
+                self.window.draw_phase,
+                DrawPhase::Prepaint | DrawPhase::Paint
+            ),
+            "this method can only be called during request_layout, prepaint, or paint"
+        );
+        if let Some(opacity) = opacity {
+            let previous_opacity = self.window.element_opacity;
+            self.window.element_opacity = Some(self.element_opacity() * opacity.clamp(0., 1.));
+            let result = f(self);
+            self.window.element_opacity = previous_opacity;
+            result
+        } else {
+            f(self)
+        }
+    }
+
+    /// The opacity that will be applied to everything painted at this point of element drawing.
+    pub fn element_opacity(&self) -> f32 {
+        self.window.element_opacity.unwrap_or(1.)
+    }
+
+    /// Applies the current element opacity to the given color.
+    fn apply_opacity(&self, mut color: Hsla) -> Hsla {
+        color.a *= self.element_opacity();
+        color
+    }
+
     /// Updates the global element offset relative to the current offset. This is used to implement
     /// scrolling. This method should only be called during the prepaint phase of element drawing.
     pub fn with_element_offset<R>(
@@ -2299,7 +2337,7 @@ impl<'a> WindowContext<'a> {
                 bounds: shadow_bounds.scale(scale_factor),
                 content_mask: content_mask.scale(scale_factor),
                 corner_radii: corner_radii.scale(scale_factor),
-                color: shadow.color,
+                color: self.apply_opacity(shadow.color),
             });
         }
     }
@@ -2323,8 +2361,8 @@ impl<'a> WindowContext<'a> {
             pad: 0,
             bounds: quad.bounds.scale(scale_factor),
             content_mask: content_mask.scale(scale_factor),
-            background: quad.background,
-            border_color: quad.border_color,
+            background: self.apply_opacity(quad.background),
+            border_color: self.apply_opacity(quad.border_color),
             corner_radii: quad.corner_radii.scale(scale_factor),
             border_widths: quad.border_widths.scale(scale_factor),
         });
@@ -2343,7 +2381,7 @@ impl<'a> WindowContext<'a> {
         let scale_factor = self.scale_factor();
         let content_mask = self.content_mask();
         path.content_mask = content_mask;
-        path.color = color.into();
+        path.color = self.apply_opacity(color.into());
         self.window
             .next_frame
             .scene
@@ -2382,7 +2420,7 @@ impl<'a> WindowContext<'a> {
             pad: 0,
             bounds: bounds.scale(scale_factor),
             content_mask: content_mask.scale(scale_factor),
-            color: style.color.unwrap_or_default(),
+            color: self.apply_opacity(style.color.unwrap_or_default()),
             thickness: style.thickness.scale(scale_factor),
             wavy: style.wavy,
         });
@@ -2417,7 +2455,7 @@ impl<'a> WindowContext<'a> {
             bounds: bounds.scale(scale_factor),
             content_mask: content_mask.scale(scale_factor),
             thickness: style.thickness.scale(scale_factor),
-            color: style.color.unwrap_or_default(),
+            color: self.apply_opacity(style.color.unwrap_or_default()),
             wavy: false,
         });
     }
@@ -2474,6 +2512,7 @@ impl<'a> WindowContext<'a> {
                 size: tile.bounds.size.map(Into::into),
             };
             let content_mask = self.content_mask().scale(scale_factor);
+            let color = self.apply_opacity(color);
             self.window
                 .next_frame
                 .scene
@@ -2545,7 +2584,9 @@ impl<'a> WindowContext<'a> {
                 .scene
                 .insert_primitive(PolychromeSprite {
                     order: 0,
+                    pad: 0,
                     grayscale: false,
+                    opacity: self.element_opacity(),
                     bounds,
                     corner_radii: Default::default(),
                     content_mask,
@@ -2594,6 +2635,7 @@ impl<'a> WindowContext<'a> {
             return Ok(());
         };
         let content_mask = self.content_mask().scale(scale_factor);
+        let color = self.apply_opacity(color);
 
         self.window
             .next_frame
@@ -2646,7 +2688,9 @@ impl<'a> WindowContext<'a> {
             .scene
             .insert_primitive(PolychromeSprite {
                 order: 0,
+                pad: 0,
                 grayscale,
+                opacity: self.element_opacity(),
                 bounds,
                 content_mask,
                 corner_radii,
@@ -181,6 +181,8 @@ pub struct Diagnostic {
     pub source: Option<String>,
     /// A machine-readable code that identifies this diagnostic.
     pub code: Option<String>,
+    /// A URL to the documentation of this diagnostic's code.
+    pub code_description: Option<String>,
     /// Whether this diagnostic is a hint, warning, or error.
     pub severity: DiagnosticSeverity,
     /// The human-readable message associated with this diagnostic.
@@ -205,6 +207,23 @@ pub struct Diagnostic {
     pub is_disk_based: bool,
     /// Whether this diagnostic marks unnecessary code.
     pub is_unnecessary: bool,
+    /// The locations in other files that the language server relates to this diagnostic.
+    ///
+    /// Related locations in the same file are stored as the other diagnostics of the
+    /// group instead.
+    pub related_locations: Vec<DiagnosticRelatedLocation>,
+}
+
+/// A location in another file that a language server relates to a diagnostic, such as
+/// where a conflicting item was first defined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticRelatedLocation {
+    /// The absolute path of the file.
+    pub abs_path: PathBuf,
+    /// The start of the related range.
+    pub position: Unclipped<PointUtf16>,
+    /// The language server's description of the location.
+    pub message: String,
 }
 
 /// TODO - move this into the `project` crate and make it private.
@@ -3792,12 +3811,14 @@ impl Default for Diagnostic {
         Self {
             source: Default::default(),
             code: None,
+            code_description: None,
             severity: DiagnosticSeverity::ERROR,
             message: Default::default(),
             group_id: 0,
             is_primary: false,
             is_disk_based: false,
             is_unnecessary: false,
+            related_locations: Vec::new(),
         }
     }
 }
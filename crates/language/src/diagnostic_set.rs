@@ -61,10 +61,18 @@ impl DiagnosticEntry<PointUtf16> {
             .clone()
             .map(lsp::NumberOrString::String);
 
+        let code_description = self
+            .diagnostic
+            .code_description
+            .as_deref()
+            .and_then(|href| lsp::Url::parse(href).ok())
+            .map(|href| lsp::CodeDescription { href });
+
         let range = range_to_lsp(self.range.clone());
 
         lsp::Diagnostic {
             code,
+            code_description,
             range,
             severity: Some(self.diagnostic.severity),
             source: self.diagnostic.source.clone(),
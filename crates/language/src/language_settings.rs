@@ -122,6 +122,9 @@ pub struct LanguageSettings {
     pub linked_edits: bool,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
+    /// The maximum width of hover popovers for this language, as a percentage
+    /// of the window width. 0 uses the default width.
+    pub hover_popover_max_width_percent: f32,
 }
 
 impl LanguageSettings {
@@ -346,6 +349,11 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: {}
     pub tasks: Option<LanguageTaskConfig>,
+    /// The maximum width of hover popovers for this language, as a percentage
+    /// of the window width, overriding the one set for all languages.
+    ///
+    /// Default: 0
+    pub hover_popover_max_width_percent: Option<f32>,
 }
 
 /// The contents of the inline completion settings.
@@ -837,6 +845,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     );
     merge(&mut settings.linked_edits, src.linked_edits);
     merge(&mut settings.tasks, src.tasks.clone());
+    merge(
+        &mut settings.hover_popover_max_width_percent,
+        src.hover_popover_max_width_percent,
+    );
 
     merge(
         &mut settings.preferred_line_length,
@@ -1,11 +1,11 @@
 //! Handles conversions of `language` items to and from the [`rpc`] protocol.
 
-use crate::{diagnostic_set::DiagnosticEntry, CursorShape, Diagnostic};
+use crate::{diagnostic_set::DiagnosticEntry, CursorShape, Diagnostic, DiagnosticRelatedLocation};
 use anyhow::{anyhow, Context as _, Result};
 use clock::ReplicaId;
 use lsp::{DiagnosticSeverity, LanguageServerId};
 use rpc::proto;
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::PathBuf, sync::Arc};
 use text::*;
 
 pub use proto::{BufferState, Operation};
@@ -211,8 +211,22 @@ pub fn serialize_diagnostics<'a>(
             is_primary: entry.diagnostic.is_primary,
             is_valid: true,
             code: entry.diagnostic.code.clone(),
+            code_description: entry.diagnostic.code_description.clone(),
             is_disk_based: entry.diagnostic.is_disk_based,
             is_unnecessary: entry.diagnostic.is_unnecessary,
+            related_locations: entry
+                .diagnostic
+                .related_locations
+                .iter()
+                .map(|location| proto::DiagnosticRelatedLocation {
+                    abs_path: location.abs_path.to_string_lossy().into_owned(),
+                    position: Some(proto::PointUtf16 {
+                        row: location.position.0.row,
+                        column: location.position.0.column,
+                    }),
+                    message: location.message.clone(),
+                })
+                .collect(),
         })
         .collect()
 }
@@ -410,9 +424,22 @@ pub fn deserialize_diagnostics(
                     message: diagnostic.message,
                     group_id: diagnostic.group_id as usize,
                     code: diagnostic.code,
+                    code_description: diagnostic.code_description,
                     is_primary: diagnostic.is_primary,
                     is_disk_based: diagnostic.is_disk_based,
                     is_unnecessary: diagnostic.is_unnecessary,
+                    related_locations: diagnostic
+                        .related_locations
+                        .into_iter()
+                        .filter_map(|location| {
+                            let position = location.position?;
+                            Some(DiagnosticRelatedLocation {
+                                abs_path: PathBuf::from(location.abs_path),
+                                position: Unclipped(PointUtf16::new(position.row, position.column)),
+                                message: location.message,
+                            })
+                        })
+                        .collect(),
                 },
             })
         })
@@ -1479,6 +1479,11 @@ impl<'a> SyntaxLayer<'a> {
         }
     }
 
+    /// How many injections deep this layer is. The buffer's own language has a depth of 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
     /// Returns the root node for this layer.
     pub fn node(&self) -> Node<'a> {
         self.tree
@@ -71,6 +71,7 @@ pub struct LanguageServer {
     name: Arc<str>,
     capabilities: ServerCapabilities,
     code_action_kinds: Option<Vec<CodeActionKind>>,
+    hover_content_format: Vec<MarkupKind>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     io_handlers: Arc<Mutex<HashMap<i32, IoHandler>>>,
@@ -383,6 +384,7 @@ impl LanguageServer {
             name: "".into(),
             capabilities: Default::default(),
             code_action_kinds,
+            hover_content_format: vec![MarkupKind::Markdown],
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
@@ -399,6 +401,12 @@ impl LanguageServer {
         self.code_action_kinds.clone()
     }
 
+    /// Sets the formats of hover contents this language server is asked for, the most
+    /// preferred first. Takes effect on [`LanguageServer::initialize`].
+    pub fn set_hover_content_format(&mut self, content_format: Vec<MarkupKind>) {
+        self.hover_content_format = content_format;
+    }
+
     async fn handle_input<Stdout, F>(
         stdout: Stdout,
         mut on_unhandled_notification: F,
@@ -620,7 +628,7 @@ impl LanguageServer {
                         ..Default::default()
                     }),
                     hover: Some(HoverClientCapabilities {
-                        content_format: Some(vec![MarkupKind::Markdown]),
+                        content_format: Some(self.hover_content_format.clone()),
                         dynamic_registration: None,
                     }),
                     inlay_hint: Some(InlayHintClientCapabilities {
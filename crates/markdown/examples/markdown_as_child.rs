@@ -88,6 +88,14 @@ pub fn main() {
                     },
                     break_style: Default::default(),
                     heading: Default::default(),
+                    code_block_line_numbers: false,
+                    code_block_line_number_color: Default::default(),
+                    highlight_inline_code_with_doc_language: false,
+                    inline_code_language: None,
+                    text_align: TextAlign::Left,
+                    paragraph_indent: 0.,
+                    images_as_links: false,
+                    local_images: Default::default(),
                 };
                 let markdown = cx.new_view(|cx| {
                     Markdown::new(MARKDOWN_EXAMPLE.into(), markdown_style, None, cx, None)
@@ -1,18 +1,19 @@
 pub mod parser;
 
 use crate::parser::CodeBlockKind;
+use collections::HashMap;
 use futures::FutureExt;
 use gpui::{
-    actions, point, quad, AnyElement, AppContext, Bounds, ClipboardItem, CursorStyle,
+    actions, img, point, quad, AnyElement, AppContext, Bounds, ClipboardItem, CursorStyle,
     DispatchPhase, Edges, FocusHandle, FocusableView, FontStyle, FontWeight, GlobalElementId,
     Hitbox, Hsla, KeyContext, Length, MouseDownEvent, MouseEvent, MouseMoveEvent, MouseUpEvent,
-    Point, Render, StrikethroughStyle, StyleRefinement, StyledText, Task, TextLayout, TextRun,
-    TextStyle, TextStyleRefinement, View,
+    ObjectFit, Point, Render, StrikethroughStyle, StyleRefinement, StyledText, Task, TextAlign,
+    TextLayout, TextRun, TextStyle, TextStyleRefinement, View,
 };
 use language::{Language, LanguageRegistry, Rope};
-use parser::{parse_markdown, MarkdownEvent, MarkdownTag, MarkdownTagEnd};
+use parser::{parse_markdown, AlertKind, MarkdownEvent, MarkdownTag, MarkdownTagEnd};
 
-use std::{iter, mem, ops::Range, rc::Rc, sync::Arc};
+use std::{iter, mem, ops::Range, path::PathBuf, rc::Rc, sync::Arc};
 use theme::SyntaxTheme;
 use ui::prelude::*;
 use util::{ResultExt, TryFutureExt};
@@ -30,6 +31,24 @@ pub struct MarkdownStyle {
     pub selection_background_color: Hsla,
     pub break_style: StyleRefinement,
     pub heading: StyleRefinement,
+    /// Whether to prefix every line of a code block with its line number.
+    pub code_block_line_numbers: bool,
+    pub code_block_line_number_color: Hsla,
+    /// Whether to highlight inline code in the language of the last code block before it.
+    pub highlight_inline_code_with_doc_language: bool,
+    /// The language to highlight inline code in when no doc language applies to it, such as
+    /// the language of the buffer a hover was requested in. Without one, inline code is
+    /// rendered in the flat `inline_code` style.
+    pub inline_code_language: Option<Arc<Language>>,
+    /// How to align the wrapped lines of prose. Code blocks are always left-aligned.
+    pub text_align: TextAlign,
+    /// The left padding of every paragraph, in rems. Paragraphs in lists aren't indented.
+    pub paragraph_indent: f32,
+    /// Whether images that aren't rendered are shown as links to them, labeled with their
+    /// alt text, rather than as their alt text alone.
+    pub images_as_links: bool,
+    /// The files of the images rendered in place of their alt text, by their destination.
+    pub local_images: HashMap<SharedString, PathBuf>,
 }
 
 impl Default for MarkdownStyle {
@@ -46,6 +65,14 @@ impl Default for MarkdownStyle {
             selection_background_color: Default::default(),
             break_style: Default::default(),
             heading: Default::default(),
+            code_block_line_numbers: false,
+            code_block_line_number_color: Default::default(),
+            highlight_inline_code_with_doc_language: false,
+            inline_code_language: None,
+            text_align: TextAlign::Left,
+            paragraph_indent: 0.,
+            images_as_links: false,
+            local_images: HashMap::default(),
         }
     }
 }
@@ -54,6 +81,7 @@ pub struct Markdown {
     selection: Selection,
     pressed_link: Option<RenderedLink>,
     autoscroll_request: Option<usize>,
+    rendered_text: Option<RenderedText>,
     style: MarkdownStyle,
     parsed_markdown: ParsedMarkdown,
     should_reparse: bool,
@@ -61,6 +89,9 @@ pub struct Markdown {
     focus_handle: FocusHandle,
     language_registry: Option<Arc<LanguageRegistry>>,
     fallback_code_block_language: Option<String>,
+    on_url_click: Option<Rc<dyn Fn(SharedString, &mut WindowContext)>>,
+    parse_text_only: bool,
+    render_line_budget: Option<usize>,
 }
 
 actions!(markdown, [Copy]);
@@ -72,6 +103,29 @@ impl Markdown {
         language_registry: Option<Arc<LanguageRegistry>>,
         cx: &mut ViewContext<Self>,
         fallback_code_block_language: Option<String>,
+    ) -> Self {
+        Self::new_internal(
+            source,
+            style,
+            language_registry,
+            cx,
+            fallback_code_block_language,
+            false,
+        )
+    }
+
+    /// Shows the source as plain text, without parsing it as markdown.
+    pub fn new_text(source: String, style: MarkdownStyle, cx: &mut ViewContext<Self>) -> Self {
+        Self::new_internal(source, style, None, cx, None, true)
+    }
+
+    fn new_internal(
+        source: String,
+        style: MarkdownStyle,
+        language_registry: Option<Arc<LanguageRegistry>>,
+        cx: &mut ViewContext<Self>,
+        fallback_code_block_language: Option<String>,
+        parse_text_only: bool,
     ) -> Self {
         let focus_handle = cx.focus_handle();
         let mut this = Self {
@@ -79,6 +133,7 @@ impl Markdown {
             selection: Selection::default(),
             pressed_link: None,
             autoscroll_request: None,
+            rendered_text: None,
             style,
             should_reparse: false,
             parsed_markdown: ParsedMarkdown::default(),
@@ -86,11 +141,38 @@ impl Markdown {
             focus_handle,
             language_registry,
             fallback_code_block_language,
+            on_url_click: None,
+            parse_text_only,
+            render_line_budget: None,
         };
         this.parse(cx);
         this
     }
 
+    /// Handle clicked links with the given callback instead of opening them with the platform.
+    pub fn on_url_click(
+        mut self,
+        on_url_click: impl Fn(SharedString, &mut WindowContext) + 'static,
+    ) -> Self {
+        self.on_url_click = Some(Rc::new(on_url_click));
+        self
+    }
+
+    /// Lays out only the blocks starting within the first `line_budget` lines of the source
+    /// in the first frame, and the rest in the next one, so that long content shows sooner.
+    pub fn render_incrementally(mut self, line_budget: usize) -> Self {
+        self.render_line_budget = Some(line_budget);
+        self
+    }
+
+    /// Opens a link of this markdown the way clicking it would.
+    pub fn open_url(&self, url: SharedString, cx: &mut WindowContext) {
+        match &self.on_url_click {
+            Some(on_url_click) => on_url_click(url, cx),
+            None => cx.open_url(&url),
+        }
+    }
+
     pub fn append(&mut self, text: &str, cx: &mut ViewContext<Self>) {
         self.source.push_str(text);
         self.parse(cx);
@@ -103,6 +185,7 @@ impl Markdown {
         self.source = source;
         self.selection = Selection::default();
         self.autoscroll_request = None;
+        self.rendered_text = None;
         self.pending_parse = None;
         self.should_reparse = false;
         self.parsed_markdown = ParsedMarkdown::default();
@@ -113,10 +196,70 @@ impl Markdown {
         &self.source
     }
 
+    /// Selects a range of the source, scrolling it into view, such as to show which link
+    /// the keyboard focuses.
+    pub fn select_source_range(&mut self, range: Range<usize>, cx: &mut ViewContext<Self>) {
+        self.autoscroll_request = Some(range.start);
+        self.selection = Selection {
+            start: range.start,
+            end: range.end,
+            reversed: false,
+            pending: false,
+        };
+        cx.notify();
+    }
+
+    pub fn clear_selection(&mut self, cx: &mut ViewContext<Self>) {
+        self.selection = Selection::default();
+        cx.notify();
+    }
+
     pub fn parsed_markdown(&self) -> &ParsedMarkdown {
         &self.parsed_markdown
     }
 
+    pub fn style(&self) -> &MarkdownStyle {
+        &self.style
+    }
+
+    pub fn set_style(&mut self, style: MarkdownStyle, cx: &mut ViewContext<Self>) {
+        self.style = style;
+        cx.notify();
+    }
+
+    /// The window position at which the given source index was laid out in the last frame.
+    pub fn position_for_source_index(&self, source_index: usize) -> Option<Point<Pixels>> {
+        let (position, _) = self
+            .rendered_text
+            .as_ref()?
+            .position_for_source_index(source_index)?;
+        Some(position)
+    }
+
+    /// The text and style runs of each line laid out in the last frame.
+    pub fn rendered_lines(&self) -> impl Iterator<Item = (&str, &[TextRun])> {
+        self.rendered_text
+            .iter()
+            .flat_map(|text| text.lines.iter())
+            .map(|line| (line.text.as_ref(), line.runs.as_slice()))
+    }
+
+    /// The text and bounds of each line laid out in the last frame.
+    pub fn rendered_line_bounds(&self) -> impl Iterator<Item = (&str, Bounds<Pixels>)> {
+        self.rendered_text
+            .iter()
+            .flat_map(|text| text.lines.iter())
+            .map(|line| (line.text.as_ref(), line.layout.bounds()))
+    }
+
+    /// The source range and destination of each link laid out in the last frame.
+    pub fn rendered_links(&self) -> impl Iterator<Item = (Range<usize>, &str)> {
+        self.rendered_text
+            .iter()
+            .flat_map(|text| text.links.iter())
+            .map(|link| (link.source_range.clone(), link.destination_url.as_ref()))
+    }
+
     fn copy(&self, text: &RenderedText, cx: &mut ViewContext<Self>) {
         if self.selection.end <= self.selection.start {
             return;
@@ -136,9 +279,14 @@ impl Markdown {
         }
 
         let text = self.source.clone();
+        let parse_text_only = self.parse_text_only;
         let parsed = cx.background_executor().spawn(async move {
             let text = SharedString::from(text);
-            let events = Arc::from(parse_markdown(text.as_ref()));
+            let events = if parse_text_only {
+                Arc::from([(0..text.len(), MarkdownEvent::Text)])
+            } else {
+                Arc::from(parse_markdown(text.as_ref()))
+            };
             anyhow::Ok(ParsedMarkdown {
                 source: text,
                 events,
@@ -447,7 +595,7 @@ impl MarkdownElement {
                 if phase.bubble() {
                     if let Some(pressed_link) = markdown.pressed_link.take() {
                         if Some(&pressed_link) == rendered_text.link_for_position(event.position) {
-                            cx.open_url(&pressed_link.destination_url);
+                            markdown.open_url(pressed_link.destination_url, cx);
                         }
                     }
                 } else {
@@ -521,22 +669,53 @@ impl Element for MarkdownElement {
             self.style.base_text_style.clone(),
             self.style.syntax.clone(),
         );
-        let parsed_markdown = self.markdown.read(cx).parsed_markdown.clone();
+        builder.text_align = self.style.text_align;
+        builder.inline_code_language = self.style.inline_code_language.clone();
+        let markdown = self.markdown.read(cx);
+        let parsed_markdown = markdown.parsed_markdown.clone();
+        let line_budget = markdown
+            .render_line_budget
+            .filter(|_| !parsed_markdown.events.is_empty());
         let markdown_end = if let Some(last) = parsed_markdown.events.last() {
             last.0.end
         } else {
             0
         };
-        for (range, event) in parsed_markdown.events.iter() {
+        // The end of the lines laid out in the first frame, when rendering incrementally.
+        let budget_end = line_budget.and_then(|line_budget| {
+            parsed_markdown
+                .source
+                .match_indices('\n')
+                .nth(line_budget.saturating_sub(1))
+                .map(|(ix, _)| ix + 1)
+        });
+        let mut depth = 0_usize;
+        let mut truncated = false;
+        // The alt text of rendered images isn't shown.
+        let mut within_rendered_image = false;
+        for (ix, (range, event)) in parsed_markdown.events.iter().enumerate() {
+            if depth == 0 && budget_end.map_or(false, |budget_end| range.start >= budget_end) {
+                truncated = true;
+                break;
+            }
+            match event {
+                MarkdownEvent::Start(_) => depth += 1,
+                MarkdownEvent::End(_) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            if within_rendered_image {
+                within_rendered_image = *event != MarkdownEvent::End(MarkdownTagEnd::Image);
+                continue;
+            }
             match event {
                 MarkdownEvent::Start(tag) => {
                     match tag {
                         MarkdownTag::Paragraph => {
-                            builder.push_div(
-                                div().mb_2().line_height(rems(1.3)),
-                                range,
-                                markdown_end,
-                            );
+                            let mut paragraph = div().mb_2().line_height(rems(1.3));
+                            if builder.list_stack.is_empty() && self.style.paragraph_indent > 0. {
+                                paragraph = paragraph.pl(rems(self.style.paragraph_indent));
+                            }
+                            builder.push_div(paragraph, range, markdown_end);
                         }
                         MarkdownTag::Heading { level, .. } => {
                             let mut heading = div().mb_2();
@@ -565,12 +744,51 @@ impl Element for MarkdownElement {
                                 markdown_end,
                             );
                         }
-                        MarkdownTag::CodeBlock(kind) => {
-                            let language = if let CodeBlockKind::Fenced(language) = kind {
-                                self.load_language(language.as_ref(), cx)
-                            } else {
-                                None
+                        MarkdownTag::Alert(kind) => {
+                            let status = cx.theme().status();
+                            let (color, icon) = match kind {
+                                AlertKind::Note => (status.info, IconName::Book),
+                                AlertKind::Tip => (status.success, IconName::Sparkle),
+                                AlertKind::Important => (status.hint, IconName::Bolt),
+                                AlertKind::Warning => {
+                                    (status.warning, IconName::ExclamationTriangle)
+                                }
+                                AlertKind::Caution => (status.error, IconName::XCircle),
                             };
+                            let mut background = color;
+                            background.fade_out(0.9);
+                            // Alerts reset the muted style of the block quotes they're nested in.
+                            builder.push_text_style(TextStyleRefinement::default());
+                            builder.push_div(
+                                div()
+                                    .pl_4()
+                                    .pr_2()
+                                    .py_1()
+                                    .mb_2()
+                                    .border_l_4()
+                                    .border_color(color)
+                                    .bg(background)
+                                    .child(
+                                        h_flex()
+                                            .gap_1()
+                                            .child(
+                                                Icon::new(icon)
+                                                    .size(IconSize::Small)
+                                                    .color(Color::Custom(color)),
+                                            )
+                                            .child(
+                                                Label::new(kind.title())
+                                                    .weight(FontWeight::BOLD)
+                                                    .color(Color::Custom(color)),
+                                            ),
+                                    ),
+                                range,
+                                markdown_end,
+                            );
+                        }
+                        MarkdownTag::CodeBlock(kind) => {
+                            let language = highlighted_code_block_language(kind)
+                                .and_then(|language| self.load_language(language, cx));
 
                             let mut d = div().w_full().rounded_lg();
                             d.style().refine(&self.style.code_block);
@@ -578,19 +796,34 @@ impl Element for MarkdownElement {
                                 builder.push_text_style(code_block_text_style.to_owned());
                             }
                             builder.push_code_block(language);
+                            if self.style.code_block_line_numbers {
+                                builder.number_code_block_lines(
+                                    code_block_line_count(&parsed_markdown, ix),
+                                    self.style.code_block_line_number_color,
+                                );
+                            }
                             builder.push_div(d, range, markdown_end);
                         }
                         MarkdownTag::HtmlBlock => builder.push_div(div(), range, markdown_end),
                         MarkdownTag::List(bullet_index) => {
-                            builder.push_list(*bullet_index);
+                            let bullet_width =
+                                list_bullet_width(&parsed_markdown.events, ix, *bullet_index);
+                            builder.push_list(*bullet_index, bullet_width);
                             builder.push_div(div().pl_4(), range, markdown_end);
                         }
                         MarkdownTag::Item => {
-                            let bullet = if let Some(bullet_index) = builder.next_bullet_index() {
-                                format!("{}.", bullet_index)
-                            } else {
-                                "•".to_string()
-                            };
+                            let bullet = list_item_bullet(
+                                &parsed_markdown.events,
+                                ix,
+                                builder.next_bullet_index(),
+                            );
+                            // The bullets of a list are as wide as its widest one, so that the
+                            // text of its items, their later paragraphs and their nested lists
+                            // are aligned.
+                            let bullet_width = builder
+                                .list_stack
+                                .last()
+                                .map_or(1, |entry| entry.bullet_width);
                             builder.push_div(
                                 div()
                                     .h_flex()
@@ -598,7 +831,13 @@ impl Element for MarkdownElement {
                                     .line_height(rems(1.3))
                                     .items_start()
                                     .gap_1()
-                                    .child(bullet),
+                                    .child(
+                                        h_flex()
+                                            .flex_none()
+                                            .w(rems(0.6 * bullet_width as f32))
+                                            .justify_end()
+                                            .child(bullet),
+                                    ),
                                 range,
                                 markdown_end,
                             );
@@ -628,6 +867,64 @@ impl Element for MarkdownElement {
                                 builder.push_text_style(self.style.link.clone())
                             }
                         }
+                        MarkdownTag::Image { dest_url, .. }
+                            if self.style.local_images.contains_key(dest_url) =>
+                        {
+                            let path = self.style.local_images[dest_url].clone();
+                            builder.push_element(
+                                img(path)
+                                    .max_w_full()
+                                    .object_fit(ObjectFit::Contain)
+                                    .into_any_element(),
+                            );
+                            within_rendered_image = true;
+                        }
+                        MarkdownTag::Image { dest_url, .. } if self.style.images_as_links => {
+                            if builder.code_block_stack.is_empty() {
+                                builder.push_link(dest_url.clone(), range.clone());
+                                builder.push_text_style(self.style.link.clone())
+                            }
+                        }
+                        MarkdownTag::Table(_) => {
+                            builder.push_div(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .w_full()
+                                    .mb_2()
+                                    .border_1()
+                                    .border_color(self.style.rule_color)
+                                    .rounded_md(),
+                                range,
+                                markdown_end,
+                            );
+                        }
+                        MarkdownTag::TableHead => {
+                            builder.push_text_style(TextStyleRefinement {
+                                font_weight: Some(FontWeight::BOLD),
+                                ..Default::default()
+                            });
+                            builder.push_div(
+                                div()
+                                    .flex()
+                                    .flex_row()
+                                    .border_b_1()
+                                    .border_color(self.style.rule_color),
+                                range,
+                                markdown_end,
+                            );
+                        }
+                        MarkdownTag::TableRow => {
+                            builder.push_div(div().flex().flex_row(), range, markdown_end);
+                        }
+                        MarkdownTag::TableCell => {
+                            // Without `w_0`, text doesn't wrap to the width of the cell.
+                            builder.push_div(
+                                div().flex_1().w_0().px_2().py_1(),
+                                range,
+                                markdown_end,
+                            );
+                        }
                         MarkdownTag::MetadataBlock(_) => {}
                         _ => log::error!("unsupported markdown tag {:?}", tag),
                     }
@@ -661,6 +958,13 @@ impl Element for MarkdownElement {
                         builder.pop_div();
                         builder.pop_div();
                     }
+                    MarkdownTagEnd::Table => builder.pop_div(),
+                    MarkdownTagEnd::TableHead => {
+                        builder.pop_div();
+                        builder.pop_text_style()
+                    }
+                    MarkdownTagEnd::TableRow => builder.pop_div(),
+                    MarkdownTagEnd::TableCell => builder.pop_div(),
                     MarkdownTagEnd::Emphasis => builder.pop_text_style(),
                     MarkdownTagEnd::Strong => builder.pop_text_style(),
                     MarkdownTagEnd::Strikethrough => builder.pop_text_style(),
@@ -669,6 +973,11 @@ impl Element for MarkdownElement {
                             builder.pop_text_style()
                         }
                     }
+                    MarkdownTagEnd::Image if self.style.images_as_links => {
+                        if builder.code_block_stack.is_empty() {
+                            builder.pop_text_style()
+                        }
+                    }
                     _ => log::error!("unsupported markdown tag end: {:?}", tag),
                 },
                 MarkdownEvent::Text => {
@@ -676,7 +985,11 @@ impl Element for MarkdownElement {
                 }
                 MarkdownEvent::Code => {
                     builder.push_text_style(self.style.inline_code.clone());
-                    builder.push_text(&parsed_markdown.source[range.clone()], range.start);
+                    builder.push_inline_code(
+                        &parsed_markdown.source[range.clone()],
+                        range.start,
+                        self.style.highlight_inline_code_with_doc_language,
+                    );
                     builder.pop_text_style();
                 }
                 MarkdownEvent::Html => {
@@ -696,6 +1009,8 @@ impl Element for MarkdownElement {
                     );
                     builder.pop_div()
                 }
+                // Task list markers are rendered as the bullet of their list item.
+                MarkdownEvent::TaskListMarker(_) => {}
                 MarkdownEvent::SoftBreak => builder.push_text(" ", range.start),
                 MarkdownEvent::HardBreak => {
                     let mut d = div().py_3();
@@ -706,6 +1021,16 @@ impl Element for MarkdownElement {
                 _ => log::error!("unsupported markdown event {:?}", event),
             }
         }
+        // The blocks after the budget are laid out in the next frame.
+        if line_budget.is_some() {
+            self.markdown
+                .update(cx, |markdown, _| markdown.render_line_budget = None);
+            if truncated {
+                let markdown = self.markdown.downgrade();
+                cx.spawn(|mut cx| async move { markdown.update(&mut cx, |_, cx| cx.notify()) })
+                    .detach_and_log_err(cx);
+            }
+        }
         let mut rendered_markdown = builder.build();
         let child_layout_id = rendered_markdown.element.request_layout(cx);
         let layout_id = cx.request_layout(gpui::Style::default(), [child_layout_id]);
@@ -722,6 +1047,9 @@ impl Element for MarkdownElement {
         let hitbox = cx.insert_hitbox(bounds, false);
         rendered_markdown.element.prepaint(cx);
         self.autoscroll(&rendered_markdown.text, cx);
+        let text = rendered_markdown.text.clone();
+        self.markdown
+            .update(cx, |markdown, _| markdown.rendered_text = Some(text));
         hitbox
     }
 
@@ -764,6 +1092,36 @@ impl IntoElement for MarkdownElement {
     }
 }
 
+/// Code blocks tagged with these languages, such as ASCII diagrams, are rendered without
+/// highlighting, which would otherwise come from a wrongly inferred language and obscure them.
+const PLAIN_TEXT_CODE_BLOCK_LANGUAGES: &[&str] = &["text", "txt", "plaintext", "diagram", "ascii"];
+
+/// The language to highlight a code block in, if any.
+fn highlighted_code_block_language(kind: &CodeBlockKind) -> Option<&str> {
+    let language = kind.language()?;
+    if PLAIN_TEXT_CODE_BLOCK_LANGUAGES
+        .iter()
+        .any(|plain_text| language.eq_ignore_ascii_case(plain_text))
+    {
+        None
+    } else {
+        Some(language)
+    }
+}
+
+/// The number of lines in the code block starting at the given event.
+fn code_block_line_count(parsed_markdown: &ParsedMarkdown, start_ix: usize) -> usize {
+    let mut text = String::new();
+    for (range, event) in &parsed_markdown.events[start_ix + 1..] {
+        match event {
+            MarkdownEvent::End(MarkdownTagEnd::CodeBlock) => break,
+            MarkdownEvent::Text => text.push_str(&parsed_markdown.source[range.clone()]),
+            _ => {}
+        }
+    }
+    text.lines().count()
+}
+
 struct MarkdownElementBuilder {
     div_stack: Vec<Div>,
     rendered_lines: Vec<RenderedLine>,
@@ -773,8 +1131,18 @@ struct MarkdownElementBuilder {
     base_text_style: TextStyle,
     text_style_stack: Vec<TextStyleRefinement>,
     code_block_stack: Vec<Option<Arc<Language>>>,
+    code_block_line_numbers: Option<CodeBlockLineNumbers>,
+    last_code_block_language: Option<Arc<Language>>,
+    inline_code_language: Option<Arc<Language>>,
     list_stack: Vec<ListStackEntry>,
     syntax_theme: Arc<SyntaxTheme>,
+    text_align: TextAlign,
+}
+
+struct CodeBlockLineNumbers {
+    next_line_number: usize,
+    width: usize,
+    color: Hsla,
 }
 
 #[derive(Default)]
@@ -786,6 +1154,8 @@ struct PendingLine {
 
 struct ListStackEntry {
     bullet_index: Option<u64>,
+    /// The number of characters of the widest bullet of the list.
+    bullet_width: usize,
 }
 
 impl MarkdownElementBuilder {
@@ -799,8 +1169,12 @@ impl MarkdownElementBuilder {
             base_text_style,
             text_style_stack: Vec::new(),
             code_block_stack: Vec::new(),
+            code_block_line_numbers: None,
+            last_code_block_language: None,
+            inline_code_language: None,
             list_stack: Vec::new(),
             syntax_theme,
+            text_align: TextAlign::Left,
         }
     }
 
@@ -855,8 +1229,19 @@ impl MarkdownElementBuilder {
         self.div_stack.last_mut().unwrap().extend(iter::once(div));
     }
 
-    fn push_list(&mut self, bullet_index: Option<u64>) {
-        self.list_stack.push(ListStackEntry { bullet_index });
+    fn push_element(&mut self, element: AnyElement) {
+        self.flush_text();
+        self.div_stack
+            .last_mut()
+            .unwrap()
+            .extend(iter::once(element));
+    }
+
+    fn push_list(&mut self, bullet_index: Option<u64>, bullet_width: usize) {
+        self.list_stack.push(ListStackEntry {
+            bullet_index,
+            bullet_width,
+        });
     }
 
     fn next_bullet_index(&mut self) -> Option<u64> {
@@ -872,11 +1257,24 @@ impl MarkdownElementBuilder {
     }
 
     fn push_code_block(&mut self, language: Option<Arc<Language>>) {
+        if language.is_some() {
+            self.last_code_block_language = language.clone();
+        }
         self.code_block_stack.push(language);
     }
 
     fn pop_code_block(&mut self) {
         self.code_block_stack.pop();
+        self.code_block_line_numbers = None;
+    }
+
+    /// Prefixes the lines of the current code block with right-aligned line numbers.
+    fn number_code_block_lines(&mut self, line_count: usize, color: Hsla) {
+        self.code_block_line_numbers = Some(CodeBlockLineNumbers {
+            next_line_number: 1,
+            width: line_count.max(1).to_string().len(),
+            color,
+        });
     }
 
     fn push_link(&mut self, destination_url: SharedString, source_range: Range<usize>) {
@@ -886,43 +1284,122 @@ impl MarkdownElementBuilder {
         });
     }
 
+    /// Pushes inline code, highlighted in the language of the last code block when `highlight`
+    /// is set, since inline code often refers to the identifiers of the doc's code blocks.
+    /// Otherwise it's highlighted in the `inline_code_language`, if any.
+    fn push_inline_code(&mut self, text: &str, source_index: usize, highlight: bool) {
+        let language = highlight
+            .then(|| self.last_code_block_language.clone())
+            .flatten()
+            .or_else(|| self.inline_code_language.clone());
+        if let Some(language) = language {
+            self.code_block_stack.push(Some(language));
+            self.push_text(text, source_index);
+            self.code_block_stack.pop();
+        } else {
+            self.push_text(text, source_index);
+        }
+    }
+
     fn push_text(&mut self, text: &str, source_index: usize) {
-        self.pending_line.source_mappings.push(SourceMapping {
-            rendered_index: self.pending_line.text.len(),
-            source_index,
-        });
-        self.pending_line.text.push_str(text);
+        let runs = self.text_runs(text);
+        if self.code_block_line_numbers.is_some() {
+            self.push_numbered_lines(text, source_index, runs);
+        } else {
+            self.pending_line.source_mappings.push(SourceMapping {
+                rendered_index: self.pending_line.text.len(),
+                source_index,
+            });
+            self.pending_line.text.push_str(text);
+            self.pending_line.runs.extend(runs);
+        }
         self.current_source_index = source_index + text.len();
+    }
 
+    fn text_runs(&self, text: &str) -> Vec<TextRun> {
+        let mut runs = Vec::new();
         if let Some(Some(language)) = self.code_block_stack.last() {
             let mut offset = 0;
             for (range, highlight_id) in language.highlight_text(&Rope::from(text), 0..text.len()) {
                 if range.start > offset {
-                    self.pending_line
-                        .runs
-                        .push(self.text_style().to_run(range.start - offset));
+                    runs.push(self.text_style().to_run(range.start - offset));
                 }
 
                 let mut run_style = self.text_style();
                 if let Some(highlight) = highlight_id.style(&self.syntax_theme) {
                     run_style = run_style.highlight(highlight);
                 }
-                self.pending_line.runs.push(run_style.to_run(range.len()));
+                runs.push(run_style.to_run(range.len()));
                 offset = range.end;
             }
 
             if offset < text.len() {
-                self.pending_line
-                    .runs
-                    .push(self.text_style().to_run(text.len() - offset));
+                runs.push(self.text_style().to_run(text.len() - offset));
             }
         } else {
-            self.pending_line
-                .runs
-                .push(self.text_style().to_run(text.len()));
+            runs.push(self.text_style().to_run(text.len()));
+        }
+        runs
+    }
+
+    /// Pushes code block text with a line number in front of every line. The line numbers
+    /// only exist in the rendered text, so each line gets its own source mapping, and the
+    /// highlight runs, computed for the whole text, are split at the line boundaries.
+    fn push_numbered_lines(&mut self, text: &str, source_index: usize, runs: Vec<TextRun>) {
+        let mut runs = runs.into_iter();
+        let mut split_run: Option<TextRun> = None;
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            if self.pending_line.text.is_empty() || self.pending_line.text.ends_with('\n') {
+                self.push_line_number(source_index + offset);
+            }
+            self.pending_line.source_mappings.push(SourceMapping {
+                rendered_index: self.pending_line.text.len(),
+                source_index: source_index + offset,
+            });
+            self.pending_line.text.push_str(line);
+
+            let mut remaining = line.len();
+            while remaining > 0 {
+                let Some(mut run) = split_run.take().or_else(|| runs.next()) else {
+                    break;
+                };
+                if run.len > remaining {
+                    let mut rest = run.clone();
+                    rest.len = run.len - remaining;
+                    run.len = remaining;
+                    split_run = Some(rest);
+                }
+                remaining -= run.len;
+                self.pending_line.runs.push(run);
+            }
+            offset += line.len();
         }
     }
 
+    fn push_line_number(&mut self, source_index: usize) {
+        let Some(line_numbers) = self.code_block_line_numbers.as_mut() else {
+            return;
+        };
+        let line_number = format!(
+            "{:>width$} ",
+            line_numbers.next_line_number,
+            width = line_numbers.width
+        );
+        line_numbers.next_line_number += 1;
+        let color = line_numbers.color;
+
+        // The line number maps to the start of the line it's in front of.
+        self.pending_line.source_mappings.push(SourceMapping {
+            rendered_index: self.pending_line.text.len(),
+            source_index,
+        });
+        let mut style = self.text_style();
+        style.color = color;
+        self.pending_line.runs.push(style.to_run(line_number.len()));
+        self.pending_line.text.push_str(&line_number);
+    }
+
     fn trim_trailing_newline(&mut self) {
         if self.pending_line.text.ends_with('\n') {
             self.pending_line
@@ -939,9 +1416,19 @@ impl MarkdownElementBuilder {
             return;
         }
 
-        let text = StyledText::new(line.text).with_runs(line.runs);
+        let text_align = if self.code_block_stack.is_empty() {
+            self.text_align
+        } else {
+            TextAlign::Left
+        };
+        let line_text = SharedString::from(line.text);
+        let text = StyledText::new(line_text.clone())
+            .with_runs(line.runs.clone())
+            .with_text_align(text_align);
         self.rendered_lines.push(RenderedLine {
             layout: text.layout().clone(),
+            text: line_text,
+            runs: line.runs,
             source_mappings: line.source_mappings,
             source_end: self.current_source_index,
         });
@@ -963,31 +1450,38 @@ impl MarkdownElementBuilder {
 
 struct RenderedLine {
     layout: TextLayout,
+    text: SharedString,
+    runs: Vec<TextRun>,
     source_mappings: Vec<SourceMapping>,
     source_end: usize,
 }
 
 impl RenderedLine {
     fn rendered_index_for_source_index(&self, source_index: usize) -> usize {
-        let mapping = match self
+        // Rendered-only text, like code block line numbers and paragraph indentation, shares
+        // its source index with the text following it, so use the last mapping for the source
+        // index.
+        let ix = self
             .source_mappings
-            .binary_search_by_key(&source_index, |probe| probe.source_index)
-        {
-            Ok(ix) => &self.source_mappings[ix],
-            Err(ix) => &self.source_mappings[ix - 1],
-        };
+            .partition_point(|probe| probe.source_index <= source_index);
+        let mapping = &self.source_mappings[ix - 1];
         mapping.rendered_index + (source_index - mapping.source_index)
     }
 
     fn source_index_for_rendered_index(&self, rendered_index: usize) -> usize {
-        let mapping = match self
+        let ix = match self
             .source_mappings
             .binary_search_by_key(&rendered_index, |probe| probe.rendered_index)
         {
-            Ok(ix) => &self.source_mappings[ix],
-            Err(ix) => &self.source_mappings[ix - 1],
+            Ok(ix) => ix,
+            Err(ix) => ix - 1,
         };
-        mapping.source_index + (rendered_index - mapping.rendered_index)
+        let mapping = &self.source_mappings[ix];
+        let source_index = mapping.source_index + (rendered_index - mapping.rendered_index);
+        match self.source_mappings.get(ix + 1) {
+            Some(next_mapping) => source_index.min(next_mapping.source_index),
+            None => source_index,
+        }
     }
 
     fn source_index_for_position(&self, position: Point<Pixels>) -> Result<usize, usize> {
@@ -1037,6 +1531,16 @@ struct RenderedLink {
 
 impl RenderedText {
     fn source_index_for_position(&self, position: Point<Pixels>) -> Result<usize, usize> {
+        // Lines laid out side by side (e.g. table cells) share the same vertical range,
+        // so prefer the line that actually contains the position.
+        if let Some(line) = self
+            .lines
+            .iter()
+            .find(|line| line.layout.bounds().contains(&position))
+        {
+            return line.source_index_for_position(position);
+        }
+
         let mut lines = self.lines.iter().peekable();
 
         while let Some(line) = lines.next() {
@@ -1152,3 +1656,352 @@ impl RenderedText {
             .find(|link| link.source_range.contains(&source_index))
     }
 }
+
+/// The bullet of the list item started by the event at `ix`: a checkbox for task list
+/// items, whose marker follows the start of the item or of its first paragraph, the
+/// item's number in ordered lists, and a dot otherwise.
+fn list_item_bullet(
+    events: &[(Range<usize>, MarkdownEvent)],
+    ix: usize,
+    bullet_index: Option<u64>,
+) -> String {
+    let task_list_marker = events[ix + 1..]
+        .iter()
+        .take(2)
+        .find_map(|(_, event)| match event {
+            MarkdownEvent::TaskListMarker(checked) => Some(*checked),
+            _ => None,
+        });
+    match (task_list_marker, bullet_index) {
+        (Some(true), _) => "☑".to_string(),
+        (Some(false), _) => "☐".to_string(),
+        (None, Some(bullet_index)) => format!("{}.", bullet_index),
+        (None, None) => "•".to_string(),
+    }
+}
+
+/// The number of characters of the widest bullet of the list starting at the given event:
+/// the number of its last item for ordered lists, or a single marker otherwise.
+fn list_bullet_width(
+    events: &[(Range<usize>, MarkdownEvent)],
+    ix: usize,
+    bullet_index: Option<u64>,
+) -> usize {
+    let Some(first_index) = bullet_index else {
+        return 1;
+    };
+    let mut depth = 0;
+    let mut item_count = 0;
+    for (_, event) in &events[ix + 1..] {
+        match event {
+            MarkdownEvent::Start(MarkdownTag::List(_)) => depth += 1,
+            MarkdownEvent::End(MarkdownTagEnd::List(_)) if depth == 0 => break,
+            MarkdownEvent::End(MarkdownTagEnd::List(_)) => depth -= 1,
+            MarkdownEvent::Start(MarkdownTag::Item) if depth == 0 => item_count += 1,
+            _ => {}
+        }
+    }
+    let last_index = first_index + item_count.max(1) - 1;
+    format!("{}.", last_index).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{size, TestAppContext, VisualTestContext};
+
+    #[test]
+    fn test_code_block_line_numbers() {
+        let line_number_color = gpui::red();
+        let mut builder =
+            MarkdownElementBuilder::new(TextStyle::default(), Arc::new(SyntaxTheme::default()));
+        builder.push_code_block(None);
+        builder.number_code_block_lines(10, line_number_color);
+        builder.push_text("let a = 1;\nlet b = 2;\n", 8);
+        builder.push_text("let c = 3;\n", 30);
+
+        assert_eq!(
+            builder.pending_line.text,
+            " 1 let a = 1;\n 2 let b = 2;\n 3 let c = 3;\n"
+        );
+        assert_eq!(
+            builder
+                .pending_line
+                .runs
+                .iter()
+                .map(|run| (run.len, run.color == line_number_color))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, true),
+                (11, false),
+                (3, true),
+                (11, false),
+                (3, true),
+                (11, false)
+            ]
+        );
+
+        builder.trim_trailing_newline();
+        builder.pop_code_block();
+        builder.flush_text();
+        let line = &builder.rendered_lines[0];
+        assert_eq!(line.rendered_index_for_source_index(8), 3);
+        assert_eq!(line.rendered_index_for_source_index(19), 17);
+        assert_eq!(line.rendered_index_for_source_index(31), 32);
+        assert_eq!(line.source_index_for_rendered_index(0), 8);
+        assert_eq!(line.source_index_for_rendered_index(1), 8);
+        assert_eq!(line.source_index_for_rendered_index(4), 9);
+        assert_eq!(line.source_index_for_rendered_index(15), 19);
+        assert_eq!(line.source_index_for_rendered_index(18), 20);
+
+        // Numbering stops with the code block.
+        builder.push_text("Some text", 42);
+        assert_eq!(builder.pending_line.text, "Some text");
+    }
+
+    #[test]
+    fn test_emphasis_inside_link() {
+        let link_style = TextStyleRefinement {
+            color: Some(gpui::blue()),
+            underline: Some(gpui::UnderlineStyle {
+                thickness: px(1.),
+                color: Some(gpui::blue()),
+                wavy: false,
+            }),
+            ..Default::default()
+        };
+        let mut builder =
+            MarkdownElementBuilder::new(TextStyle::default(), Arc::new(SyntaxTheme::default()));
+
+        // The events of `[a **bold** link](https://zed.dev)`, styled the way `MarkdownElement` does.
+        builder.push_link("https://zed.dev".into(), 0..33);
+        builder.push_text_style(link_style.clone());
+        builder.push_text("a ", 1);
+        builder.push_text_style(TextStyleRefinement {
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        });
+        builder.push_text("bold", 5);
+        builder.pop_text_style();
+        builder.push_text(" link", 11);
+        builder.pop_text_style();
+        builder.push_text(" after", 33);
+
+        let runs = &builder.pending_line.runs;
+        assert_eq!(
+            runs.iter().map(|run| run.len).collect::<Vec<_>>(),
+            vec![2, 4, 5, 6]
+        );
+        for run in &runs[..3] {
+            assert_eq!(run.color, gpui::blue());
+            assert_eq!(run.underline, link_style.underline);
+        }
+        assert_eq!(runs[1].font.weight, FontWeight::BOLD);
+        assert_eq!(runs[0].font.weight, runs[2].font.weight);
+        assert_ne!(runs[0].font.weight, FontWeight::BOLD);
+        assert_eq!(runs[3].underline, None);
+        assert_eq!(runs[3].font.weight, runs[0].font.weight);
+    }
+
+    #[test]
+    fn test_plain_text_code_blocks_not_highlighted() {
+        let source = "```text\nlet a = 1;\n```\n\n```diagram\n+--+\n```\n\n```rust\nlet a = 1;\n```\n\n```rust,ignore\nlet a = 1;\n```";
+        let languages = parse_markdown(source)
+            .into_iter()
+            .filter_map(|(_, event)| match event {
+                MarkdownEvent::Start(MarkdownTag::CodeBlock(kind)) => {
+                    Some(highlighted_code_block_language(&kind).map(str::to_string))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            languages,
+            vec![
+                None,
+                None,
+                Some("rust".to_string()),
+                Some("rust".to_string())
+            ]
+        );
+
+        let keyword_color = gpui::red();
+        let syntax_theme = Arc::new(SyntaxTheme::new_test([("keyword", keyword_color)]));
+        let mut builder = MarkdownElementBuilder::new(TextStyle::default(), syntax_theme.clone());
+        builder.push_code_block(None);
+        builder.push_text("let a = 1;", 8);
+        assert_eq!(builder.pending_line.runs.len(), 1);
+        assert_ne!(builder.pending_line.runs[0].color, keyword_color);
+
+        // A block whose info string lists attributes after its language is highlighted in it.
+        let rust = Arc::new(
+            Language::new(
+                language::LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_highlights_query(r#""let" @keyword"#)
+            .unwrap(),
+        );
+        rust.set_theme(&syntax_theme);
+        let mut builder = MarkdownElementBuilder::new(TextStyle::default(), syntax_theme);
+        builder.push_code_block(Some(rust));
+        builder.push_text("let a = 1;", 81);
+        assert_eq!(builder.pending_line.runs[0].len, 3);
+        assert_eq!(builder.pending_line.runs[0].color, keyword_color);
+    }
+
+    #[test]
+    fn test_inline_code_highlighted_with_doc_language() {
+        let keyword_color = gpui::red();
+        let syntax_theme = Arc::new(SyntaxTheme::new_test([("keyword", keyword_color)]));
+        let rust = Arc::new(
+            Language::new(
+                language::LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_highlights_query(r#""let" @keyword"#)
+            .unwrap(),
+        );
+        rust.set_theme(&syntax_theme);
+        let mut builder = MarkdownElementBuilder::new(TextStyle::default(), syntax_theme);
+
+        // Inline code is only highlighted after a code block set the doc's language.
+        builder.push_inline_code("let", 0, true);
+        builder.push_code_block(Some(rust));
+        builder.push_text("let a = 1;", 10);
+        builder.pop_code_block();
+        builder.push_inline_code("let", 30, false);
+        builder.push_inline_code("let", 40, true);
+
+        let keyword_runs = builder
+            .pending_line
+            .runs
+            .iter()
+            .map(|run| (run.len, run.color == keyword_color))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keyword_runs,
+            vec![(3, false), (3, true), (7, false), (3, false), (3, true)]
+        );
+    }
+
+    #[test]
+    fn test_inline_code_highlighted_with_inline_code_language() {
+        let keyword_color = gpui::red();
+        let syntax_theme = Arc::new(SyntaxTheme::new_test([("keyword", keyword_color)]));
+        let rust = Arc::new(
+            Language::new(
+                language::LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_highlights_query(r#""let" @keyword"#)
+            .unwrap(),
+        );
+        rust.set_theme(&syntax_theme);
+
+        // Without a language, inline code keeps its flat style.
+        let mut builder = MarkdownElementBuilder::new(TextStyle::default(), syntax_theme.clone());
+        builder.push_inline_code("let", 0, false);
+        builder.push_inline_code("let", 10, true);
+        assert!(builder
+            .pending_line
+            .runs
+            .iter()
+            .all(|run| run.color != keyword_color));
+
+        let mut builder = MarkdownElementBuilder::new(TextStyle::default(), syntax_theme);
+        builder.inline_code_language = Some(rust);
+        builder.push_inline_code("let x", 0, false);
+        builder.push_inline_code("let", 10, true);
+        let keyword_runs = builder
+            .pending_line
+            .runs
+            .iter()
+            .map(|run| (run.len, run.color == keyword_color))
+            .collect::<Vec<_>>();
+        assert_eq!(keyword_runs, vec![(3, true), (2, false), (3, true)]);
+    }
+
+    #[test]
+    fn test_task_list_bullets() {
+        let source = "- [x] done\n- [ ] todo\n  1. [ ] nested\n  2. numbered\n- plain";
+        let events = parse_markdown(source);
+        let mut builder =
+            MarkdownElementBuilder::new(TextStyle::default(), Arc::new(SyntaxTheme::default()));
+        let mut bullets = Vec::new();
+        for (ix, (_, event)) in events.iter().enumerate() {
+            match event {
+                MarkdownEvent::Start(MarkdownTag::List(bullet_index)) => {
+                    builder.push_list(*bullet_index, list_bullet_width(&events, ix, *bullet_index))
+                }
+                MarkdownEvent::End(MarkdownTagEnd::List(_)) => builder.pop_list(),
+                MarkdownEvent::Start(MarkdownTag::Item) => {
+                    let bullet = list_item_bullet(&events, ix, builder.next_bullet_index());
+                    bullets.push((builder.list_stack.len(), bullet));
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(
+            bullets,
+            vec![
+                (1, "☑".to_string()),
+                (1, "☐".to_string()),
+                (2, "☐".to_string()),
+                (2, "2.".to_string()),
+                (1, "•".to_string()),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_render_incrementally(cx: &mut TestAppContext) {
+        let source = (0..20)
+            .map(|ix| format!("Paragraph {ix}."))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let cx = cx.add_empty_window();
+        let markdown = cx.new_view(|cx| {
+            Markdown::new(source, MarkdownStyle::default(), None, cx, None).render_incrementally(8)
+        });
+        cx.run_until_parked();
+        let draw = |cx: &mut VisualTestContext| {
+            cx.draw(point(px(0.), px(0.)), size(px(500.), px(2000.)), |_| {
+                markdown.clone()
+            });
+            cx.update(|cx| {
+                markdown
+                    .read(cx)
+                    .rendered_lines()
+                    .map(|(text, _)| text.to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // The first frame only lays out the paragraphs within the first 8 lines.
+        assert_eq!(
+            draw(cx),
+            [
+                "Paragraph 0.",
+                "Paragraph 1.",
+                "Paragraph 2.",
+                "Paragraph 3."
+            ]
+        );
+
+        // The next frame lays out all of them.
+        cx.run_until_parked();
+        let lines = draw(cx);
+        assert_eq!(lines.len(), 20);
+        assert_eq!(lines.last().unwrap(), "Paragraph 19.");
+    }
+}
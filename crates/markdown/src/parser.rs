@@ -67,9 +67,19 @@ pub fn parse_markdown(text: &str) -> Vec<(Range<usize>, MarkdownEvent)> {
                     events.push((range, MarkdownEvent::Text));
                 }
             }
-            pulldown_cmark::Event::Code(_) => {
-                range.start += 1;
-                range.end -= 1;
+            pulldown_cmark::Event::Code(code) => {
+                // Code spans containing backticks are delimited by several of them, and a
+                // space padding the code is stripped.
+                let delimiter_len = text[range.clone()]
+                    .bytes()
+                    .take_while(|byte| *byte == b'`')
+                    .count();
+                range.start += delimiter_len;
+                range.end -= delimiter_len;
+                if let Some(code_start) = text[range.clone()].find(code.as_ref()) {
+                    range.start += code_start;
+                    range.end = range.start + code.len();
+                }
                 events.push((range, MarkdownEvent::Code))
             }
             pulldown_cmark::Event::Html(_) => events.push((range, MarkdownEvent::Html)),
@@ -85,9 +95,96 @@ pub fn parse_markdown(text: &str) -> Vec<(Range<usize>, MarkdownEvent)> {
             }
         }
     }
+    detect_alerts(text, &mut events);
     events
 }
 
+/// Turns block quotes starting with a GitHub-flavored alert marker, such as `[!NOTE]`,
+/// into [`MarkdownTag::Alert`]s, removing the marker from their text.
+fn detect_alerts(text: &str, events: &mut Vec<(Range<usize>, MarkdownEvent)>) {
+    let mut ix = 0;
+    while ix + 1 < events.len() {
+        let (
+            MarkdownEvent::Start(MarkdownTag::BlockQuote),
+            MarkdownEvent::Start(MarkdownTag::Paragraph),
+        ) = (&events[ix].1, &events[ix + 1].1)
+        else {
+            ix += 1;
+            continue;
+        };
+        let paragraph = events[ix + 1].0.clone();
+        let Some((kind, marker_len)) = AlertKind::from_marker(&text[paragraph.clone()]) else {
+            ix += 1;
+            continue;
+        };
+        events[ix].1 = MarkdownEvent::Start(MarkdownTag::Alert(kind));
+
+        // Drop the marker's text and the line break following it.
+        let marker_end = paragraph.start + marker_len;
+        let mut content_start = ix + 2;
+        while content_start < events.len() && events[content_start].0.start < marker_end {
+            content_start += 1;
+        }
+        if matches!(
+            events.get(content_start),
+            Some((_, MarkdownEvent::SoftBreak | MarkdownEvent::HardBreak))
+        ) {
+            content_start += 1;
+        }
+        if matches!(
+            events.get(content_start),
+            Some((_, MarkdownEvent::End(MarkdownTagEnd::Paragraph)))
+        ) {
+            // The marker was alone in its paragraph.
+            events.drain(ix + 1..=content_start);
+        } else {
+            events.drain(ix + 2..content_start);
+        }
+        ix += 1;
+    }
+}
+
+/// The kind of a GitHub-flavored alert, a block quote starting with a `[!KIND]` marker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AlertKind {
+    /// Parses the alert marker on the first line of a paragraph, returning its kind and length.
+    fn from_marker(paragraph: &str) -> Option<(Self, usize)> {
+        let first_line = paragraph.lines().next()?;
+        let name = first_line
+            .trim_end()
+            .strip_prefix("[!")?
+            .strip_suffix(']')?;
+        let kind = match name.to_ascii_uppercase().as_str() {
+            "NOTE" => Self::Note,
+            "TIP" => Self::Tip,
+            "IMPORTANT" => Self::Important,
+            "WARNING" => Self::Warning,
+            "CAUTION" => Self::Caution,
+            _ => return None,
+        };
+        Some((kind, name.len() + "[!]".len()))
+    }
+
+    /// The title shown at the top of the alert.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
 /// A static-lifetime equivalent of pulldown_cmark::Event so we can cache the
 /// parse result for rendering without resorting to unsafe lifetime coercion.
 #[derive(Clone, Debug, PartialEq)]
@@ -140,6 +237,10 @@ pub enum MarkdownTag {
 
     BlockQuote,
 
+    /// A block quote starting with an alert marker such as `[!NOTE]`, which isn't part of its
+    /// content. Like block quotes, ends with [`MarkdownTagEnd::BlockQuote`].
+    Alert(AlertKind),
+
     /// A code block.
     CodeBlock(CodeBlockKind),
 
@@ -203,6 +304,20 @@ pub enum CodeBlockKind {
     Fenced(SharedString),
 }
 
+impl CodeBlockKind {
+    /// The language of a fenced code block, without the attributes its info string may list
+    /// after the language, as in `rust,ignore`.
+    pub fn language(&self) -> Option<&str> {
+        match self {
+            CodeBlockKind::Indented => None,
+            CodeBlockKind::Fenced(info) => info
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .next()
+                .filter(|language| !language.is_empty()),
+        }
+    }
+}
+
 impl From<pulldown_cmark::Tag<'_>> for MarkdownTag {
     fn from(tag: pulldown_cmark::Tag) -> Self {
         match tag {
@@ -282,3 +397,154 @@ impl From<pulldown_cmark::Tag<'_>> for MarkdownTag {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text rendered for a markdown source, and the tags it started.
+    fn rendered(source: &str) -> (String, Vec<MarkdownTag>) {
+        let mut text = String::new();
+        let mut tags = Vec::new();
+        for (range, event) in parse_markdown(source) {
+            match event {
+                MarkdownEvent::Text | MarkdownEvent::Code => text.push_str(&source[range]),
+                MarkdownEvent::Start(tag) => tags.push(tag),
+                _ => {}
+            }
+        }
+        (text, tags)
+    }
+
+    #[test]
+    fn test_escaped_characters() {
+        assert_eq!(
+            rendered(r"\[not a link\](https://zed.dev)"),
+            (
+                "[not a link](https://zed.dev)".to_string(),
+                vec![MarkdownTag::Paragraph]
+            )
+        );
+        assert_eq!(
+            rendered(r"\*not emphasis\*"),
+            ("*not emphasis*".to_string(), vec![MarkdownTag::Paragraph])
+        );
+        assert_eq!(
+            rendered(r"\`not code\`"),
+            ("`not code`".to_string(), vec![MarkdownTag::Paragraph])
+        );
+        assert_eq!(
+            rendered(r"a \| b"),
+            ("a | b".to_string(), vec![MarkdownTag::Paragraph])
+        );
+
+        let (text, _) = rendered("| a |\n| - |\n| b \\| c |");
+        assert_eq!(text, "ab | c");
+    }
+
+    #[test]
+    fn test_alerts() {
+        assert_eq!(
+            rendered("> [!NOTE]\n> Useful information."),
+            (
+                "Useful information.".to_string(),
+                vec![MarkdownTag::Alert(AlertKind::Note), MarkdownTag::Paragraph]
+            )
+        );
+        assert_eq!(
+            rendered("> [!WARNING]\n>\n> Critical content.\n>\n> More."),
+            (
+                "Critical content.More.".to_string(),
+                vec![
+                    MarkdownTag::Alert(AlertKind::Warning),
+                    MarkdownTag::Paragraph,
+                    MarkdownTag::Paragraph
+                ]
+            )
+        );
+        // Unknown kinds and markers that don't stand alone on their line stay block quotes.
+        assert_eq!(
+            rendered("> [!UNKNOWN]\n> Text"),
+            (
+                "[!UNKNOWN]Text".to_string(),
+                vec![MarkdownTag::BlockQuote, MarkdownTag::Paragraph]
+            )
+        );
+        assert_eq!(
+            rendered("> [!NOTE] inline"),
+            (
+                "[!NOTE] inline".to_string(),
+                vec![MarkdownTag::BlockQuote, MarkdownTag::Paragraph]
+            )
+        );
+    }
+
+    #[test]
+    fn test_nested_block_quotes() {
+        // Each level is its own block quote, which renders indented inside its parent.
+        assert_eq!(
+            rendered("> Quoted.\n>\n> > Nested."),
+            (
+                "Quoted.Nested.".to_string(),
+                vec![
+                    MarkdownTag::BlockQuote,
+                    MarkdownTag::Paragraph,
+                    MarkdownTag::BlockQuote,
+                    MarkdownTag::Paragraph
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_strikethrough() {
+        let source = "keep ~~drop~~ keep";
+        let mut struck = false;
+        let mut struck_ranges = Vec::new();
+        for (range, event) in parse_markdown(source) {
+            match event {
+                MarkdownEvent::Start(MarkdownTag::Strikethrough) => struck = true,
+                MarkdownEvent::End(MarkdownTagEnd::Strikethrough) => struck = false,
+                MarkdownEvent::Text if struck => struck_ranges.push(range),
+                _ => {}
+            }
+        }
+        assert_eq!(struck_ranges, vec![7..11]);
+        assert_eq!(&source[7..11], "drop");
+    }
+
+    #[test]
+    fn test_rule_between_paragraphs() {
+        // The rule is its own block, rendered as a full-width line between the paragraphs.
+        let source = "Above.\n\n---\n\nBelow.";
+        let events = parse_markdown(source)
+            .into_iter()
+            .filter(|(_, event)| !matches!(event, MarkdownEvent::Text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events
+                .iter()
+                .map(|(_, event)| event.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                MarkdownEvent::Start(MarkdownTag::Paragraph),
+                MarkdownEvent::End(MarkdownTagEnd::Paragraph),
+                MarkdownEvent::Rule,
+                MarkdownEvent::Start(MarkdownTag::Paragraph),
+                MarkdownEvent::End(MarkdownTagEnd::Paragraph),
+            ]
+        );
+        assert_eq!(source[events[2].0.clone()].trim(), "---");
+    }
+
+    #[test]
+    fn test_code_containing_backticks() {
+        let source = "`` a`b `` and ```c``d```";
+        let code = parse_markdown(source)
+            .into_iter()
+            .filter(|(_, event)| *event == MarkdownEvent::Code)
+            .map(|(range, _)| &source[range])
+            .collect::<Vec<_>>();
+        assert_eq!(code, ["a`b", "c``d"]);
+    }
+}
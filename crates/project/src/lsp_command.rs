@@ -177,6 +177,15 @@ pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
 
+pub(crate) struct GetSymbolKind {
+    pub position: PointUtf16,
+}
+
+#[derive(Clone)]
+pub(crate) struct GetRawHover {
+    pub position: PointUtf16,
+}
+
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
     type Response = Option<Range<Anchor>>;
@@ -1588,9 +1597,6 @@ impl LspCommand for GetHover {
                 },
             })
             .collect();
-        if contents.is_empty() {
-            return Ok(None);
-        }
 
         let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
         let range = if let (Some(start), Some(end)) = (message.start, message.end) {
@@ -1599,6 +1605,9 @@ impl LspCommand for GetHover {
         } else {
             None
         };
+        if contents.is_empty() && range.is_none() {
+            return Ok(None);
+        }
         if let Some(range) = range.as_ref() {
             buffer
                 .update(&mut cx, |buffer, _| {
@@ -2889,3 +2898,216 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+#[async_trait(?Send)]
+impl LspCommand for GetSymbolKind {
+    type Response = Option<lsp::SymbolKind>;
+    type LspRequest = lsp::request::DocumentSymbolRequest;
+    type ProtoRequest = proto::GetSymbolKind;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        matches!(
+            capabilities.document_symbol_provider,
+            Some(OneOf::Left(true) | OneOf::Right(_))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentSymbolParams {
+        lsp::DocumentSymbolParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::DocumentSymbolResponse>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Option<lsp::SymbolKind>> {
+        Ok(message.and_then(|message| symbol_kind_at(message, point_to_lsp(self.position))))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSymbolKind {
+        proto::GetSymbolKind {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSymbolKind,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Option<lsp::SymbolKind>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSymbolKindResponse {
+        proto::GetSymbolKindResponse {
+            kind: response.map(|kind| unsafe { std::mem::transmute::<lsp::SymbolKind, i32>(kind) }),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSymbolKindResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Option<lsp::SymbolKind>> {
+        Ok(message
+            .kind
+            .map(|kind| unsafe { std::mem::transmute::<i32, lsp::SymbolKind>(kind) }))
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSymbolKind) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetRawHover {
+    type Response = String;
+    type LspRequest = lsp::request::HoverRequest;
+    type ProtoRequest = proto::GetRawHover;
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        language_server: &Arc<LanguageServer>,
+        cx: &AppContext,
+    ) -> lsp::HoverParams {
+        GetHover {
+            position: self.position,
+        }
+        .to_lsp(path, buffer, language_server, cx)
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::Hover>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&message)?)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetRawHover {
+        proto::GetRawHover {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetRawHover,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: String,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetRawHoverResponse {
+        proto::GetRawHoverResponse { json: response }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetRawHoverResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<String> {
+        Ok(message.json)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetRawHover) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+/// The kind of the innermost document symbol whose name is at the given position. Flat
+/// symbols only have the range of their whole definition, so they can't be matched.
+pub(crate) fn symbol_kind_at(
+    response: lsp::DocumentSymbolResponse,
+    position: lsp::Position,
+) -> Option<lsp::SymbolKind> {
+    fn nested_kind_at(
+        symbols: &[lsp::DocumentSymbol],
+        position: lsp::Position,
+    ) -> Option<lsp::SymbolKind> {
+        symbols.iter().find_map(|symbol| {
+            if !(symbol.range.start <= position && position <= symbol.range.end) {
+                return None;
+            }
+            symbol
+                .children
+                .as_deref()
+                .and_then(|children| nested_kind_at(children, position))
+                .or_else(|| {
+                    let name = symbol.selection_range;
+                    (name.start <= position && position <= name.end).then_some(symbol.kind)
+                })
+        })
+    }
+
+    match response {
+        lsp::DocumentSymbolResponse::Nested(symbols) => nested_kind_at(&symbols, position),
+        lsp::DocumentSymbolResponse::Flat(_) => None,
+    }
+}
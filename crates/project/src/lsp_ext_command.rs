@@ -1,15 +1,22 @@
-use std::{path::Path, sync::Arc};
+use std::{ops::Range, path::Path, sync::Arc};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use gpui::{AppContext, AsyncAppContext, Model};
-use language::{point_to_lsp, proto::deserialize_anchor, Buffer};
+use language::{
+    point_to_lsp,
+    proto::{deserialize_anchor, deserialize_version, serialize_version},
+    range_to_lsp, Buffer,
+};
 use lsp::{LanguageServer, LanguageServerId};
 use rpc::proto::{self, PeerId};
 use serde::{Deserialize, Serialize};
 use text::{BufferId, PointUtf16, ToPointUtf16};
 
-use crate::{lsp_command::LspCommand, Project};
+use crate::{
+    lsp_command::{GetHover, LspCommand},
+    Hover, Project,
+};
 
 pub enum LspExpandMacro {}
 
@@ -135,3 +142,138 @@ impl LspCommand for ExpandMacro {
         BufferId::new(message.buffer_id)
     }
 }
+
+pub enum LspHoverRange {}
+
+impl lsp::request::Request for LspHoverRange {
+    type Params = HoverRangeParams;
+    type Result = Option<lsp::Hover>;
+    const METHOD: &'static str = "textDocument/hover";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HoverRangeParams {
+    pub text_document: lsp::TextDocumentIdentifier,
+    /// rust-analyzer accepts a range in place of the hovered position.
+    pub position: lsp::Range,
+}
+
+/// Requests the hover of a range, such as the type of an expression, from servers
+/// advertising rust-analyzer's `hoverRange` extension.
+pub struct HoverRange {
+    pub range: Range<PointUtf16>,
+}
+
+#[async_trait(?Send)]
+impl LspCommand for HoverRange {
+    type Response = Option<Hover>;
+    type LspRequest = LspHoverRange;
+    type ProtoRequest = proto::LspExtHoverRange;
+
+    fn check_capabilities(&self, capabilities: &lsp::ServerCapabilities) -> bool {
+        capabilities
+            .experimental
+            .as_ref()
+            .and_then(|experimental| experimental.get("hoverRange")?.as_bool())
+            .unwrap_or(false)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> HoverRangeParams {
+        HoverRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            position: range_to_lsp(self.range.clone()),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::Hover>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> anyhow::Result<Option<Hover>> {
+        GetHover {
+            position: self.range.start,
+        }
+        .response_from_lsp(message, project, buffer, server_id, cx)
+        .await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::LspExtHoverRange {
+        proto::LspExtHoverRange {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            start: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.range.start),
+            )),
+            end: Some(language::proto::serialize_anchor(
+                &buffer.anchor_after(self.range.end),
+            )),
+            version: serialize_version(&buffer.version),
+        }
+    }
+
+    async fn from_proto(
+        message: Self::ProtoRequest,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> anyhow::Result<Self> {
+        let start = message
+            .start
+            .and_then(deserialize_anchor)
+            .context("invalid start")?;
+        let end = message
+            .end
+            .and_then(deserialize_anchor)
+            .context("invalid end")?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            range: buffer.update(&mut cx, |buffer, _| {
+                start.to_point_utf16(buffer)..end.to_point_utf16(buffer)
+            })?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Option<Hover>,
+        project: &mut Project,
+        peer_id: PeerId,
+        buffer_version: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetHoverResponse {
+        GetHover::response_to_proto(response, project, peer_id, buffer_version, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetHoverResponse,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> anyhow::Result<Option<Hover>> {
+        GetHover {
+            position: self.range.start,
+        }
+        .response_from_proto(message, project, buffer, cx)
+        .await
+    }
+
+    fn buffer_id_from_proto(message: &proto::LspExtHoverRange) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
@@ -38,7 +38,8 @@ use git::{blame::Blame, repository::GitRepository};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, BorrowAppContext, Context, Entity,
-    EventEmitter, Model, ModelContext, PromptLevel, SharedString, Task, WeakModel, WindowContext,
+    EventEmitter, Global, Model, ModelContext, PromptLevel, SharedString, Task, WeakModel,
+    WindowContext,
 };
 use http::{HttpClient, Url};
 use itertools::Itertools;
@@ -46,16 +47,16 @@ use language::{
     language_settings::{
         language_settings, AllLanguageSettings, FormatOnSave, Formatter, InlayHintKind,
     },
-    markdown, point_to_lsp, prepare_completion_documentation,
+    markdown, point_from_lsp, point_to_lsp, prepare_completion_documentation,
     proto::{
         deserialize_anchor, deserialize_line_ending, deserialize_version, serialize_anchor,
         serialize_line_ending, serialize_version, split_operations,
     },
     range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability, CodeLabel,
-    ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation,
-    Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName, LocalFile,
-    LspAdapterDelegate, Operation, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot,
-    ToOffset, ToPointUtf16, Transaction, Unclipped,
+    ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticRelatedLocation, DiagnosticSet, Diff,
+    Documentation, Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName,
+    LocalFile, LspAdapterDelegate, Operation, Patch, PendingLanguageServer, PointUtf16,
+    TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use log::error;
 use lsp::{
@@ -124,7 +125,7 @@ pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
 pub use task_inventory::{
-    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
+    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskRunResult, TaskSourceKind,
 };
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, RepositoryEntry,
@@ -591,6 +592,13 @@ impl Hover {
     }
 }
 
+/// The formats language servers are asked for hover contents in, the most preferred first.
+/// Servers are asked when they start, so a change only applies to servers started after it.
+#[derive(Clone, Debug)]
+pub struct HoverContentFormat(pub Vec<lsp::MarkupKind>);
+
+impl Global for HoverContentFormat {}
+
 #[derive(Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
@@ -691,6 +699,8 @@ impl Project {
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetHover>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSymbolKind>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetRawHover>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
@@ -706,6 +716,7 @@ impl Project {
         client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_message_handler(Self::handle_update_diff_base);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
+        client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::HoverRange>);
         client.add_model_request_handler(Self::handle_blame_buffer);
         client.add_model_request_handler(Self::handle_multi_lsp_query);
         client.add_model_request_handler(Self::handle_restart_language_servers);
@@ -3557,7 +3568,7 @@ impl Project {
             .clone()
             .workspace_configuration(&delegate, cx)
             .await?;
-        let (language_server, mut initialization_options) = pending_server.task.await?;
+        let (mut language_server, mut initialization_options) = pending_server.task.await?;
 
         let name = language_server.name();
         language_server
@@ -3848,6 +3859,11 @@ impl Project {
             (None, override_options) => initialization_options = override_options,
             _ => {}
         }
+        if let Some(HoverContentFormat(content_format)) =
+            cx.update(|cx| cx.try_global::<HoverContentFormat>().cloned())?
+        {
+            language_server.set_hover_content_format(content_format);
+        }
         let language_server = cx
             .update(|cx| language_server.initialize(initialization_options, cx))?
             .await?;
@@ -4714,6 +4730,10 @@ impl Project {
                 lsp::NumberOrString::Number(code) => code.to_string(),
                 lsp::NumberOrString::String(code) => code.clone(),
             });
+            let code_description = diagnostic
+                .code_description
+                .as_ref()
+                .map(|description| description.href.to_string());
             let range = range_from_lsp(diagnostic.range);
             let is_supporting = diagnostic
                 .related_information
@@ -4746,17 +4766,33 @@ impl Project {
                 primary_diagnostic_group_ids
                     .insert((source, code.clone(), range.clone()), group_id);
 
+                let related_locations = diagnostic
+                    .related_information
+                    .iter()
+                    .flatten()
+                    .filter(|info| info.location.uri != params.uri)
+                    .filter_map(|info| {
+                        Some(DiagnosticRelatedLocation {
+                            abs_path: info.location.uri.to_file_path().ok()?,
+                            position: point_from_lsp(info.location.range.start),
+                            message: info.message.trim().to_string(),
+                        })
+                    })
+                    .collect();
+
                 diagnostics.push(DiagnosticEntry {
                     range,
                     diagnostic: Diagnostic {
                         source: diagnostic.source.clone(),
                         code: code.clone(),
+                        code_description: code_description.clone(),
                         severity: diagnostic.severity.unwrap_or(DiagnosticSeverity::ERROR),
                         message: diagnostic.message.trim().to_string(),
                         group_id,
                         is_primary: true,
                         is_disk_based,
                         is_unnecessary,
+                        related_locations,
                     },
                 });
                 if let Some(infos) = &diagnostic.related_information {
@@ -4768,12 +4804,14 @@ impl Project {
                                 diagnostic: Diagnostic {
                                     source: diagnostic.source.clone(),
                                     code: code.clone(),
+                                    code_description: code_description.clone(),
                                     severity: DiagnosticSeverity::INFORMATION,
                                     message: info.message.trim().to_string(),
                                     group_id,
                                     is_primary: false,
                                     is_disk_based,
                                     is_unnecessary: false,
+                                    related_locations: Vec::new(),
                                 },
                             });
                         }
@@ -5886,11 +5924,7 @@ impl Project {
             let all_actions_task = self.request_multiple_lsp_locally(
                 &buffer,
                 Some(position),
-                |server_capabilities| match server_capabilities.hover_provider {
-                    Some(lsp::HoverProviderCapability::Simple(enabled)) => enabled,
-                    Some(lsp::HoverProviderCapability::Options(_)) => true,
-                    None => false,
-                },
+                provides_hover,
                 GetHover { position },
                 cx,
             );
@@ -5971,6 +6005,123 @@ impl Project {
         self.hover_impl(buffer, position, cx)
     }
 
+    /// Requests hovers from those of the buffer's language servers that also serve the given
+    /// language, such as a language injected into the buffer.
+    pub fn hover_for_language<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        language: &Arc<Language>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<Hover>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        if !self.is_local() {
+            // Guests can't tell which of the host's language servers serve a language.
+            return self.hover_impl(buffer, position, cx);
+        }
+        let adapters = self.languages.lsp_adapters(language);
+        let requests = self
+            .language_servers_for_buffer(buffer.read(cx), cx)
+            .filter(|(adapter, server)| {
+                provides_hover(server.capabilities())
+                    && adapters
+                        .iter()
+                        .any(|language_adapter| language_adapter.name == adapter.name)
+            })
+            .map(|(_, server)| server.server_id())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|server_id| {
+                self.request_lsp(
+                    buffer.clone(),
+                    LanguageServerToQuery::Other(server_id),
+                    GetHover { position },
+                    cx,
+                )
+            })
+            .collect::<Vec<_>>();
+        cx.spawn(|_, _| async move {
+            join_all(requests)
+                .await
+                .into_iter()
+                .filter_map(|hover| remove_empty_hover_blocks(hover.log_err()??))
+                .collect()
+        })
+    }
+
+    /// Requests the hover of a range of the buffer, such as the type of an expression, from
+    /// its primary language server, if that server supports rust-analyzer's `hoverRange`.
+    pub fn hover_range<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<T>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<Hover>> {
+        let range =
+            range.start.to_point_utf16(buffer.read(cx))..range.end.to_point_utf16(buffer.read(cx));
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            lsp_ext_command::HoverRange { range },
+            cx,
+        );
+        cx.spawn(|_, _| async move {
+            request
+                .await
+                .log_err()
+                .flatten()
+                .and_then(remove_empty_hover_blocks)
+                .into_iter()
+                .collect()
+        })
+    }
+
+    /// The kind of the document symbol whose name is at the given position, according to
+    /// the buffer's primary language server.
+    pub fn symbol_kind<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Option<lsp::SymbolKind>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSymbolKind { position },
+            cx,
+        );
+        cx.spawn(|_, _| async move { request.await.log_err().flatten() })
+    }
+
+    /// The unprocessed hover responses of the buffer's language servers, as pretty-printed
+    /// JSON. Guests only get the response of the host's primary language server.
+    pub fn raw_hover<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<String>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        if self.is_local() {
+            self.request_multiple_lsp_locally(
+                buffer,
+                Some(position),
+                provides_hover,
+                GetRawHover { position },
+                cx,
+            )
+        } else {
+            let request = self.request_lsp(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                GetRawHover { position },
+                cx,
+            );
+            cx.spawn(|_, _| async move { request.await.log_err().into_iter().collect() })
+        }
+    }
+
     fn linked_edit_impl(
         &self,
         buffer: &Model<Buffer>,
@@ -12084,11 +12235,20 @@ fn deserialize_blame_buffer_response(response: proto::BlameBufferResponse) -> gi
     }
 }
 
+fn provides_hover(capabilities: &ServerCapabilities) -> bool {
+    match capabilities.hover_provider {
+        Some(lsp::HoverProviderCapability::Simple(enabled)) => enabled,
+        Some(lsp::HoverProviderCapability::Options(_)) => true,
+        None => false,
+    }
+}
+
 fn remove_empty_hover_blocks(mut hover: Hover) -> Option<Hover> {
     hover
         .contents
         .retain(|hover_block| !hover_block.text.trim().is_empty());
-    if hover.contents.is_empty() {
+    // Keep hovers without contents that still carry a range, as the symbol can be highlighted.
+    if hover.contents.is_empty() && hover.range.is_none() {
         None
     } else {
         Some(hover)
@@ -3776,6 +3776,95 @@ async fn test_grouped_diagnostics(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_diagnostic_related_locations_in_other_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-dir",
+        json!({
+            "a.rs": "fn a() { b(); }",
+            "b.rs": "fn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/the-dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let buffer_uri = Url::from_file_path("/the-dir/a.rs").unwrap();
+    let message = lsp::PublishDiagnosticsParams {
+        uri: buffer_uri.clone(),
+        diagnostics: vec![lsp::Diagnostic {
+            range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: "error 1".to_string(),
+            related_information: Some(vec![
+                lsp::DiagnosticRelatedInformation {
+                    location: lsp::Location {
+                        uri: buffer_uri,
+                        range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                    },
+                    message: "error 1 hint 1".to_string(),
+                },
+                lsp::DiagnosticRelatedInformation {
+                    location: lsp::Location {
+                        uri: Url::from_file_path("/the-dir/b.rs").unwrap(),
+                        range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                    },
+                    message: " defined here ".to_string(),
+                },
+            ]),
+            ..Default::default()
+        }],
+        version: None,
+    };
+
+    project
+        .update(cx, |p, cx| {
+            p.update_diagnostics(LanguageServerId(0), message, &[], cx)
+        })
+        .unwrap();
+    let buffer = buffer.update(cx, |buffer, _| buffer.snapshot());
+
+    // Only the location in the other file is kept on the primary diagnostic; the one in
+    // the same file is a diagnostic of the group.
+    assert_eq!(
+        buffer.diagnostic_group::<Point>(0).collect::<Vec<_>>(),
+        &[
+            DiagnosticEntry {
+                range: Point::new(0, 3)..Point::new(0, 4),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::INFORMATION,
+                    message: "error 1 hint 1".to_string(),
+                    group_id: 0,
+                    is_primary: false,
+                    ..Default::default()
+                }
+            },
+            DiagnosticEntry {
+                range: Point::new(0, 9)..Point::new(0, 10),
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::ERROR,
+                    message: "error 1".to_string(),
+                    group_id: 0,
+                    is_primary: true,
+                    related_locations: vec![DiagnosticRelatedLocation {
+                        abs_path: PathBuf::from("/the-dir/b.rs"),
+                        position: Unclipped(PointUtf16::new(0, 3)),
+                        message: "defined here".to_string(),
+                    }],
+                    ..Default::default()
+                }
+            },
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_rename(cx: &mut gpui::TestAppContext) {
     // hi
@@ -4524,6 +4613,45 @@ fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("foo/bar/baz.js"), "foo/bar/baz.js");
 }
 
+#[test]
+fn test_symbol_kind_at() {
+    use crate::lsp_command::symbol_kind_at;
+
+    let definition_range = lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(2, 1));
+    let name_range = lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 7));
+    #[allow(deprecated)]
+    let symbol = lsp::DocumentSymbol {
+        name: "test".to_string(),
+        detail: None,
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        deprecated: None,
+        range: definition_range,
+        selection_range: name_range,
+        children: None,
+    };
+    let nested = || lsp::DocumentSymbolResponse::Nested(vec![symbol.clone()]);
+    assert_eq!(
+        symbol_kind_at(nested(), lsp::Position::new(0, 4)),
+        Some(lsp::SymbolKind::FUNCTION)
+    );
+    // Symbols are only matched by the range of their name, which flat ones don't have.
+    assert_eq!(symbol_kind_at(nested(), lsp::Position::new(0, 10)), None);
+    #[allow(deprecated)]
+    let flat = lsp::DocumentSymbolResponse::Flat(vec![lsp::SymbolInformation {
+        name: "test".to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        deprecated: None,
+        location: lsp::Location::new(
+            lsp::Url::from_file_path("/dir/test.rs").unwrap(),
+            definition_range,
+        ),
+        container_name: None,
+    }]);
+    assert_eq!(symbol_kind_at(flat, lsp::Position::new(0, 4)), None);
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
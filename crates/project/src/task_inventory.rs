@@ -5,10 +5,11 @@ use std::{
     cmp::{self, Reverse},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Result;
-use collections::{btree_map, BTreeMap, VecDeque};
+use collections::{btree_map, BTreeMap, HashMap, VecDeque};
 use futures::{
     channel::mpsc::{unbounded, UnboundedSender},
     StreamExt,
@@ -30,10 +31,18 @@ use crate::Project;
 pub struct Inventory {
     sources: Vec<SourceInInventory>,
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
+    last_task_results: HashMap<TaskId, TaskRunResult>,
     update_sender: UnboundedSender<()>,
     _update_pooler: Task<anyhow::Result<()>>,
 }
 
+/// The outcome of the most recent run of a task in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskRunResult {
+    pub success: bool,
+    pub duration: Duration,
+}
+
 struct SourceInInventory {
     source: StaticSource,
     kind: TaskSourceKind,
@@ -107,6 +116,7 @@ impl Inventory {
             Self {
                 sources: Vec::new(),
                 last_scheduled_tasks: VecDeque::new(),
+                last_task_results: HashMap::default(),
                 update_sender,
                 _update_pooler,
             }
@@ -382,6 +392,16 @@ impl Inventory {
         }
     }
 
+    /// Records how the last run of the task with the given id ended.
+    pub fn task_finished(&mut self, id: TaskId, result: TaskRunResult) {
+        self.last_task_results.insert(id, result);
+    }
+
+    /// Returns the outcome of the last finished run of the task with the given id, if any.
+    pub fn last_task_result(&self, id: &TaskId) -> Option<TaskRunResult> {
+        self.last_task_results.get(id).copied()
+    }
+
     /// Deletes a resolved task from history, using its id.
     /// A similar may still resurface in `used_and_current_resolved_tasks` when its [`TaskTemplate`] is resolved again.
     pub fn delete_previously_used(&mut self, id: &TaskId) {
@@ -1,4 +1,4 @@
-use crate::Project;
+use crate::{Project, TaskRunResult};
 use anyhow::Context as _;
 use collections::HashMap;
 use gpui::{
@@ -12,6 +12,7 @@ use std::{
     fs::File,
     io::Write,
     path::{Path, PathBuf},
+    time::Instant,
 };
 use task::{SpawnInTerminal, TerminalWorkDir};
 use terminal::{
@@ -131,6 +132,7 @@ impl Project {
                         command_label: spawn_task.command_label,
                         status: TaskStatus::Running,
                         completion_rx,
+                        started_at: Instant::now(),
                     }),
                     ssh_shell,
                 )
@@ -155,6 +157,7 @@ impl Project {
                             command_label: spawn_task.command_label,
                             status: TaskStatus::Running,
                             completion_rx,
+                            started_at: Instant::now(),
                         }),
                         Shell::WithArguments {
                             program: spawn_task.command,
@@ -189,6 +192,25 @@ impl Project {
                 .local_handles
                 .push(terminal_handle.downgrade());
 
+            cx.subscribe(&terminal_handle, |project, _, event, cx| {
+                if let terminal::Event::TaskFinished {
+                    id,
+                    success,
+                    duration,
+                } = event
+                {
+                    let result = TaskRunResult {
+                        success: *success,
+                        duration: *duration,
+                    };
+                    project.task_inventory().update(cx, |inventory, cx| {
+                        inventory.task_finished(id.clone(), result);
+                        cx.notify();
+                    });
+                }
+            })
+            .detach();
+
             let id = terminal_handle.entity_id();
             cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
                 drop(retained_script);
@@ -202,12 +202,16 @@ messages!(
     (GetPrivateUserInfoResponse, Foreground),
     (GetProjectSymbols, Background),
     (GetProjectSymbolsResponse, Background),
+    (GetRawHover, Background),
+    (GetRawHoverResponse, Background),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
     (GetSignatureHelp, Background),
     (GetSignatureHelpResponse, Background),
     (GetSupermavenApiKey, Background),
     (GetSupermavenApiKeyResponse, Background),
+    (GetSymbolKind, Background),
+    (GetSymbolKindResponse, Background),
     (GetTypeDefinition, Background),
     (GetTypeDefinitionResponse, Background),
     (GetImplementation, Background),
@@ -312,6 +316,7 @@ messages!(
     (UsersResponse, Foreground),
     (LspExtExpandMacro, Background),
     (LspExtExpandMacroResponse, Background),
+    (LspExtHoverRange, Background),
     (SetRoomParticipantRole, Foreground),
     (BlameBuffer, Foreground),
     (BlameBufferResponse, Foreground),
@@ -383,9 +388,11 @@ request_messages!(
     (GetNotifications, GetNotificationsResponse),
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
+    (GetRawHover, GetRawHoverResponse),
     (GetReferences, GetReferencesResponse),
     (GetSignatureHelp, GetSignatureHelpResponse),
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
+    (GetSymbolKind, GetSymbolKindResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (LinkedEditingRange, LinkedEditingRangeResponse),
     (GetUsers, UsersResponse),
@@ -443,6 +450,7 @@ request_messages!(
     (UpdateProject, Ack),
     (UpdateWorktree, Ack),
     (LspExtExpandMacro, LspExtExpandMacroResponse),
+    (LspExtHoverRange, GetHoverResponse),
     (SetRoomParticipantRole, Ack),
     (BlameBuffer, BlameBufferResponse),
     (CreateDevServerProject, CreateDevServerProjectResponse),
@@ -484,8 +492,10 @@ entity_messages!(
     GetDocumentHighlights,
     GetHover,
     GetProjectSymbols,
+    GetRawHover,
     GetReferences,
     GetSignatureHelp,
+    GetSymbolKind,
     GetTypeDefinition,
     InlayHints,
     JoinProject,
@@ -523,6 +533,7 @@ entity_messages!(
     UpdateWorktree,
     UpdateWorktreeSettings,
     LspExtExpandMacro,
+    LspExtHoverRange,
     AdvertiseContexts,
     OpenContext,
     UpdateContext,
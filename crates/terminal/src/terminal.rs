@@ -50,7 +50,7 @@ use std::{
     ops::{Deref, Index, RangeInclusive},
     path::PathBuf,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use thiserror::Error;
 
@@ -104,6 +104,12 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The terminal's task exited and reported its exit code.
+    TaskFinished {
+        id: TaskId,
+        success: bool,
+        duration: Duration,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -608,6 +614,7 @@ pub struct TaskState {
     pub command_label: String,
     pub status: TaskStatus,
     pub completion_rx: Receiver<()>,
+    pub started_at: Instant,
 }
 
 /// A status of the current terminal tab's task.
@@ -1562,6 +1569,13 @@ impl Terminal {
                 task.status.register_terminal_exit();
             }
         };
+        if let TaskStatus::Completed { success } = task.status {
+            cx.emit(Event::TaskFinished {
+                id: task.id.clone(),
+                success,
+                duration: task.started_at.elapsed(),
+            });
+        }
 
         let (task_line, command_line) = task_summary(task, error_code);
         // SAFETY: the invocation happens on non `TaskStatus::Running` tasks, once,
@@ -749,6 +749,7 @@ fn subscribe_for_terminal_events(
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
+            Event::TaskFinished { .. } => {}
         });
     vec![terminal_subscription, terminal_events_subscription]
 }